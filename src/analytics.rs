@@ -0,0 +1,50 @@
+use log::info;
+
+use crate::events::{GameEvent, Listener};
+
+/// Tallies how each run went from its events, and logs a summary when it ends
+#[derive(Debug, Default)]
+pub struct Analytics {
+    answers: u32,
+    correct: u32,
+    timeouts: u32,
+    best_streak: u32,
+    hovers: u32,
+    runs: u32
+}
+
+impl Analytics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Listener for Analytics {
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::HoverChanged => self.hovers += 1,
+            GameEvent::AnswerCorrect { streak } => {
+                self.answers += 1;
+                self.correct += 1;
+                self.best_streak = self.best_streak.max(streak);
+            }
+            GameEvent::AnswerIncorrect { timed_out } => {
+                self.answers += 1;
+                if timed_out {
+                    self.timeouts += 1;
+                }
+            }
+            GameEvent::RunEnded { mode, score } => {
+                self.runs += 1;
+                let accuracy = if self.answers > 0 {self.correct as f32 / self.answers as f32 * 100.0} else {0.0};
+                info!(
+                    "Run {} ({mode:?}) ended on {score}: {}/{} correct ({accuracy:.0}%), {} timed out, best streak {}, {} hovers",
+                    self.runs, self.correct, self.answers, self.timeouts, self.best_streak, self.hovers
+                );
+                // the run count carries on across runs, everything else starts again
+                *self = Self { runs: self.runs, ..Self::default() };
+            }
+            GameEvent::SettingsChanged(_) | GameEvent::VolumeTested => {}
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+
+use rodio::OutputStreamHandle;
+use winit::{dpi::{PhysicalPosition, PhysicalSize}, event::{ElementState, MouseButton}, keyboard::{Key, NamedKey}, window::{Fullscreen, Window}};
+
+use crate::{config::{camera_distance, CONSOLE_KEY, DEFAULT_CLEARCOL}, configfile::GameConfig, console::{CommandHandler, CommandSpec, Console}, game::GameState, d2::{drawlist::DrawList, font::FontAtlas, layout::Layout, mouseutils::convert_mouse_coords, texture::TextureSource}, debugoverlay::DebugOverlay, gpustate::State, scenes::{title::Title, Frame, MouseState, SceneStack, Shared, UserSettings}, analytics::Analytics, audio::Audio, events::{EventBus, Listener}, savegame};
+#[cfg(debug_assertions)]
+use crate::assetwatcher::{AssetChange, AssetWatcher};
+
+const HELP: CommandSpec = CommandSpec { name: "help", usage: "", help: "Lists the commands that can be run right now" };
+
+/// Owns the window's GPU state and the scene stack, and routes everything between them
+pub struct App<'a> {
+    gpustate: State<'a>,
+    scenes: SceneStack,
+    shared: Shared,
+    audio: Option<Audio>, // None without sound
+    analytics: Analytics,
+    #[cfg(debug_assertions)]
+    asset_watcher: AssetWatcher,
+    overlay: DebugOverlay,
+    console: Console,
+    update_time: Duration, // spent updating since the last frame was drawn
+    draw_list: DrawList
+}
+
+impl<'a> App<'a> {
+    pub async fn new(window: Arc<Window>, tex_arr: Vec<TextureSource<'_>>, sound_stream_handle: Option<OutputStreamHandle>, font: Arc<FontAtlas>, config: GameConfig) -> Self {
+        let gpustate = State::new(window, tex_arr, DEFAULT_CLEARCOL, camera_distance(config.sidelen)).await;
+
+        let layout = Layout::new(gpustate.aspect_uniform.aspect);
+
+        let audio = sound_stream_handle.map(Audio::new);
+
+        let mut shared = Shared {
+            events: EventBus::new(),
+            config,
+            layout,
+            mouse: MouseState::new(),
+            best_score: 0,
+            font,
+            settings: UserSettings::default(),
+            saved_run: savegame::load().unwrap_or_else(|e| {
+                log::warn!("{e}, starting afresh");
+                None
+            })
+        };
+
+        shared.apply_settings();
+        let mut scenes = SceneStack::new(Box::new(Title::new(&shared)), &mut shared);
+        // straight into a run, with the title underneath to come back to
+        if let Some(mode) = shared.config.mode {
+            scenes.push(Box::new(GameState::new(&shared, mode)), &mut shared);
+        }
+
+        let overlay = DebugOverlay::new(shared.font.clone(), &shared.layout);
+        let console = Console::new(shared.font.clone(), &shared.layout);
+
+        Self {
+            gpustate,
+            scenes,
+            shared,
+            audio,
+            analytics: Analytics::new(),
+            #[cfg(debug_assertions)]
+            asset_watcher: AssetWatcher::new(),
+            overlay,
+            console,
+            update_time: Duration::ZERO,
+            draw_list: DrawList::new()
+        }
+    }
+    /// Advances the top scene by `dt` seconds, returns false once the app should quit
+    pub fn update(&mut self, dt: f32) -> bool {
+        let start = Instant::now();
+        #[cfg(debug_assertions)]
+        self.reload_assets(dt);
+        self.gpustate.update();
+        let running = self.scenes.update(&mut self.shared, dt);
+        self.console.update(dt);
+
+        // events from key presses since the last update go out with this update's
+        let mut listeners: Vec<&mut dyn Listener> = vec![&mut self.analytics];
+        if let Some(a) = &mut self.audio {
+            listeners.push(a);
+        }
+        self.shared.events.dispatch(&mut listeners);
+
+        if let Some(a) = &mut self.audio {
+            a.update(dt, self.scenes.music());
+        }
+        self.shared.mouse.end_update();
+        self.update_time += start.elapsed();
+        running
+    }
+    // swaps in anything whose file has changed, keeping what's loaded if the new version is broken
+    #[cfg(debug_assertions)]
+    fn reload_assets(&mut self, dt: f32) {
+        for change in self.asset_watcher.poll(dt) {
+            match change {
+                AssetChange::Texture(index, source) => {
+                    if let Err(e) = self.gpustate.reload_texture(index, &source) {
+                        log::error!("Failed to reload texture {index}: {e}");
+                    }
+                }
+                AssetChange::Sound(id, data) => {
+                    if let Some(a) = &mut self.audio {
+                        a.reload_sound(id, data);
+                    }
+                }
+                AssetChange::Shader(id, source) => {
+                    if let Err(e) = self.gpustate.reload_shader(id, &source) {
+                        log::error!("Failed to compile {}, keeping the old shader:\n{e}", id.asset().file);
+                    }
+                }
+            }
+        }
+    }
+    /// Draws the visible scenes, `alpha` being how far between the last two updates to interpolate
+    pub fn render(&mut self, alpha: f32) -> Result<(), wgpu::SurfaceError> {
+        let start = Instant::now();
+
+        // gathered before the scenes lend out their shapes
+        if self.overlay.wants_refresh() {
+            let mut info = vec![];
+            self.scenes.debug_info(&mut info);
+            self.overlay.refresh(&info, &self.gpustate.buffer_usage());
+        }
+
+        let mut frame = Frame::default();
+        self.scenes.render(&mut frame, alpha);
+        self.console.render(&mut frame);
+        self.overlay.render(&mut frame);
+
+        if let Some(g) = frame.grid {
+            self.gpustate.set_camera_distance(camera_distance(g.sidelen));
+        }
+        self.gpustate.set_bg_col(frame.clear_colour.unwrap_or(DEFAULT_CLEARCOL));
+        self.draw_list.build(&mut frame.shapes);
+
+        let built = start.elapsed();
+        let mut stats = self.gpustate.render((), frame.grid, &mut self.draw_list)?;
+        stats.cpu += built + std::mem::take(&mut self.update_time);
+        self.overlay.record(stats);
+
+        Ok(())
+    }
+    /// Passes a key press to the console or the top scene, `repeat` if it comes from the key being held,
+    /// returns false once the app should quit
+    pub fn key_pressed(&mut self, key: &Key, repeat: bool) -> bool {
+        // holding a toggle down shouldn't flick it on and off
+        if *key == Key::Named(NamedKey::F3) {
+            if !repeat {
+                self.overlay.toggle();
+            }
+            return true
+        }
+        if matches!(key, Key::Character(c) if c.as_str() == CONSOLE_KEY) {
+            if !repeat {
+                self.console.toggle();
+            }
+            return true
+        }
+        // everything typed goes to the console while it's open
+        if self.console.is_open() {
+            let mut commands = vec![];
+            self.commands(&mut commands);
+            if let Some(line) = self.console.input(key, &commands) {
+                self.run_command(&line, &commands);
+            }
+            return true
+        }
+        // held keys repeat for typing, but the game only wants the first press
+        if repeat {
+            return true
+        }
+        self.scenes.input(&mut self.shared, key)
+    }
+    // every command that can be run at the moment
+    fn commands(&self, out: &mut Vec<CommandSpec>) {
+        out.push(HELP);
+        self.console.commands(out);
+        self.overlay.commands(out);
+        self.scenes.commands(out);
+    }
+    // runs a line typed into the console, printing what came of it
+    fn run_command(&mut self, line: &str, commands: &[CommandSpec]) {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return
+        };
+        let args: Vec<&str> = words.collect();
+
+        let result = match name {
+            "help" => Some(Ok(commands.iter()
+                .map(|c| match c.usage {
+                    "" => format!("{} - {}", c.name, c.help),
+                    u => format!("{} {u} - {}", c.name, c.help)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"))),
+            _ => self.console.run_command(&mut self.shared, name, &args)
+                .or_else(|| self.overlay.run_command(&mut self.shared, name, &args))
+                .or_else(|| self.scenes.run_command(&mut self.shared, name, &args))
+        };
+        match result {
+            Some(Ok(s)) => self.console.print(&s),
+            Some(Err(e)) => self.console.print(&format!("Error: {e}")),
+            None => self.console.print(&format!("Unknown command '{name}', type help to list them"))
+        }
+    }
+    /// Called as the window closes, so anything in progress can be saved
+    pub fn close(&mut self) {
+        self.scenes.close(&mut self.shared);
+    }
+    pub fn window(&self) -> &Window {
+        self.gpustate.window()
+    }
+    pub fn resize_window(&mut self, s: PhysicalSize<u32>) {
+        self.gpustate.resize(s);
+        self.shared.layout = Layout::new(self.gpustate.aspect_uniform.aspect);
+        self.scenes.relayout(&self.shared.layout);
+        self.overlay.relayout(&self.shared.layout);
+        self.console.relayout(&self.shared.layout);
+    }
+    pub fn toggle_fullscreen(&mut self) {
+        let w = self.gpustate.window();
+        if w.fullscreen().is_some() {
+            w.set_fullscreen(None);
+        } else {
+            w.set_fullscreen(Some(Fullscreen::Borderless(None)));
+        }
+    }
+    pub fn refresh_window(&mut self) {
+        self.gpustate.fake_resize();
+    }
+    pub fn mouse_pos_update(&mut self, p: PhysicalPosition<f64>) {
+        self.shared.mouse.pos = convert_mouse_coords(p, self.gpustate.size, self.gpustate.aspect_uniform.aspect);
+    }
+    pub fn mouse_click_update(&mut self, state: ElementState, button: MouseButton) {
+        if button == MouseButton::Left {
+            self.shared.mouse.left = state == ElementState::Pressed;
+        }
+    }
+}
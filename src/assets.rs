@@ -0,0 +1,119 @@
+use std::{borrow::Cow, io::ErrorKind, path::PathBuf};
+
+use image::ImageError;
+use log::warn;
+
+use crate::{config::{ASSET_DIR, NET_TEX_INDEX}, d2::texture::TextureSource};
+
+// Every file the game uses is listed here along with a copy built into the game.
+// The copy in the asset directory is used when there is one, so art can change without a rebuild,
+// and the built in copy is used when the file is missing or broken.
+
+/// Every sound effect the game ships with, looked up in SOUNDS
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundId {
+    Hover,
+    #[allow(dead_code)] // shipped, but nothing plays it at the moment
+    Press,
+    Correct,
+    Incorrect
+}
+
+/// A sound effect's file and how it's played
+pub struct SoundAsset {
+    pub id: SoundId,
+    pub file: &'static str, // relative to the asset directory
+    pub data: &'static [u8],
+    pub volume: f32,
+    pub max_concurrent: usize // how many can play at once before the oldest is cut off
+}
+
+pub const SOUNDS: [SoundAsset; 4] = [
+    SoundAsset { id: SoundId::Hover, file: "click.wav", data: include_bytes!("res/click.wav"), volume: 0.1, max_concurrent: 3 },
+    SoundAsset { id: SoundId::Press, file: "click2.wav", data: include_bytes!("res/click2.wav"), volume: 0.3, max_concurrent: 3 },
+    SoundAsset { id: SoundId::Correct, file: "dingup.wav", data: include_bytes!("res/dingup.wav"), volume: 0.1, max_concurrent: 2 },
+    SoundAsset { id: SoundId::Incorrect, file: "dinglow.wav", data: include_bytes!("res/dinglow.wav"), volume: 0.3, max_concurrent: 2 },
+];
+
+/// A texture loaded from a file, the rest are generated from the font
+pub struct TextureAsset {
+    pub index: u32, // in the texture array
+    pub file: &'static str,
+    pub data: &'static [u8]
+}
+
+pub const TEXTURES: [TextureAsset; 1] = [
+    TextureAsset { index: NET_TEX_INDEX, file: "black.png", data: include_bytes!("res/black.png") },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderId {
+    D3,
+    D2
+}
+
+pub struct ShaderAsset {
+    pub id: ShaderId,
+    pub file: &'static str,
+    pub source: &'static str
+}
+
+pub const SHADERS: [ShaderAsset; 2] = [
+    ShaderAsset { id: ShaderId::D3, file: "shaders/d3.wgsl", source: include_str!("res/shaders/d3.wgsl") },
+    ShaderAsset { id: ShaderId::D2, file: "shaders/d2.wgsl", source: include_str!("res/shaders/d2.wgsl") },
+];
+
+impl ShaderId {
+    pub fn asset(self) -> &'static ShaderAsset {
+        SHADERS.iter().find(|s| s.id == self).expect("Every shader is in SHADERS")
+    }
+}
+
+/// Where asset files are looked for, which can be moved with CUBE_GAME_ASSET_DIR
+pub fn asset_dir() -> PathBuf {
+    std::env::var_os("CUBE_GAME_ASSET_DIR").map_or_else(|| ASSET_DIR.into(), PathBuf::from)
+}
+
+/// The file from the asset directory, or the built in copy if it can't be read
+pub fn load(file: &str, embedded: &'static [u8]) -> Cow<'static, [u8]> {
+    let path = asset_dir().join(file);
+    match std::fs::read(&path) {
+        Ok(data) => Cow::Owned(data),
+        Err(e) => {
+            // not having the file at all is normal, and not worth mentioning
+            if e.kind() != ErrorKind::NotFound {
+                warn!("Failed to read {}, using the built in copy: {e}", path.display());
+            }
+            Cow::Borrowed(embedded)
+        }
+    }
+}
+
+/// Decodes a texture read from disk
+pub fn decode_texture(data: &[u8]) -> Result<TextureSource<'static>, ImageError> {
+    Ok(TextureSource::Rgba(image::load_from_memory(data)?.to_rgba8()))
+}
+
+pub fn load_texture(asset: &TextureAsset) -> TextureSource<'static> {
+    match load(asset.file, asset.data) {
+        Cow::Borrowed(b) => TextureSource::Encoded(b),
+        Cow::Owned(data) => decode_texture(&data).unwrap_or_else(|e| {
+            warn!("Failed to decode {}, using the built in copy: {e}", asset.file);
+            TextureSource::Encoded(asset.data)
+        })
+    }
+}
+
+/// A shader's source. Whether it compiles is only found out when it's used.
+pub fn load_shader(asset: &ShaderAsset) -> Cow<'static, str> {
+    match load(asset.file, asset.source.as_bytes()) {
+        Cow::Borrowed(_) => Cow::Borrowed(asset.source),
+        Cow::Owned(data) => match String::from_utf8(data) {
+            Ok(s) => Cow::Owned(s),
+            Err(e) => {
+                warn!("{} isn't valid text, using the built in copy: {e}", asset.file);
+                Cow::Borrowed(asset.source)
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::{path::{Path, PathBuf}, time::SystemTime};
+
+use log::{info, warn};
+
+use crate::{assets::{asset_dir, decode_texture, ShaderId, SoundId, SHADERS, SOUNDS, TEXTURES}, config::ASSET_POLL_INTERVAL, d2::texture::TextureSource};
+
+// Debug builds only. Files are checked by their modified time every ASSET_POLL_INTERVAL,
+// which is plenty quick enough for editing by hand and needs nothing from the OS.
+
+/// An asset whose file has changed, read and ready to be swapped in
+pub enum AssetChange {
+    Texture(u32, TextureSource<'static>),
+    Sound(SoundId, Vec<u8>),
+    Shader(ShaderId, String)
+}
+
+// what a watched file is, by its index in the manifest
+#[derive(Clone, Copy, Debug)]
+enum Watched {
+    Texture(usize),
+    Sound(usize),
+    Shader(usize)
+}
+
+struct WatchedFile {
+    asset: Watched,
+    path: PathBuf,
+    modified: Option<SystemTime> // None while the file isn't there
+}
+
+/// Looks out for asset files changing on disk
+pub struct AssetWatcher {
+    files: Vec<WatchedFile>,
+    since_poll: f32
+}
+
+impl AssetWatcher {
+    pub fn new() -> Self {
+        let dir = asset_dir();
+        let listed = TEXTURES.iter().enumerate().map(|(i, a)| (Watched::Texture(i), a.file))
+            .chain(SOUNDS.iter().enumerate().map(|(i, a)| (Watched::Sound(i), a.file)))
+            .chain(SHADERS.iter().enumerate().map(|(i, a)| (Watched::Shader(i), a.file)));
+
+        let files = listed.map(|(asset, file)| {
+            let path = dir.join(file);
+            WatchedFile { asset, modified: modified(&path), path }
+        }).collect();
+
+        info!("Watching {} for asset changes", dir.display());
+        Self { files, since_poll: 0.0 }
+    }
+
+    /// Advances by `dt` seconds, returning whatever has changed since it last looked
+    pub fn poll(&mut self, dt: f32) -> Vec<AssetChange> {
+        self.since_poll += dt;
+        if self.since_poll < ASSET_POLL_INTERVAL {
+            return vec![]
+        }
+        self.since_poll = 0.0;
+
+        let mut changes = vec![];
+        for f in self.files.iter_mut() {
+            let m = modified(&f.path);
+            if m == f.modified {
+                continue
+            }
+            f.modified = m;
+            // deleted files leave what's loaded alone
+            if m.is_none() {
+                continue
+            }
+
+            let data = match std::fs::read(&f.path) {
+                Ok(d) => d,
+                Err(e) => {
+                    warn!("Failed to read {}: {e}", f.path.display());
+                    continue
+                }
+            };
+            info!("Reloading {}", f.path.display());
+            let change = match f.asset {
+                Watched::Texture(i) => match decode_texture(&data) {
+                    Ok(t) => AssetChange::Texture(TEXTURES[i].index, t),
+                    Err(e) => {
+                        warn!("Failed to decode {}, keeping the old texture: {e}", f.path.display());
+                        continue
+                    }
+                }
+                Watched::Sound(i) => AssetChange::Sound(SOUNDS[i].id, data),
+                Watched::Shader(i) => match String::from_utf8(data) {
+                    Ok(s) => AssetChange::Shader(SHADERS[i].id, s),
+                    Err(e) => {
+                        warn!("{} isn't valid text, keeping the old shader: {e}", f.path.display());
+                        continue
+                    }
+                }
+            };
+            changes.push(change);
+        }
+        changes
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
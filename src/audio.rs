@@ -0,0 +1,49 @@
+use rodio::OutputStreamHandle;
+
+use crate::{assets::{SoundId, SOUNDS}, events::{GameEvent, Listener}, music::{Music, MusicCue}, soundmanager::{Channel, SoundManager}};
+
+/// Everything that makes a noise, played in response to game events
+pub struct Audio {
+    sm: SoundManager,
+    music: Music
+}
+
+impl Audio {
+    pub fn new(osh: OutputStreamHandle) -> Self {
+        let mut sm = SoundManager::new(&SOUNDS, osh);
+        let music = Music::new(&mut sm);
+        Self { sm, music }
+    }
+
+    /// Moves the music towards `cue` and advances fades by `dt` seconds
+    pub fn update(&mut self, dt: f32, cue: Option<MusicCue>) {
+        self.music.update(dt, cue, &mut self.sm);
+        self.sm.update(dt);
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn reload_sound(&mut self, id: SoundId, data: Vec<u8>) {
+        self.sm.replace(id, data);
+    }
+
+    fn play(&mut self, id: SoundId) {
+        self.sm.play(id, Channel::Sfx);
+    }
+}
+
+impl Listener for Audio {
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::HoverChanged | GameEvent::VolumeTested => self.play(SoundId::Hover),
+            GameEvent::AnswerCorrect { .. } => self.play(SoundId::Correct),
+            GameEvent::AnswerIncorrect { .. } => self.play(SoundId::Incorrect),
+            GameEvent::SettingsChanged(s) => {
+                self.sm.set_muted(!s.sound_on);
+                self.sm.set_master_volume(s.volume);
+                self.sm.set_channel_volume(Channel::Sfx, s.sfx_volume);
+                self.sm.set_channel_volume(Channel::Music, s.music_volume);
+            }
+            GameEvent::RunEnded { .. } => {}
+        }
+    }
+}
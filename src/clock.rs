@@ -0,0 +1,135 @@
+use std::time::{Duration, Instant};
+
+/// Source of time for the simulation, so that real time can be swapped out
+/// for something deterministic when replaying or testing
+pub trait Clock {
+    /// Time elapsed since the clock was created
+    fn now(&mut self) -> Duration;
+}
+
+/// Follows the real wall clock
+pub struct SystemClock {
+    start: Instant
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self { start: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&mut self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Moves forward by exactly `frame_time` every time it is read, regardless of how
+/// long frames actually take, so runs are reproducible
+pub struct SteppedClock {
+    now: Duration,
+    frame_time: Duration
+}
+
+impl SteppedClock {
+    pub fn new(frame_time: Duration) -> Self {
+        Self { now: Duration::ZERO, frame_time }
+    }
+}
+
+impl Clock for SteppedClock {
+    fn now(&mut self) -> Duration {
+        let t = self.now;
+        self.now += self.frame_time;
+        t
+    }
+}
+
+/// Turns variable length frames into a whole number of fixed length simulation steps.
+/// Leftover time is carried over to the next frame, and is exposed through `alpha`
+/// so rendering can interpolate between the last two steps.
+pub struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+    last: Option<Duration>,
+    max_steps: u32
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration, max_steps: u32) -> Self {
+        Self {
+            step,
+            accumulator: Duration::ZERO,
+            last: None,
+            max_steps
+        }
+    }
+
+    /// Feeds in the current time, returns how many steps should be simulated this frame
+    pub fn advance(&mut self, now: Duration) -> u32 {
+        let elapsed = match self.last {
+            Some(l) => now.saturating_sub(l),
+            None => Duration::ZERO
+        };
+        self.last = Some(now);
+        self.accumulator += elapsed;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+
+        // after a long stall (window dragged, breakpoint hit) don't try to catch up
+        // all at once, just drop the excess time
+        if steps > self.max_steps {
+            steps = self.max_steps;
+            self.accumulator = Duration::ZERO;
+        }
+
+        steps
+    }
+
+    /// Length of one step in seconds
+    pub fn dt(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    /// How far between the previous and the next step we are, from 0 to 1
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(10);
+
+    // (steps, alpha) for each of `frames` frames
+    fn run(frame_time: Duration, frames: usize) -> Vec<(u32, f32)> {
+        let mut clock = SteppedClock::new(frame_time);
+        let mut timestep = FixedTimestep::new(STEP, 8);
+        (0..frames).map(|_| (timestep.advance(clock.now()), timestep.alpha())).collect()
+    }
+
+    #[test]
+    fn stepped_clock_is_deterministic() {
+        let a = run(Duration::from_millis(25), 6);
+        assert_eq!(a, run(Duration::from_millis(25), 6));
+        // 25ms frames are two and a half steps, the halves adding up to a third step every other frame
+        assert_eq!(a, vec![(0, 0.0), (2, 0.5), (3, 0.0), (2, 0.5), (3, 0.0), (2, 0.5)]);
+    }
+
+    #[test]
+    fn long_stall_drops_the_backlog() {
+        let mut timestep = FixedTimestep::new(STEP, 3);
+        assert_eq!(timestep.advance(Duration::ZERO), 0);
+        assert_eq!(timestep.advance(Duration::from_millis(105)), 3);
+        assert_eq!(timestep.alpha(), 0.0);
+        // carries on from the stall rather than catching up
+        assert_eq!(timestep.advance(Duration::from_millis(115)), 1);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+}
@@ -1,44 +1,222 @@
+use std::ops::RangeInclusive;
+
 use cgmath::Vector3;
 
+use crate::d2::layout::{Anchor, Anchored};
+
 // Window
 pub const DEFAULT_CLEARCOL: [f32;4] = [0.8,0.8,0.95,1.0];
-pub const WINDOW_WIDTH: u32 = 1280;
+pub const WINDOW_WIDTH: u32 = 1280; // the default, the config file can change it
 pub const WINDOW_HEIGHT: u32 = 720;
+pub const WINDOW_MIN_WIDTH: u32 = 320;
+pub const WINDOW_MIN_HEIGHT: u32 = 240;
 pub const MSAA_COUNT: u32 = 4; // crashes on start if this isn't 4
 
 // ColumnGrid
-pub const COLUMN_GRID_SIDELEN: u8 = 3;
-pub const COLUMN_GRID_Z_OFFSET: f32 = - (COLUMN_GRID_SIDELEN as f32 * 1.1);
-pub const COLUMN_GRID_POS: Vector3<f32> = Vector3::new(0.0,0.0,COLUMN_GRID_Z_OFFSET);
-pub const COLUMN_GRID_SLIDE_MULTIPLIER: f32 = 0.26; // higher == faster
+pub const COLUMN_GRID_SIDELEN: u8 = 3; // the default, the config file can change it
+pub const COLUMN_GRID_SLIDE_DISTANCE: f32 = 30.0; // how far off screen the grid slides to and from
+
+/// Where a grid rests, pushed back as it gets bigger
+pub fn column_grid_pos(sidelen: u8) -> Vector3<f32> {
+    Vector3::new(0.0, 0.0, -(sidelen as f32 * 1.1))
+}
 
 // Camera
 pub const CAMERA_FOV: f32 = 15.0;
-pub const CAMERA_DISTANCE: f32 = 15.0 + (COLUMN_GRID_SIDELEN as f32 * 3.0);
+
+/// Far enough back to fit a grid of this size in
+pub fn camera_distance(sidelen: u8) -> f32 {
+    15.0 + sidelen as f32 * 3.0
+}
 
 // Nets
 pub const NET_TEX_INDEX: u32 = 0;
-pub const NET_COUNT: usize = 4;
-pub const NET_SCALE: f32 = 0.5 / COLUMN_GRID_SIDELEN as f32;
-pub const NET_EDGE_THICKNESS: f32 = NET_SCALE / 10.0;
+pub const NET_COUNT: usize = 4; // fixed, there's an answer key for each
+
+/// Size of a net's squares at full size, so nets of any grid fit the same space
+pub fn net_scale(sidelen: u8) -> f32 {
+    0.5 / sidelen as f32
+}
+
+pub fn net_edge_thickness(sidelen: u8) -> f32 {
+    net_scale(sidelen) / 10.0
+}
+
+pub const NET_EDGE_COLOUR: [f32;3] = [0.0, 0.0, 0.0];
 pub const NET_LAYER: u8 = 4;
-pub const NET_GAP: f32 = 0.86;
-pub const NET_Y_OFFSET: f32 = -0.52;
+pub const NET_GAP: f32 = 0.86; // distance between net centres at full size
+pub const NET_AREA_TOP: f32 = -0.09; // nets are laid out between these two heights
+pub const NET_AREA_BOTTOM: f32 = -0.95;
+pub const NET_LAYOUT_MARGIN: f32 = 0.05; // from the sides of the window
 pub const NET_RANDOM_CHANGES: u32 = 2;
 
 // Buttons
-pub const BUTTON_TEX_INDEX: u32 = 1;
+pub const BUTTON_SIZE: f32 = 0.75;
+pub const BUTTON_CORNER_RADIUS: f32 = 0.14; // at BUTTON_SIZE, scales with the button
+pub const BUTTON_COLOUR: [f32;3] = [0.0, 0.0, 0.0];
 
 // Timer
 pub const TIMER_TEX_INDEX: u32 = 0;
-pub const TIMER_DEFAULT_MAX: f32 = 480.0; //frames
+pub const TIMER_DEFAULT_MAX: f32 = 8.0; // seconds, the default, the config file can change it
+pub const TIMER_MIN: f32 = 1.0 / 6.0; // seconds, the round ends when the timer gets this low
 pub const TIMER_REDUCTION_MULTIPLER: f32 = 0.99;
 pub const TIMER_OPACITY_MAX: f32 = 0.5;
+pub const TIMER_ANCHOR: Anchored = Anchored::new(Anchor::Centre, [0.0, 0.35]);
+
+// HUD
+// numbers are right aligned with a digit's worth of padding, hence the slightly odd margins
+pub const COUNTER_ANCHOR: Anchored = Anchored::new(Anchor::TopRight, [-0.022, 0.175]);
+pub const BEST_COUNTER_ANCHOR: Anchored = Anchored::new(Anchor::TopRight, [0.018, 0.4]);
+// left aligned numbers are positioned by the centre of their first character
+pub const TIME_ANCHOR: Anchored = Anchored::new(Anchor::TopLeft, [0.1, 0.175]);
+pub const ACCURACY_ANCHOR: Anchored = Anchored::new(Anchor::TopLeft, [0.06, 0.4]);
+
+// Two player
+pub const HOT_SEAT_KEYS: [[&str; NET_COUNT]; 2] = [["1", "2", "3", "4"], ["7", "8", "9", "0"]]; // each player's answers, in net order
+pub const HOT_SEAT_MOUSE_PLAYER: usize = 1; // clicking an answer answers for this player
+pub const HOT_SEAT_WIN_SCORE: u32 = 7; // rounds won to take the match
+pub const HOT_SEAT_COLOURS: [[f32;4]; 2] = [[0.55, 0.7, 1.0, 1.0], [1.0, 0.75, 0.45, 1.0]]; // flashed when each player wins a round
+// scores where the streak and time go in one player, streaks under them
+pub const HOT_SEAT_SCORE_ANCHORS: [Anchored; 2] = [Anchored::new(Anchor::TopLeft, [0.1, 0.175]), COUNTER_ANCHOR];
+pub const HOT_SEAT_STREAK_ANCHORS: [Anchored; 2] = [ACCURACY_ANCHOR, BEST_COUNTER_ANCHOR];
+pub const HOT_SEAT_NAME_ANCHORS: [Anchored; 2] = [Anchored::new(Anchor::TopLeft, [0.45, 0.6]), Anchored::new(Anchor::TopRight, [0.45, 0.6])];
+
+// Text
+pub const FONT_TEX_INDEX: u32 = 16; // the atlas goes after the digit and symbol textures
+pub const FONT_RASTER_PX: u32 = 64; // pixels per em in the atlas
+pub const FONT_GLYPH_PADDING: u32 = 2; // pixels, so glyphs don't bleed into each other
+pub const FONT_ATLAS_WIDTH: u32 = 1024;
+pub const TEXT_COLOUR: [f32;3] = [0.1, 0.1, 0.15];
+
+// Menus
+pub const MENU_LAYER: u8 = 8; // above everything in game, so menus can overlay it
+pub const MENU_BUTTON_SIZE: f32 = 0.4;
+pub const MENU_BUTTON_SPACING: f32 = 0.55; // between button centres
+pub const MENU_ROW_ANCHOR: Anchored = Anchored::new(Anchor::Centre, [0.0, -0.3]);
+pub const MENU_SCORE_ANCHOR: Anchored = Anchored::new(Anchor::Centre, [0.0, 0.35]);
+pub const MENU_BEST_ANCHOR: Anchored = Anchored::new(Anchor::Centre, [0.0, 0.02]);
+pub const MENU_IMPROVEMENT_ANCHOR: Anchored = Anchored::new(Anchor::Centre, [0.45, 0.5]);
+pub const MENU_HEADING_ANCHOR: Anchored = Anchored::new(Anchor::Top, [0.0, 0.3]);
+pub const MENU_SOUND_ROW_ANCHOR: Anchored = Anchored::new(Anchor::Centre, [0.0, 0.48]);
+pub const MENU_VOLUME_ROW_ANCHOR: Anchored = Anchored::new(Anchor::Centre, [0.0, 0.32]);
+pub const MENU_SFX_ROW_ANCHOR: Anchored = Anchored::new(Anchor::Centre, [0.0, 0.16]);
+pub const MENU_MUSIC_ROW_ANCHOR: Anchored = Anchored::new(Anchor::Centre, [0.0, 0.0]);
+pub const MENU_HINT_ANCHOR: Anchored = Anchored::new(Anchor::Bottom, [0.0, 0.15]);
+pub const MENU_HEADING_SIZE: f32 = 0.22;
+pub const MENU_LABEL_SIZE: f32 = 0.08;
+pub const PAUSE_DIM_OPACITY: f32 = 0.6;
+
+// Widgets
+pub const UI_IDLE_OPACITY: f32 = 0.2;
+pub const UI_HOVER_OPACITY: f32 = 0.3;
+pub const UI_PRESSED_OPACITY: f32 = 0.6;
+pub const UI_DISABLED_OPACITY: f32 = 0.1;
+pub const UI_TEXT_OPACITY: f32 = 0.8;
+pub const UI_ACCENT_COLOUR: [f32;3] = [0.25, 0.35, 0.8]; // focus rings, and the on parts of toggles and sliders
+pub const UI_FOCUS_RING_WIDTH: f32 = 0.012;
+pub const UI_FOCUS_RING_GAP: f32 = 0.025; // between a widget and its focus ring
+pub const UI_ROW_WIDTH: f32 = 1.3; // toggles and sliders, label and control together
+pub const UI_ROW_HEIGHT: f32 = 0.16;
+pub const UI_ROW_GAP: f32 = 0.05; // either side of the centre, between a row's label and its control
+pub const UI_TOGGLE_WIDTH: f32 = 0.18;
+pub const UI_TOGGLE_HEIGHT: f32 = 0.09;
+pub const UI_SLIDER_WIDTH: f32 = 0.55;
+pub const UI_SLIDER_HEIGHT: f32 = 0.03;
+pub const UI_SLIDER_KNOB_RADIUS: f32 = 0.04;
+pub const UI_SLIDER_STEP: f32 = 0.1; // per arrow key press
 
 // Numbers
-pub const NUMBER_TEX_INDEX_START: u32 = 2;
+pub const NUMBER_TEX_INDEX_START: u32 = 1; // digits 0-9, then NUMBER_SYMBOLS in order
+pub const NUMBER_SYMBOLS: &str = "+-.:%";
+pub const NUMBER_NARROW_ADVANCE: f32 = 0.5; // fraction of a digit's width taken up by '.' and ':'
+pub const NUMBER_ROLL_LEN: f32 = 0.4; // seconds for a counter to roll to a new value
+pub const DIGIT_TEX_WIDTH: u32 = 128; // pixels
+pub const DIGIT_TEX_HEIGHT: u32 = 256;
+pub const DIGIT_TEX_BLUR: f32 = 1.0; // sigma, softens the edges a touch
+
+// Music
+pub const MUSIC_SAMPLE_RATE: u32 = 32_000; // generated at startup, so kept modest
+pub const MUSIC_VOLUME: f32 = 0.35; // relative to the effects
+pub const MUSIC_CROSSFADE_LEN: f32 = 2.0; // seconds
+pub const MUSIC_STEM_MAX_CONCURRENT: usize = 2; // one fading out while the same track fades back in
+pub const MUSIC_INTENSITY_RATE: f32 = 0.5; // fastest the intensity layer changes, per second
+pub const MUSIC_STREAK_FULL: u32 = 10; // streak at which the streak alone gives full intensity
+
+// Animations (seconds)
+pub const ANIM_SLIDE_OUT_LEN: f32 = 1.0 / 3.0;
+pub const ANIM_SLIDE_IN_LEN: f32 = 1.0 / 3.0;
+pub const ANIM_BETWEEN_SLIDES: f32 = 1.0 / 12.0;
+pub const ANIM_BACKGROUND_FLASH_LEN: f32 = 0.5;
+pub const ANIM_MENU_FADE_LEN: f32 = 0.2;
+pub const ANIM_CONSOLE_SLIDE_LEN: f32 = 0.15;
+
+// Timing
+pub const UPDATE_RATE: f32 = 60.0; // simulation steps per second
+pub const MAX_UPDATES_PER_FRAME: u32 = 8;
+
+// Vector shapes
+pub const SOLID_TEX_INDEX: u32 = u32::MAX; // drawn in the vertex colour, without a texture
+pub const SHAPE_TOLERANCE: f32 = 0.0005; // furthest a curve can stray from the true shape, well under a pixel
+pub const SHAPE_MAX_SEGMENTS: usize = 256; // per curve
+pub const SHAPE_MITER_LIMIT: f32 = 4.0; // in half widths, sharper corners are bevelled
+
+// GPU buffers
+pub const GPU_BUFFER_INITIAL_SIZE: u64 = 65_536; // bytes
+pub const GPU_BUFFER_GROWTH_HEADROOM: f32 = 1.5;
+pub const GPU_BUFFER_SHRINK_THRESHOLD: f32 = 0.25; // fraction of capacity in use
+pub const GPU_BUFFER_SHRINK_AFTER_FRAMES: u32 = 600;
+
+// Assets
+// looked for on disk first, falling back on the copies built into the game
+#[cfg(debug_assertions)]
+pub const ASSET_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/res"); // so edits to the repo's copies show straight away
+#[cfg(not(debug_assertions))]
+pub const ASSET_DIR: &str = "assets";
+#[cfg(debug_assertions)]
+pub const ASSET_POLL_INTERVAL: f32 = 0.5; // seconds between checking for changed files, in debug builds
+
+// Config file
+pub const CONFIG_DIR_NAME: &str = "cube_game"; // within the user's config and data directories
+pub const SAVE_FILE_NAME: &str = "run.json"; // in the data directory, the run to carry on with
+pub const CONFIG_WINDOW_WIDTH_RANGE: RangeInclusive<u32> = WINDOW_MIN_WIDTH..=16384;
+pub const CONFIG_WINDOW_HEIGHT_RANGE: RangeInclusive<u32> = WINDOW_MIN_HEIGHT..=16384;
+pub const CONFIG_SIDELEN_RANGE: RangeInclusive<u8> = 3..=8; // smaller grids can't make puzzles different enough
+pub const CONFIG_TIMER_MAX_RANGE: RangeInclusive<f32> = 1.0..=60.0;
+pub const CONFIG_TIMER_REDUCTION_RANGE: RangeInclusive<f32> = 0.5..=1.0;
+
+// Debug overlay
+pub const DEBUG_OVERLAY_LAYER: u8 = 14; // over menus, the text and graph go one above
+pub const DEBUG_OVERLAY_ANCHOR: Anchored = Anchored::new(Anchor::TopLeft, [0.05, 0.05]); // the panel's top left corner
+pub const DEBUG_OVERLAY_WIDTH: f32 = 1.2;
+pub const DEBUG_OVERLAY_PADDING: f32 = 0.03;
+pub const DEBUG_OVERLAY_TEXT_SIZE: f32 = 0.045;
+pub const DEBUG_OVERLAY_LINE_SPACING: f32 = 0.055;
+pub const DEBUG_OVERLAY_COLOUR: [f32;3] = [0.05, 0.05, 0.08];
+pub const DEBUG_OVERLAY_OPACITY: f32 = 0.75;
+pub const DEBUG_OVERLAY_TEXT_COLOUR: [f32;3] = [0.9, 0.9, 0.9];
+pub const DEBUG_OVERLAY_REFRESH: f32 = 0.25; // seconds between updating the text, so it can be read
+pub const DEBUG_GRAPH_FRAMES: usize = 240;
+pub const DEBUG_GRAPH_HEIGHT: f32 = 0.3;
+pub const DEBUG_GRAPH_MAX_MS: f32 = 50.0; // frame times are clipped to the top of the graph
+pub const DEBUG_GRAPH_TARGET_MS: f32 = 1000.0 / 60.0; // marked with a line across the graph
+pub const DEBUG_GRAPH_LINE_WIDTH: f32 = 0.006;
+pub const DEBUG_GRAPH_COLOUR: [f32;3] = [0.3, 0.9, 0.4];
+pub const DEBUG_GRAPH_TARGET_COLOUR: [f32;3] = [0.9, 0.4, 0.3];
+
+// Console
+pub const CONSOLE_KEY: &str = "`"; // opens and closes it, never typed into it
+pub const CONSOLE_LAYER: u8 = 12; // over menus, under the debug overlay
+pub const CONSOLE_HEIGHT: f32 = 0.9; // from the top of the window
+pub const CONSOLE_PADDING: f32 = 0.03;
+pub const CONSOLE_TEXT_SIZE: f32 = 0.05;
+pub const CONSOLE_LINE_SPACING: f32 = 0.06;
+pub const CONSOLE_LINES: usize = 12; // output kept, as many lines as fit above the prompt
+pub const CONSOLE_HISTORY: usize = 50; // commands that can be brought back with up and down
+pub const CONSOLE_COLOUR: [f32;3] = [0.05, 0.05, 0.08];
+pub const CONSOLE_OPACITY: f32 = 0.85;
+pub const CONSOLE_TEXT_COLOUR: [f32;3] = [0.9, 0.9, 0.9];
 
-// Animations
-pub const ANIM_SLIDE_OUT_LEN_FRAMES: f32 = 20.0;
-pub const ANIM_SLIDE_IN_LEN_FRAMES: f32 = 20.0;
-pub const ANIM_BETWEEN_SLIDES_FRAMES: f32 = 5.0;
+// GPU profiling
+pub const PROFILER_WINDOW: usize = 120; // frames that timings are averaged over
+pub const PROFILER_READBACKS: usize = 3; // frames of timestamps that can be on their way back at once
+pub const PROFILER_LOG_INTERVAL: u32 = 600; // frames between logging the averages
@@ -0,0 +1,247 @@
+use std::{fmt, ops::RangeInclusive, path::{Path, PathBuf}};
+
+use serde::Deserialize;
+
+use crate::{config::*, game::GameMode};
+
+// The tunables that can be changed without a rebuild. They're read from config.toml in the
+// user's config directory, then anything given on the command line goes on top.
+// Everything else stays in config.rs.
+
+const USAGE: &str = "\
+Usage: rotate_game_v2 [options]
+
+Options:
+  --config <path>      read this config file instead of the usual one
+  --seed <number>      make every run's puzzles the same, for practice or comparing scores
+  --mode <mode>        skip the menus and start a run, classic, sudden-death or hot-seat
+  --sidelen <number>   columns along each side of the grid
+  --fullscreen         start fullscreen
+  --windowed           start in a window, whatever the config file says
+  -h, --help           show this and quit";
+
+/// The game's settings from the config file and command line, checked to be in range
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub fullscreen: bool,
+    pub sidelen: u8,
+    pub timer_max: f32, // seconds for the first round, and after a wrong answer
+    pub timer_reduction: f32, // the time for the next round is multiplied by this after a right answer
+    pub seed: Option<u64>, // None for different puzzles every run
+    pub mode: Option<GameMode> // None to start at the title screen
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            window_width: WINDOW_WIDTH,
+            window_height: WINDOW_HEIGHT,
+            fullscreen: false,
+            sidelen: COLUMN_GRID_SIDELEN,
+            timer_max: TIMER_DEFAULT_MAX,
+            timer_reduction: TIMER_REDUCTION_MULTIPLER,
+            seed: None,
+            mode: None
+        }
+    }
+}
+
+/// Why the game can't start with the config it was given
+#[derive(Debug)]
+pub enum ConfigError {
+    Help, // not really an error, but the game shouldn't start either
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Flag(String),
+    OutOfRange { name: &'static str, value: String, range: String, source: String }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Help => write!(f, "{USAGE}"),
+            ConfigError::Read(p, e) => write!(f, "Couldn't read {}: {e}", p.display()),
+            ConfigError::Parse(p, e) => write!(f, "Error in {}:\n{e}", p.display()),
+            ConfigError::Flag(m) => write!(f, "{m}\n\n{USAGE}"),
+            ConfigError::OutOfRange { name, value, range, source } =>
+                write!(f, "{name} is {value} {source}, but needs to be between {range}")
+        }
+    }
+}
+
+impl GameConfig {
+    /// Reads the config file if there is one, then applies the command line `args`
+    /// (without the program name)
+    pub fn load(args: impl IntoIterator<Item = String>) -> Result<Self, ConfigError> {
+        let args: Vec<String> = args.into_iter().collect();
+
+        // the config file has to be found before anything else on the command line means anything
+        let explicit = args.iter().position(|a| a == "--config")
+            .map(|i| args.get(i + 1).map(PathBuf::from).ok_or_else(|| missing("--config")))
+            .transpose()?;
+
+        let mut config = match &explicit {
+            Some(p) => Self::from_file(p)?,
+            None => match config_path() {
+                Some(p) if p.exists() => Self::from_file(&p)?,
+                _ => Self::default()
+            }
+        };
+        config.apply_args(&args)?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.into(), e))?;
+        let config: Self = toml::from_str(&text).map_err(|e| ConfigError::Parse(path.into(), e))?;
+        config.validate(&format!("in {}", path.display()))?;
+        Ok(config)
+    }
+
+    fn apply_args(&mut self, args: &[String]) -> Result<(), ConfigError> {
+        let mut args = args.iter();
+        while let Some(a) = args.next() {
+            let mut value = |flag: &str| args.next().cloned().ok_or_else(|| missing(flag));
+            match a.as_str() {
+                "--config" => {
+                    value("--config")?;
+                }
+                "--seed" => self.seed = Some(number("--seed", &value("--seed")?)?),
+                "--mode" => {
+                    let v = value("--mode")?;
+                    self.mode = Some(GameMode::from_key(&v).ok_or_else(|| ConfigError::Flag(
+                        format!("Unknown mode '{v}', expected classic, sudden-death or hot-seat")
+                    ))?);
+                }
+                "--sidelen" => {
+                    // checked before narrowing, so a huge number is out of range rather than wrapped
+                    let n: u32 = number("--sidelen", &value("--sidelen")?)?;
+                    let r = CONFIG_SIDELEN_RANGE;
+                    check("sidelen", n, *r.start() as u32..=*r.end() as u32, "on the command line")?;
+                    self.sidelen = n as u8;
+                }
+                "--fullscreen" => self.fullscreen = true,
+                "--windowed" => self.fullscreen = false,
+                "-h" | "--help" => return Err(ConfigError::Help),
+                _ => return Err(ConfigError::Flag(format!("Unknown option '{a}'")))
+            }
+        }
+        self.validate("on the command line")
+    }
+
+    /// Checks everything is in a range the game can cope with, `source` saying where it came from
+    fn validate(&self, source: &str) -> Result<(), ConfigError> {
+        check("window_width", self.window_width, CONFIG_WINDOW_WIDTH_RANGE, source)?;
+        check("window_height", self.window_height, CONFIG_WINDOW_HEIGHT_RANGE, source)?;
+        check("sidelen", self.sidelen, CONFIG_SIDELEN_RANGE, source)?;
+        check("timer_max", self.timer_max, CONFIG_TIMER_MAX_RANGE, source)?;
+        check("timer_reduction", self.timer_reduction, CONFIG_TIMER_REDUCTION_RANGE, source)?;
+        Ok(())
+    }
+}
+
+/// Where the config file is looked for unless given with --config
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join(CONFIG_DIR_NAME).join("config.toml"))
+}
+
+fn check<T: PartialOrd + fmt::Display>(name: &'static str, value: T, range: RangeInclusive<T>, source: &str) -> Result<(), ConfigError> {
+    if range.contains(&value) {
+        return Ok(())
+    }
+    Err(ConfigError::OutOfRange {
+        name,
+        value: value.to_string(),
+        range: format!("{} and {}", range.start(), range.end()),
+        source: source.into()
+    })
+}
+
+fn missing(flag: &str) -> ConfigError {
+    ConfigError::Flag(format!("{flag} needs a value after it"))
+}
+
+fn number<T: std::str::FromStr>(flag: &str, v: &str) -> Result<T, ConfigError> {
+    v.parse().map_err(|_| ConfigError::Flag(format!("{flag} needs a whole number, not '{v}'")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(a: &[&str]) -> Vec<String> {
+        a.iter().map(|s| s.to_string()).collect()
+    }
+
+    // the flags on top of the defaults, leaving the player's own config file out of it
+    fn with_flags(a: &[&str]) -> Result<GameConfig, ConfigError> {
+        let mut config = GameConfig::default();
+        config.apply_args(&args(a)).map(|_| config)
+    }
+
+    // loads `text` as the config file, followed by the flags
+    fn with_file(name: &str, text: &str, a: &[&str]) -> Result<GameConfig, ConfigError> {
+        let path = std::env::temp_dir().join(format!("cube_game_test_{}_{name}.toml", std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        let mut all = args(&["--config", path.to_str().unwrap()]);
+        all.extend(args(a));
+        let config = GameConfig::load(all);
+        let _ = std::fs::remove_file(&path);
+        config
+    }
+
+    #[test]
+    fn flags_go_on_top_of_the_defaults() {
+        let config = with_flags(&["--seed", "42", "--mode", "sudden-death", "--sidelen", "5", "--fullscreen"]).unwrap();
+        assert_eq!(config, GameConfig {
+            seed: Some(42),
+            mode: Some(GameMode::SuddenDeath),
+            sidelen: 5,
+            fullscreen: true,
+            ..GameConfig::default()
+        });
+        // the last of two opposite flags wins
+        assert!(!with_flags(&["--fullscreen", "--windowed"]).unwrap().fullscreen);
+        assert!(matches!(with_flags(&["--help"]), Err(ConfigError::Help)));
+    }
+
+    #[test]
+    fn sidelen_flag_is_range_checked() {
+        for n in ["2", "9", "300"] {
+            assert!(
+                matches!(with_flags(&["--sidelen", n]), Err(ConfigError::OutOfRange { name: "sidelen", .. })),
+                "--sidelen {n} was let through"
+            );
+        }
+        for n in ["-1", "five", ""] {
+            assert!(matches!(with_flags(&["--sidelen", n]), Err(ConfigError::Flag(_))), "--sidelen '{n}' was let through");
+        }
+        assert_eq!(with_flags(&["--sidelen", "8"]).unwrap().sidelen, 8);
+    }
+
+    #[test]
+    fn unknown_flags_are_turned_away() {
+        assert!(matches!(with_flags(&["--sidelenn", "5"]), Err(ConfigError::Flag(_))));
+        assert!(matches!(with_flags(&["--mode", "easy"]), Err(ConfigError::Flag(_))));
+        assert!(matches!(with_flags(&["--seed"]), Err(ConfigError::Flag(_))));
+    }
+
+    #[test]
+    fn config_file_is_checked_then_overridden() {
+        let config = with_file("ok", "sidelen = 4\ntimer_max = 20.0", &["--sidelen", "6"]).unwrap();
+        assert_eq!((config.sidelen, config.timer_max), (6, 20.0));
+
+        assert!(matches!(with_file("field", "sidelength = 4", &[]), Err(ConfigError::Parse(..))));
+        assert!(matches!(
+            with_file("range", "timer_max = 100.0", &[]),
+            Err(ConfigError::OutOfRange { name: "timer_max", .. })
+        ));
+        assert!(matches!(
+            with_file("reduction", "timer_reduction = 1.5", &[]),
+            Err(ConfigError::OutOfRange { name: "timer_reduction", .. })
+        ));
+    }
+}
@@ -0,0 +1,251 @@
+use std::{collections::VecDeque, str::FromStr, sync::Arc};
+
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{config::*, d2::{font::FontAtlas, layout::Layout, retained::Retained, roundedrect::RoundedRect, shape::Shape, tessellate::Style, text::{Align, Text}}, scenes::{Frame, Shared}};
+
+// Commands are typed as a name then arguments separated by spaces. Whatever understands
+// a command says so through CommandHandler, so the console doesn't need to know about them,
+// and only the commands that make sense right now (a run's while one is going) are offered.
+
+/// A command the console can offer, for autocomplete and help
+#[derive(Clone, Copy, Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub usage: &'static str, // the arguments it takes
+    pub help: &'static str
+}
+
+/// What to print after a command, or why it couldn't be done
+pub type CommandResult = Result<String, String>;
+
+/// Something commands typed into the console can be sent to
+pub trait CommandHandler {
+    /// Adds the commands this understands at the moment
+    fn commands(&self, out: &mut Vec<CommandSpec>);
+    /// Runs `name` if it's one of this handler's commands, None if it isn't
+    fn run_command(&mut self, shared: &mut Shared, name: &str, args: &[&str]) -> Option<CommandResult>;
+}
+
+/// Reads argument `index` as a `T`, `what` describing it for the error if it can't be
+pub fn parse_arg<T: FromStr>(args: &[&str], index: usize, what: &str) -> Result<T, String> {
+    let Some(a) = args.get(index) else {
+        return Err(format!("Expected {what}"))
+    };
+    a.parse().map_err(|_| format!("Expected {what}, not '{a}'"))
+}
+
+const COMMANDS: [CommandSpec; 1] = [
+    CommandSpec { name: "clear", usage: "", help: "Clears the console" },
+];
+
+/// A drop-down prompt for typing commands, with history and autocomplete
+pub struct Console {
+    open: bool,
+    openness: f32, // 0 hidden to 1 all the way down
+    input: String,
+    history: VecDeque<String>, // oldest first
+    history_pos: Option<usize>, // where up and down have got to, None when typing a new line
+    output: VecDeque<String>, // oldest first
+    layout: Layout,
+    bg: Retained<RoundedRect>,
+    lines: Vec<Retained<Text>>, // the output, top first
+    prompt: Retained<Text>
+}
+
+impl Console {
+    pub fn new(font: Arc<FontAtlas>, layout: &Layout) -> Self {
+        let text = |s: &str| Retained::new(Text::new(
+            s,
+            font.clone(),
+            [0.0, 0.0].into(),
+            0.0,
+            CONSOLE_TEXT_SIZE,
+            CONSOLE_LAYER + 1,
+            CONSOLE_TEXT_COLOUR,
+            1.0,
+            Align::Left,
+            None
+        ));
+
+        let mut console = Self {
+            open: false,
+            openness: 0.0,
+            input: String::new(),
+            history: VecDeque::new(),
+            history_pos: None,
+            output: VecDeque::new(),
+            layout: *layout,
+            bg: Retained::new(RoundedRect::new(
+                0.0,
+                CONSOLE_HEIGHT,
+                0.0,
+                [0.0, 0.0].into(),
+                Style::Fill,
+                CONSOLE_LAYER,
+                CONSOLE_COLOUR,
+                CONSOLE_OPACITY
+            )),
+            lines: (0..CONSOLE_LINES).map(|_| text("")).collect(),
+            prompt: text("")
+        };
+        console.print("Type help to list commands");
+        console.place();
+        console
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.refresh_prompt();
+    }
+
+    /// Adds text to the output, a line at a time
+    pub fn print(&mut self, text: &str) {
+        for l in text.lines() {
+            if self.output.len() == CONSOLE_LINES {
+                self.output.pop_front();
+            }
+            self.output.push_back(l.into());
+        }
+        let blank = CONSOLE_LINES - self.output.len();
+        for (i, t) in self.lines.iter_mut().enumerate() {
+            t.set_text(i.checked_sub(blank).and_then(|i| self.output.get(i)).map_or("", |s| s));
+        }
+    }
+
+    /// Slides the console towards open or closed
+    pub fn update(&mut self, dt: f32) {
+        let target = if self.open {1.0} else {0.0};
+        if self.openness != target {
+            let step = dt / ANIM_CONSOLE_SLIDE_LEN;
+            self.openness = if self.open {(self.openness + step).min(1.0)} else {(self.openness - step).max(0.0)};
+            self.place();
+        }
+    }
+
+    /// Handles a key press while open, returning a line once it's entered.
+    /// `commands` are the ones that can be autocompleted.
+    pub fn input(&mut self, key: &Key, commands: &[CommandSpec]) -> Option<String> {
+        match key {
+            Key::Named(NamedKey::Enter) => return self.submit(),
+            Key::Named(NamedKey::Escape) => self.open = false,
+            Key::Named(NamedKey::Backspace) => {
+                self.input.pop();
+            }
+            Key::Named(NamedKey::Tab) => self.complete(commands),
+            Key::Named(NamedKey::ArrowUp) => self.recall(-1),
+            Key::Named(NamedKey::ArrowDown) => self.recall(1),
+            Key::Named(NamedKey::Space) => self.input.push(' '),
+            Key::Character(c) => self.input.extend(c.chars().filter(|c| !c.is_control())),
+            _ => return None
+        }
+        self.refresh_prompt();
+        None
+    }
+
+    pub fn relayout(&mut self, layout: &Layout) {
+        self.layout = *layout;
+        self.place();
+    }
+
+    pub fn render<'f>(&'f mut self, frame: &mut Frame<'f>) {
+        if self.openness == 0.0 {
+            return
+        }
+        frame.shapes.push(&mut self.bg);
+        for l in self.lines.iter_mut() {
+            frame.shapes.push(l);
+        }
+        frame.shapes.push(&mut self.prompt);
+    }
+
+    fn submit(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.input);
+        self.history_pos = None;
+        self.refresh_prompt();
+        self.print(&format!("> {line}"));
+        if line.trim().is_empty() {
+            return None
+        }
+        if self.history.back() != Some(&line) {
+            if self.history.len() == CONSOLE_HISTORY {
+                self.history.pop_front();
+            }
+            self.history.push_back(line.clone());
+        }
+        Some(line)
+    }
+
+    // steps through the history, -1 being back towards older commands
+    fn recall(&mut self, dir: i32) {
+        let pos = match (self.history_pos, dir) {
+            (None, -1) => self.history.len().checked_sub(1),
+            (None, _) => None,
+            (Some(p), -1) => Some(p.saturating_sub(1)),
+            (Some(p), _) => Some(p + 1).filter(|&p| p < self.history.len())
+        };
+        self.history_pos = pos;
+        self.input = pos.and_then(|p| self.history.get(p)).cloned().unwrap_or_default();
+    }
+
+    // completes the command name as far as it's the same for everything that matches,
+    // listing them when that's no further than what's typed
+    fn complete(&mut self, commands: &[CommandSpec]) {
+        if self.input.contains(' ') {
+            return
+        }
+        let matches: Vec<&str> = commands.iter().map(|c| c.name).filter(|n| n.starts_with(&self.input)).collect();
+        match matches.as_slice() {
+            [] => {}
+            [one] => self.input = format!("{one} "),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |len, n| {
+                    first.bytes().zip(n.bytes()).take(len).take_while(|(a, b)| a == b).count()
+                });
+                if common > self.input.len() {
+                    self.input = first[..common].into();
+                } else {
+                    self.print(&matches.join("  "));
+                }
+            }
+        }
+    }
+
+    fn refresh_prompt(&mut self) {
+        self.prompt.set_text(&format!("> {}_", self.input));
+    }
+
+    // the panel hangs down from the top of the window as far as it's open, the prompt along its bottom
+    fn place(&mut self) {
+        let bottom = 1.0 - CONSOLE_HEIGHT * self.openness;
+        self.bg.width = self.layout.aspect * 2.0;
+        self.bg.set_pos([0.0, bottom + CONSOLE_HEIGHT * 0.5].into());
+
+        let left = -self.layout.aspect + CONSOLE_PADDING;
+        let prompt_y = bottom + CONSOLE_PADDING + CONSOLE_LINE_SPACING * 0.5;
+        self.prompt.set_pos([left, prompt_y].into());
+        for (i, l) in self.lines.iter_mut().rev().enumerate() {
+            l.set_pos([left, prompt_y + CONSOLE_LINE_SPACING * (i + 1) as f32].into());
+        }
+    }
+}
+
+impl CommandHandler for Console {
+    fn commands(&self, out: &mut Vec<CommandSpec>) {
+        out.extend(COMMANDS);
+    }
+    fn run_command(&mut self, _shared: &mut Shared, name: &str, _args: &[&str]) -> Option<CommandResult> {
+        match name {
+            "clear" => {
+                self.output.clear();
+                self.print("");
+                Some(Ok(String::new()))
+            }
+            _ => None
+        }
+    }
+}
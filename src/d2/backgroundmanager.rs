@@ -1,52 +1,31 @@
-use crate::{config::DEFAULT_CLEARCOL, mathsutils::lerp4d};
+use crate::{config::DEFAULT_CLEARCOL, tween::{Property, Tweenable, Value}};
 
+/// Holds the clear colour, flashes are animated by tweening its `Colour`
 pub struct BackgroundManager {
-    start: u64,
-    len: u64,
-    frame: u64,
-    col_start: [f32;4],
-    col_end: [f32;4],
-    col_current: [f32;4],
-    running: bool
+    col_current: [f32;4]
 }
 
 impl BackgroundManager {
     pub fn new() -> Self {
         Self {
-            start: 0,
-            len: 0,
-            frame: 0,
-            col_start: DEFAULT_CLEARCOL,
-            col_end: DEFAULT_CLEARCOL,
-            col_current: DEFAULT_CLEARCOL,
-            running: false
+            col_current: DEFAULT_CLEARCOL
         }
     }
-    pub fn start_anim(&mut self, end: [f32;4], len: u64) {
-        self.col_end = end;
-        self.len = len;
-        self.start = self.frame;
-        self.running = true;
+    pub fn current(&self) -> [f32;4] {
+        self.col_current
     }
-    pub fn update(&mut self, frame: u64) {
-        self.frame = frame;
-        if !self.running {
-            return
-        }
+}
 
-        let n = (self.frame as f32 - self.start as f32) / self.len as f32;
-        self.col_current = lerp4d(self.col_start, self.col_end, n);
-        if n >= 1.0 {
-            self.running = false
+impl Tweenable for BackgroundManager {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Colour => Some(Value::Colour(self.col_current)),
+            _ => None
         }
     }
-    pub fn set_bg(&mut self, col: [f32;4]) {
-        // println!("Set to {:?}", col);
-        // println!("Backtrace: {}", Backtrace::force_capture());
-        self.col_start = col;
-        self.col_current = col;
-    }
-    pub fn current(&self) -> [f32;4] {
-        self.col_current
+    fn set_property(&mut self, p: Property, v: Value) {
+        if let (Property::Colour, Value::Colour(c)) = (p, v) {
+            self.col_current = c;
+        }
     }
 }
@@ -0,0 +1,110 @@
+use std::f32::consts::PI;
+
+use cgmath::{InnerSpace, Vector2};
+
+use crate::tween::{Property, Tweenable, Value};
+
+use super::{hittest::HitTest, mouseutils::is_in_circle, shape::Shape, tessellate::{arc_points, Mesh, Style}, tovertind2d::ToVertInd2D, vertex::Vertex2D};
+
+/// A solid colour circle, filled or outlined
+#[derive(Clone, Debug)]
+pub struct Circle {
+    pub pos: Vector2<f32>,
+    pub rot: f32, // radians
+    pub radius: f32,
+    pub style: Style, // strokes are centred on the radius
+    pub layer: u8,
+    pub colour: [f32;3],
+    pub opacity: f32
+}
+
+impl Circle {
+    pub fn new(
+        pos: Vector2<f32>,
+        radius: f32,
+        style: Style,
+        layer: u8,
+        colour: [f32;3],
+        opacity: f32
+    ) -> Self {
+        Self { pos, rot: 0.0, radius, style, layer, colour, opacity }
+    }
+}
+
+impl ToVertInd2D for Circle {
+    fn layer(&self) -> u8 {
+        self.layer
+    }
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
+        let mut verts = vec![];
+        let mut inds = vec![];
+        self.append_vert_ind(&mut verts, &mut inds);
+        (verts, inds)
+    }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        let mut mesh = Mesh::new();
+        match self.style {
+            Style::Fill => {
+                let mut outline = vec![];
+                arc_points([0.0, 0.0].into(), self.radius, 0.0, 2.0 * PI, &mut outline);
+                mesh.fill_convex(&outline);
+            }
+            Style::Stroke(w) => {
+                mesh.ring([0.0, 0.0].into(), (self.radius - w * 0.5).max(0.0), self.radius + w * 0.5, 0.0, 2.0 * PI);
+            }
+        }
+        mesh.append_to(verts, inds, self.pos, self.rot, self.layer, self.colour, self.opacity);
+    }
+}
+
+impl HitTest for Circle {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        match self.style {
+            Style::Fill => is_in_circle(p, self.pos, self.radius),
+            Style::Stroke(w) => ((p - self.pos).magnitude() - self.radius).abs() <= w * 0.5
+        }
+    }
+}
+
+impl Shape for Circle {
+    fn get_pos(&self) -> Vector2<f32> {
+        self.pos
+    }
+    fn set_pos(&mut self, p: Vector2<f32>) {
+        self.pos = p
+    }
+    fn get_rot(&self) -> f32 {
+        self.rot
+    }
+    fn set_rot(&mut self, r: f32) {
+        self.rot = r
+    }
+}
+
+impl Tweenable for Circle {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Position => Some(Value::Vec2(self.pos)),
+            Property::Rotation => Some(Value::Float(self.rot)),
+            Property::Opacity => Some(Value::Float(self.opacity)),
+            Property::Colour => {
+                let [r, g, b] = self.colour;
+                Some(Value::Colour([r, g, b, self.opacity]))
+            }
+            Property::Scale => Some(Value::Float(self.radius))
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        match (p, v) {
+            (Property::Position, Value::Vec2(pos)) => self.pos = pos,
+            (Property::Rotation, Value::Float(r)) => self.rot = r,
+            (Property::Opacity, Value::Float(o)) => self.opacity = o,
+            (Property::Colour, Value::Colour([r, g, b, a])) => {
+                self.colour = [r, g, b];
+                self.opacity = a;
+            }
+            (Property::Scale, Value::Float(s)) => self.radius = s,
+            _ => {}
+        }
+    }
+}
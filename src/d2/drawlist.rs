@@ -0,0 +1,129 @@
+use std::ops::Range;
+
+use super::{retained::RetainedShape, vertex::Vertex2D};
+
+/// Persistent 2D vertex/index data for the whole screen.
+///
+/// Each frame the retained shapes are laid out in layer order, and only the
+/// parts of the buffers belonging to shapes that changed (or moved) are rewritten.
+/// The rewritten ranges are kept so the GPU upload can be limited to them.
+#[derive(Default)]
+pub struct DrawList {
+    verts: Vec<Vertex2D>,
+    inds: Vec<u32>,
+    entries: Vec<Entry>,
+    order: Vec<usize>,
+    dirty_verts: Vec<Range<usize>>,
+    dirty_inds: Vec<Range<usize>>
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Entry {
+    key: (u64, u64),
+    vert_start: usize,
+    ind_start: usize
+}
+
+impl DrawList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lays out this frame's shapes, refreshing any that have changed
+    pub fn build(&mut self, shapes: &mut [&mut dyn RetainedShape]) {
+        // stable sort so that shapes on the same layer keep their submission order
+        self.order.clear();
+        self.order.extend(0..shapes.len());
+        self.order.sort_by_key(|&i| shapes[i].layer());
+
+        let mut vert_cursor = 0;
+        let mut ind_cursor = 0;
+
+        for (slot, &i) in self.order.iter().enumerate() {
+            let shape = &mut shapes[i];
+            shape.refresh();
+
+            let entry = Entry {
+                key: shape.key(),
+                vert_start: vert_cursor,
+                ind_start: ind_cursor
+            };
+            let (v, ind) = shape.geometry();
+
+            if self.entries.get(slot) != Some(&entry) {
+                write_at(&mut self.verts, vert_cursor, v.iter().copied());
+                let base = vert_cursor as u32;
+                write_at(&mut self.inds, ind_cursor, ind.iter().map(|i| i + base));
+
+                push_range(&mut self.dirty_verts, vert_cursor..vert_cursor + v.len());
+                push_range(&mut self.dirty_inds, ind_cursor..ind_cursor + ind.len());
+
+                if slot < self.entries.len() {
+                    self.entries[slot] = entry;
+                } else {
+                    self.entries.push(entry);
+                }
+            }
+
+            debug_assert!(
+                vert_cursor + v.len() <= u32::MAX as usize,
+                "2D geometry has too many vertices to be indexed with u32"
+            );
+
+            vert_cursor += v.len();
+            ind_cursor += ind.len();
+        }
+
+        self.entries.truncate(shapes.len());
+        self.verts.truncate(vert_cursor);
+        self.inds.truncate(ind_cursor);
+    }
+
+    pub fn vertices(&self) -> &[Vertex2D] {
+        &self.verts
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.inds
+    }
+
+    /// Ranges of vertices rewritten since the last `clear_dirty`
+    pub fn dirty_vertices(&self) -> &[Range<usize>] {
+        &self.dirty_verts
+    }
+
+    /// Ranges of indices rewritten since the last `clear_dirty`
+    pub fn dirty_indices(&self) -> &[Range<usize>] {
+        &self.dirty_inds
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.dirty_verts.clear();
+        self.dirty_inds.clear();
+    }
+}
+
+fn write_at<T: Copy + bytemuck::Zeroable>(buf: &mut Vec<T>, start: usize, data: impl ExactSizeIterator<Item = T>) {
+    let end = start + data.len();
+    if buf.len() < end {
+        buf.resize(end, T::zeroed());
+    }
+    for (dst, src) in buf[start..end].iter_mut().zip(data) {
+        *dst = src;
+    }
+}
+
+// merges with the previous range when they touch, which is the common case
+// since shapes are written in order
+fn push_range(ranges: &mut Vec<Range<usize>>, r: Range<usize>) {
+    if r.is_empty() {
+        return
+    }
+    match ranges.last_mut() {
+        Some(last) if last.end >= r.start && r.end >= last.start => {
+            last.start = last.start.min(r.start);
+            last.end = last.end.max(r.end);
+        }
+        _ => ranges.push(r)
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use ab_glyph::{point, Font, FontArc, GlyphId, InvalidFont, PxScale};
+use image::{imageops, Rgba, RgbaImage};
+
+use crate::config::{DIGIT_TEX_BLUR, DIGIT_TEX_HEIGHT, DIGIT_TEX_WIDTH, FONT_ATLAS_WIDTH, FONT_GLYPH_PADDING, FONT_RASTER_PX};
+
+/// Where a glyph is in the atlas and how to place it.
+/// Sizes are in ems, so they can be scaled to any text size.
+#[derive(Clone, Copy, Debug)]
+pub struct GlyphInfo {
+    pub uv_min: [f32;2],
+    pub uv_max: [f32;2],
+    // bounds relative to the pen position on the baseline, y up
+    pub min: [f32;2],
+    pub max: [f32;2],
+    pub advance: f32,
+    id: GlyphId
+}
+
+/// Glyphs rasterised from a font at startup and packed into one texture.
+/// Glyphs are white, with coverage in the alpha channel, so text can be tinted any colour.
+pub struct FontAtlas {
+    font: FontArc,
+    glyphs: HashMap<char, GlyphInfo>,
+    image: RgbaImage,
+    units_per_em: f32,
+    pub ascent: f32,
+    pub descent: f32, // negative, below the baseline
+    pub line_gap: f32
+}
+
+impl FontAtlas {
+    /// Rasterises every printable ASCII character
+    pub fn new(ttf: &'static [u8]) -> Result<Self, InvalidFont> {
+        let font = FontArc::try_from_slice(ttf)?;
+        let units_per_em = font.units_per_em().unwrap_or(1000.0);
+        let px = FONT_RASTER_PX as f32;
+        // ab_glyph scales by the font's height rather than its em, so work out which
+        // scale gives FONT_RASTER_PX pixels per em
+        let scale = PxScale::from(px * font.height_unscaled() / units_per_em);
+        let pad = FONT_GLYPH_PADDING;
+
+        // rasterise everything first, then pack tallest first so rows waste less space
+        let mut rasters = vec![];
+        for c in (' '..='~').chain(std::iter::once('\u{FFFD}')) {
+            let id = font.glyph_id(c);
+            if c != ' ' && id.0 == 0 {
+                continue
+            }
+            let glyph = id.with_scale(scale);
+            let outlined = font.outline_glyph(glyph);
+            rasters.push((c, id, outlined));
+        }
+        rasters.sort_by_key(|(_, _, o)| {
+            std::cmp::Reverse(o.as_ref().map(|o| o.px_bounds().height() as u32).unwrap_or(0))
+        });
+
+        // simple shelf packing, each row as tall as its first (tallest) glyph
+        let mut placements = vec![];
+        let (mut x, mut y, mut row_h) = (pad, pad, 0);
+        for (c, id, outlined) in rasters {
+            let (w, h) = match &outlined {
+                Some(o) => {
+                    let b = o.px_bounds();
+                    (b.width().ceil() as u32, b.height().ceil() as u32)
+                }
+                None => (0, 0)
+            };
+            if x + w + pad > FONT_ATLAS_WIDTH {
+                x = pad;
+                y += row_h + pad;
+                row_h = 0;
+            }
+            placements.push((c, id, outlined, x, y, w, h));
+            x += w + pad;
+            row_h = row_h.max(h);
+        }
+        let height = (y + row_h + pad).next_power_of_two();
+
+        let mut image = RgbaImage::from_pixel(FONT_ATLAS_WIDTH, height, Rgba([255, 255, 255, 0]));
+        let mut glyphs = HashMap::new();
+        let (aw, ah) = (FONT_ATLAS_WIDTH as f32, height as f32);
+
+        for (c, id, outlined, x, y, w, h) in placements {
+            let advance = font.h_advance_unscaled(id) / units_per_em;
+            let (min, max) = match &outlined {
+                Some(o) => {
+                    o.draw(|gx, gy, coverage| {
+                        let (px, py) = (x + gx, y + gy);
+                        if px < image.width() && py < image.height() {
+                            image.put_pixel(px, py, Rgba([255, 255, 255, (coverage * 255.0).round() as u8]));
+                        }
+                    });
+                    let b = o.px_bounds();
+                    // pixel bounds are y down from the baseline, flip them to y up in ems
+                    (
+                        [b.min.x / px, -(b.min.y + h as f32) / px],
+                        [(b.min.x + w as f32) / px, -b.min.y / px]
+                    )
+                }
+                None => ([0.0;2], [0.0;2])
+            };
+            glyphs.insert(c, GlyphInfo {
+                uv_min: [x as f32 / aw, y as f32 / ah],
+                uv_max: [(x + w) as f32 / aw, (y + h) as f32 / ah],
+                min,
+                max,
+                advance,
+                id
+            });
+        }
+
+        Ok(Self {
+            ascent: font.ascent_unscaled() / units_per_em,
+            descent: font.descent_unscaled() / units_per_em,
+            line_gap: font.line_gap_unscaled() / units_per_em,
+            font,
+            glyphs,
+            image,
+            units_per_em
+        })
+    }
+
+    /// The packed glyphs, to be uploaded as a texture
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    /// Characters the atlas doesn't have are drawn as the replacement character, or '?'
+    pub fn glyph(&self, c: char) -> Option<&GlyphInfo> {
+        self.glyphs.get(&c)
+            .or_else(|| self.glyphs.get(&'\u{FFFD}'))
+            .or_else(|| self.glyphs.get(&'?'))
+    }
+
+    /// Extra space between a pair of glyphs, in ems
+    pub fn kern(&self, a: &GlyphInfo, b: &GlyphInfo) -> f32 {
+        self.font.kern_unscaled(a.id, b.id) / self.units_per_em
+    }
+
+    /// Distance between baselines, in ems
+    pub fn line_height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+
+    /// Draws a single character in black, as large as fits in a DIGIT_TEX_WIDTH x DIGIT_TEX_HEIGHT
+    /// texture, centred and slightly blurred. Used for the digit and symbol textures that `Number` draws with.
+    pub fn render_number_glyph(&self, c: char) -> Result<RgbaImage, String> {
+        let id = self.font.glyph_id(c);
+        if id.0 == 0 {
+            return Err(format!("the font has no glyph for '{c}'"))
+        }
+
+        // fit the character's whole line box, not just its ink, so every digit
+        // comes out the same size with its baseline in the same place
+        let (w, h) = (DIGIT_TEX_WIDTH as f32, DIGIT_TEX_HEIGHT as f32);
+        let advance = self.font.h_advance_unscaled(id) / self.units_per_em;
+        let line = self.ascent - self.descent;
+        if advance <= 0.0 || line <= 0.0 {
+            return Err(format!("the font's metrics for '{c}' are empty (advance {advance}, line height {line})"))
+        }
+        let px = (w / advance).min(h / line);
+
+        let origin = point((w - advance * px) * 0.5, (h - line * px) * 0.5 + self.ascent * px);
+        let scale = PxScale::from(px * self.font.height_unscaled() / self.units_per_em);
+        let Some(outlined) = self.font.outline_glyph(id.with_scale_and_position(scale, origin)) else {
+            return Err(format!("the font's glyph for '{c}' has no outline"))
+        };
+
+        let mut image = RgbaImage::from_pixel(DIGIT_TEX_WIDTH, DIGIT_TEX_HEIGHT, Rgba([0, 0, 0, 0]));
+        let b = outlined.px_bounds();
+        outlined.draw(|gx, gy, coverage| {
+            let (x, y) = (b.min.x as i32 + gx as i32, b.min.y as i32 + gy as i32);
+            if x >= 0 && y >= 0 && (x as u32) < DIGIT_TEX_WIDTH && (y as u32) < DIGIT_TEX_HEIGHT {
+                image.put_pixel(x as u32, y as u32, Rgba([0, 0, 0, (coverage * 255.0).round() as u8]));
+            }
+        });
+
+        Ok(imageops::blur(&image, DIGIT_TEX_BLUR))
+    }
+}
@@ -0,0 +1,166 @@
+use cgmath::{InnerSpace, Vector2};
+
+use super::{retained::Retained, tessellate::Cap};
+
+/// A shape that can say whether a point lands on it, for picking things with the mouse
+pub trait HitTest {
+    /// Expects a point in GUI space, not pixel space
+    fn hit(&self, p: Vector2<f32>) -> bool;
+}
+
+impl<T: HitTest> HitTest for Retained<T> {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        (**self).hit(p)
+    }
+}
+
+/// Moves a point into a shape's own space, undoing the shape's position and its rotation about that position
+pub fn to_local(p: Vector2<f32>, pos: Vector2<f32>, rot: f32) -> Vector2<f32> {
+    let d = p - pos;
+    let (sin, cos) = rot.sin_cos();
+    Vector2::new(d.x * cos + d.y * sin, d.y * cos - d.x * sin)
+}
+
+/// Shortest distance from a point to the line segment between `a` and `b`
+pub fn segment_distance(p: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    let ab = b - a;
+    let len2 = ab.magnitude2();
+    if len2 == 0.0 {
+        return (p - a).magnitude()
+    }
+    let t = ((p - a).dot(ab) / len2).clamp(0.0, 1.0);
+    (p - (a + ab * t)).magnitude()
+}
+
+/// Whether a point is on a stroke through `points`, all in the stroke's own space.
+/// Joins are treated as round, which misses at most the tip of a sharp miter.
+pub fn on_stroke(p: Vector2<f32>, points: &[Vector2<f32>], closed: bool, width: f32, cap: Cap) -> bool {
+    let h = width * 0.5;
+    let n = points.len();
+    if n < 2 {
+        return false
+    }
+    let seg_count = if closed {n} else {n - 1};
+
+    (0..seg_count).any(|i| {
+        let (mut a, mut b) = (points[i], points[(i + 1) % n]);
+        let first = !closed && i == 0;
+        let last = !closed && i == seg_count - 1;
+        let len = (b - a).magnitude();
+        if len == 0.0 {
+            return (p - a).magnitude() <= h
+        }
+        let d = (b - a) / len;
+
+        // square caps are butt caps carried on for half the width
+        if cap == Cap::Square {
+            if first {a -= d * h}
+            if last {b += d * h}
+        }
+        // and butt caps cut the stroke off square at the ends, rather than rounding them
+        if cap != Cap::Round {
+            let t = (p - a).dot(d);
+            if (first && t < 0.0) || (last && t > (b - a).magnitude()) {
+                return false
+            }
+        }
+        segment_distance(p, a, b) <= h
+    })
+}
+
+/// Finds the topmost of a set of shapes under a point, given each shape's layer and a key to identify it by.
+/// Higher layers are on top, and within a layer the first shape wins, matching how they're drawn:
+/// shapes on the same layer are drawn in order, and later ones fail the depth test where they overlap.
+/// Shapes of different types can be mixed as `&dyn HitTest`.
+pub fn topmost_hit<'a, K, H: HitTest + ?Sized + 'a>(p: Vector2<f32>, shapes: impl IntoIterator<Item = (K, u8, &'a H)>) -> Option<K> {
+    let mut best: Option<(K, u8)> = None;
+    for (key, layer, shape) in shapes {
+        if best.as_ref().is_some_and(|(_, l)| *l >= layer) {
+            continue
+        }
+        if shape.hit(p) {
+            best = Some((key, layer));
+        }
+    }
+    best.map(|(k, _)| k)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::d2::{circle::Circle, rectangle::Rectangle, tessellate::Style, tovertind2d::ToVertInd2D};
+
+    const EPS: f32 = 1e-5;
+
+    #[test]
+    fn to_local_undoes_position_and_rotation() {
+        let pos = Vector2::new(1.0, 2.0);
+        // a quarter turn anticlockwise takes the shape's +x to the screen's +y
+        let local = to_local(pos + Vector2::new(0.0, 0.5), pos, FRAC_PI_2);
+        assert!((local - Vector2::new(0.5, 0.0)).magnitude() < EPS);
+        assert!(to_local(pos, pos, 1.234).magnitude() < EPS);
+    }
+
+    #[test]
+    fn rotated_rectangle_is_hit_where_it_is_drawn() {
+        // 2 wide and 0.5 tall, turned on its end
+        let rect = Rectangle::new(2.0, 0.5, [1.0, 1.0].into(), FRAC_PI_2, 0, 0, false, 1.0);
+        assert!(rect.hit([1.0, 1.9].into()));
+        assert!(!rect.hit([1.9, 1.0].into()));
+
+        // just inside each drawn corner hits, just outside doesn't
+        let (verts, _) = rect.to_vert_ind();
+        for v in verts {
+            let corner: Vector2<f32> = v.pos.into();
+            let out = corner - rect.pos;
+            assert!(rect.hit(rect.pos + out * 0.99), "missed inside the corner at {corner:?}");
+            assert!(!rect.hit(rect.pos + out * 1.01), "hit outside the corner at {corner:?}");
+        }
+    }
+
+    #[test]
+    fn caps_decide_what_counts_past_the_ends() {
+        let line = [Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0)];
+        let hit = |p: [f32;2], cap| on_stroke(p.into(), &line, false, 0.2, cap);
+
+        // just past the end
+        for p in [[-0.05, 0.0], [1.05, 0.0]] {
+            assert!(!hit(p, Cap::Butt));
+            assert!(hit(p, Cap::Square));
+            assert!(hit(p, Cap::Round));
+        }
+        // off the corner of a square cap, which a round one doesn't reach
+        assert!(hit([-0.08, 0.08], Cap::Square));
+        assert!(!hit([-0.08, 0.08], Cap::Round));
+
+        // along the side, every cap is the same
+        for cap in [Cap::Butt, Cap::Square, Cap::Round] {
+            assert!(hit([0.5, 0.09], cap));
+            assert!(!hit([0.5, 0.11], cap));
+        }
+    }
+
+    #[test]
+    fn topmost_hit_goes_by_layer_then_order() {
+        let circle = |x: f32, layer| Circle::new([x, 0.0].into(), 0.5, Style::Fill, layer, [1.0;3], 1.0);
+        let low = circle(0.0, 1);
+        let first = circle(0.2, 3);
+        let second = circle(-0.2, 3);
+        let elsewhere = circle(5.0, 6);
+        let rect = Rectangle::new(1.0, 1.0, [0.0, 0.0].into(), 0.0, 2, 0, false, 1.0);
+        let shapes: [(&str, u8, &dyn HitTest); 5] = [
+            ("low", 1, &low),
+            ("rect", 2, &rect),
+            ("first", 3, &first),
+            ("second", 3, &second),
+            ("elsewhere", 6, &elsewhere)
+        ];
+
+        assert_eq!(topmost_hit([0.0, 0.0].into(), shapes), Some("first"));
+        // the rectangle's corner, clear of the circles above it
+        assert_eq!(topmost_hit([0.45, -0.45].into(), shapes), Some("rect"));
+        assert_eq!(topmost_hit([0.0, 3.0].into(), shapes), None);
+    }
+}
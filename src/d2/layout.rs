@@ -0,0 +1,119 @@
+use cgmath::Vector2;
+
+use crate::config::{NET_AREA_BOTTOM, NET_AREA_TOP, NET_COUNT, NET_GAP, NET_LAYOUT_MARGIN};
+
+// GUI space runs from -1 to 1 vertically and from -aspect to aspect horizontally,
+// so distances measured in GUI units don't depend on the window's aspect ratio
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Centre,
+    Bottom
+}
+
+impl Anchor {
+    /// Where this anchor point is in GUI space
+    pub fn origin(&self, aspect: f32) -> Vector2<f32> {
+        let (x, y) = self.signs();
+        [x * aspect, y].into()
+    }
+    // which screen edge (if any) this anchor is on, for each axis
+    fn signs(&self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft  => (-1.0,  1.0),
+            Anchor::Top      => ( 0.0,  1.0),
+            Anchor::TopRight => ( 1.0,  1.0),
+            Anchor::Centre   => ( 0.0,  0.0),
+            Anchor::Bottom   => ( 0.0, -1.0),
+        }
+    }
+}
+
+/// A position given relative to an anchor.
+/// The offset points inwards from whichever edges the anchor is on, so a
+/// `TopRight` offset of `[0.1, 0.2]` is 0.1 left of the right edge and 0.2 below the top.
+/// On axes where the anchor is centred, the offset is just added.
+#[derive(Clone, Copy, Debug)]
+pub struct Anchored {
+    pub anchor: Anchor,
+    pub offset: Vector2<f32>
+}
+
+impl Anchored {
+    pub const fn new(anchor: Anchor, offset: [f32;2]) -> Self {
+        Self {
+            anchor,
+            offset: Vector2::new(offset[0], offset[1])
+        }
+    }
+    pub fn resolve(&self, aspect: f32) -> Vector2<f32> {
+        let (sx, sy) = self.anchor.signs();
+        let origin = self.anchor.origin(aspect);
+        // flip the offset on axes pinned to an edge so it points inwards
+        let dx = if sx == 0.0 {self.offset.x} else {-sx * self.offset.x};
+        let dy = if sy == 0.0 {self.offset.y} else {-sy * self.offset.y};
+        origin + Vector2::new(dx, dy)
+    }
+}
+
+/// Works out where things go on screen for the current window shape
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    pub aspect: f32
+}
+
+impl Layout {
+    pub fn new(aspect: f32) -> Self {
+        Self { aspect }
+    }
+
+    pub fn resolve(&self, a: Anchored) -> Vector2<f32> {
+        a.resolve(self.aspect)
+    }
+
+    /// Positions of the answer nets, and the scale to draw them at.
+    /// Nets are laid out in whichever number of rows lets them be drawn biggest
+    /// while still fitting in the area along the bottom of the screen.
+    pub fn net_positions(&self) -> (Vec<Vector2<f32>>, f32) {
+        let count = NET_COUNT;
+        let avail_w = 2.0 * (self.aspect - NET_LAYOUT_MARGIN);
+        let avail_h = NET_AREA_TOP - NET_AREA_BOTTOM;
+
+        let mut best_rows = 1;
+        let mut best_scale = 0.0;
+        for rows in 1..=count {
+            let per_row = count.div_ceil(rows);
+            let scale = (avail_w / (per_row as f32 * NET_GAP))
+                .min(avail_h / (rows as f32 * NET_GAP))
+                .min(1.0);
+            if scale > best_scale {
+                best_rows = rows;
+                best_scale = scale;
+            }
+        }
+
+        let rows = best_rows;
+        let per_row = count.div_ceil(rows);
+        let cell = NET_GAP * best_scale;
+        // centre the block of rows vertically within the net area
+        let top = NET_AREA_TOP - (avail_h - rows as f32 * cell) * 0.5;
+
+        let mut out = vec![];
+        for i in 0..count {
+            let row = i / per_row;
+            let col = i % per_row;
+            let in_this_row = (count - row * per_row).min(per_row);
+
+            let mut x = -((in_this_row - 1) as f32 * cell) * 0.5;
+            x += cell * col as f32;
+            let y = top - cell * (row as f32 + 0.5);
+
+            out.push(Vector2::new(x, y));
+        }
+
+        (out, best_scale)
+    }
+}
@@ -6,6 +6,16 @@ pub mod vertex;
 pub mod net;
 pub mod shape;
 pub mod mouseutils;
+pub mod hittest;
 pub mod backgroundmanager;
 pub mod spiral;
 pub mod number;
+pub mod retained;
+pub mod drawlist;
+pub mod layout;
+pub mod font;
+pub mod text;
+pub mod tessellate;
+pub mod circle;
+pub mod roundedrect;
+pub mod polyline;
@@ -1,7 +1,7 @@
 use cgmath::{InnerSpace, Vector2};
 use winit::dpi::{PhysicalPosition, PhysicalSize};
 
-use super::rectangle::Rectangle;
+use super::{hittest::to_local, rectangle::Rectangle, roundedrect::RoundedRect};
 
 pub fn convert_mouse_coords(pos: PhysicalPosition<f64>, size: PhysicalSize<u32>, aspect: f32) -> Vector2<f32> {
     let mut x = pos.x as f32 / size.width as f32;
@@ -20,66 +20,31 @@ pub fn is_in_circle(mpos: Vector2<f32>, centre: Vector2<f32>, radius: f32) -> bo
 }
 
 /// Expects inputs in GUI space, not pixel space
-pub fn is_in_rounded_rect(pos: Vector2<f32>, rect: &Rectangle, radius: f32) -> bool {
-
-    // instead of testing against rotated coordinates, just rotate
-    // the mouse position to match
-    let mpos: Vector2<f32> = {[
-        (pos.x * rect.rot.cos()) - (pos.y * rect.rot.sin()),
-        (pos.x * rect.rot.sin()) + (pos.y * rect.rot.cos())
-    ].into()};
-
-    let vert_rect = Rectangle::new(
-        rect.width - 2.0 * radius,
-        rect.height,
-        rect.pos,
-        rect.rot,
-        0,
-        255,
-        false,
-        0.0
-    );
-    let hor_rect = Rectangle::new(
-        rect.width,
-        rect.height - 2.0 * radius,
-        rect.pos,
-        rect.rot,
-        0,
-        0,
-        false,
-        0.0
-    );
+pub fn is_in_rounded_rect(pos: Vector2<f32>, rect: &RoundedRect) -> bool {
+    rounded_rect_distance(pos, rect) < 0.0
+}
 
-    let tl: Vector2<f32> = [  (rect.pos.x - rect.width/2.0)+radius,   (rect.pos.y + rect.height/2.0)-radius  ].into();
-    let tr: Vector2<f32> = [  (rect.pos.x + rect.width/2.0)-radius,   (rect.pos.y + rect.height/2.0)-radius  ].into();
-    let bl: Vector2<f32> = [  (rect.pos.x - rect.width/2.0)+radius,   (rect.pos.y - rect.height/2.0)+radius  ].into();
-    let br: Vector2<f32> = [  (rect.pos.x + rect.width/2.0)-radius,   (rect.pos.y - rect.height/2.0)+radius  ].into();
+/// Signed distance from a point to the edge of a rounded rectangle, negative inside
+pub fn rounded_rect_distance(pos: Vector2<f32>, rect: &RoundedRect) -> f32 {
+    let (hw, hh) = (rect.width * 0.5, rect.height * 0.5);
+    let radius = rect.radius.clamp(0.0, hw.min(hh));
 
-    is_in_rect(mpos, &vert_rect) ||
-    is_in_rect(mpos, &hor_rect) ||
-    is_in_circle(mpos, tl, radius) ||
-    is_in_circle(mpos, tr, radius) ||
-    is_in_circle(mpos, bl, radius) ||
-    is_in_circle(mpos, br, radius)
+    // instead of testing against rotated coordinates, just rotate
+    // the mouse position to match, about the middle of the rectangle
+    let mpos = to_local(pos, rect.pos, rect.rot);
+
+    // how far past the straight part of each edge the point is
+    let q = Vector2::new(mpos.x.abs() - (hw - radius), mpos.y.abs() - (hh - radius));
+    let outside = Vector2::new(q.x.max(0.0), q.y.max(0.0)).magnitude();
+    let inside = q.x.max(q.y).min(0.0);
+    outside + inside - radius
 }
 
 /// Expects inputs in GUI space, not pixel space
 pub fn is_in_rect(pos: Vector2<f32>, rect: &Rectangle) -> bool {
-    
     // instead of testing against rotated coordinates, just rotate
-    // the mouse position to match
-    let mpos: Vector2<f32> = {[
-        (pos.x * rect.rot.cos()) - (pos.y * rect.rot.sin()),
-        (pos.x * rect.rot.sin()) + (pos.y * rect.rot.cos())
-    ].into()};
-
-    let x1 = rect.pos.x + rect.width/2.0;
-    let x2 = rect.pos.x - rect.width/2.0;
-    let y1 = rect.pos.y + rect.height/2.0;
-    let y2 = rect.pos.y - rect.height/2.0;
-
-    let is_in_x = x1 > mpos.x && mpos.x > x2;
-    let is_in_y = y1 > mpos.y && mpos.y > y2;
+    // the mouse position to match, about the middle of the rectangle
+    let mpos = to_local(pos, rect.pos, rect.rot);
 
-    is_in_y && is_in_x
+    mpos.x.abs() < rect.width / 2.0 && mpos.y.abs() < rect.height / 2.0
 }
@@ -1,19 +1,32 @@
-use std::iter::zip;
+use std::{collections::{BTreeMap, BTreeSet}, iter::zip};
 
-use cgmath::Vector2;
+use cgmath::{InnerSpace, Vector2};
 
-use crate::d3::columngrid::ColumnGrid;
+use crate::{config::NET_EDGE_COLOUR, d3::columngrid::ColumnGrid, tween::{Property, Tweenable, Value}};
 
-use super::{rectangle::Rectangle, shape::Shape, tovertind2d::ToVertInd2D};
+use super::{hittest::HitTest, polyline::Polyline, rectangle::Rectangle, shape::Shape, tessellate::{Cap, Join}, tovertind2d::ToVertInd2D, vertex::Vertex2D};
+
+// a corner between squares, (0, 0) being the top left corner of the top left square
+type Corner = [i16;2];
+
+// a run of edges traced into one line
+#[derive(Clone, Debug)]
+struct Outline {
+    corners: Vec<Corner>,
+    closed: bool,
+    meets: [bool;2] // whether each end stops against another line, rather than being loose
+}
 
 // represents the top-down wireframe 2D view of a columngrid
 #[derive(Clone, Debug)]
 pub struct Net {
     sidelen: u8,
+    columns: Vec<Vec<u8>>, // the heights of the grid it was made from, so it can be made again
     squares: Vec< Vec< NetSquare > >,
+    paths: Vec<Outline>, // edges traced into lines
+    outlines: Vec<Polyline>, // the paths, placed on screen
     layer: u8,
     pos: Vector2<f32>,
-    rot: f32, // DEGREES
     opacity: f32
 }
 
@@ -36,15 +49,12 @@ impl Net {
                     [0.0;2].into(), // value never used
                     0.0, // neither this one
                     scale,
-                    edgethickness,
                     false,
                     opacity
                 );sidelen.into()
             ];sidelen.into()
         ];
 
-        let offset = scale * f32::from(sidelen - 1) * 0.5;
-
         for x in 0i16..sidelen.into() {
             for y in 0i16..sidelen.into() {
                 let sq = &mut squares[x as usize][y as usize];
@@ -58,10 +68,33 @@ impl Net {
                 sq.bottom = sample_column_grid(x, y+1, cg) != current;
                 sq.right  = sample_column_grid(x-1, y, cg) != current;
                 sq.left   = sample_column_grid(x+1, y, cg) != current;
+            }
+        }
+
+        let paths = trace_outlines(&squares);
+
+        let mut net = Self {
+            sidelen,
+            columns: cg.columns.clone(),
+            squares,
+            paths,
+            outlines: vec![],
+            layer,
+            pos,
+            opacity
+        };
+        net.place(pos, scale, edgethickness);
+        net
+    }
+    /// Moves and resizes the net, keeping its pattern
+    pub fn place(&mut self, pos: Vector2<f32>, scale: f32, edgethickness: f32) {
+        let offset = scale * f32::from(self.sidelen - 1) * 0.5;
 
+        for (x, col) in self.squares.iter_mut().enumerate() {
+            for (y, sq) in col.iter_mut().enumerate() {
                 let mut sqpos: Vector2<f32> = [0.0;2].into();
-                sqpos.x = sqpos.x + x as f32 * scale;
-                sqpos.y = sqpos.y - y as f32 * scale;
+                sqpos.x += x as f32 * scale;
+                sqpos.y -= y as f32 * scale;
 
                 sq.pos = sqpos;
 
@@ -70,23 +103,55 @@ impl Net {
 
                 sq.pos.x -= offset;
                 sq.pos.y += offset;
+
+                sq.scale = scale;
             }
         }
 
-        Self {
-            sidelen,
-            squares,
-            layer,
-            pos,
-            rot: 0.0,
-            opacity
-        }
+        // corners are half a square out from the square centres
+        let corner_pos = |c: Corner| -> Vector2<f32> {
+            [
+                (c[0] as f32 - 0.5) * scale - offset,
+                -(c[1] as f32 - 0.5) * scale + offset
+            ].into()
+        };
+        self.outlines = self.paths.iter().map(|o| {
+            let mut points: Vec<Vector2<f32>> = o.corners.iter().map(|c| corner_pos(*c)).collect();
+            // loose ends carry on half the width to look squared off, while ends against another
+            // line stop at its edge, so nothing is drawn twice and shows darker as the net fades
+            if !o.closed {
+                let last = points.len() - 1;
+                for (end, inner, meets) in [(0, 1, o.meets[0]), (last, last - 1, o.meets[1])] {
+                    let dir = (points[end] - points[inner]).normalize();
+                    points[end] += dir * edgethickness * if meets {-0.5} else {0.5};
+                }
+            }
+            Polyline::new(
+                points,
+                pos,
+                edgethickness,
+                Join::Miter,
+                Cap::Butt,
+                o.closed,
+                self.layer,
+                NET_EDGE_COLOUR,
+                self.opacity
+            )
+        }).collect();
+
+        self.pos = pos;
+    }
+    pub fn columns(&self) -> &Vec<Vec<u8>> {
+        &self.columns
     }
     pub fn set_opacity(&mut self, o: f32) {
         self.opacity = o;
         for s in self.squares.iter_mut().flatten() {
             s.opacity = o;
         }
+        for l in self.outlines.iter_mut() {
+            l.opacity = o;
+        }
     }
     pub fn square_debug_info(&self) -> String {
         let mut out: String = "".into();
@@ -120,8 +185,15 @@ impl ToVertInd2D for Net {
     fn layer(&self) -> u8 {
         self.layer
     }
-    fn to_vert_ind(&self) -> (Vec<super::vertex::Vertex2D>, Vec<u16>) {
-        self.squares.to_vert_ind()
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
+        let mut verts = vec![];
+        let mut inds = vec![];
+        self.append_vert_ind(&mut verts, &mut inds);
+        (verts, inds)
+    }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        self.squares.append_vert_ind(verts, inds);
+        self.outlines.append_vert_ind(verts, inds);
     }
 }
 
@@ -129,9 +201,20 @@ impl ToVertInd2D for &Net {
     fn layer(&self) -> u8 {
         (*self).layer()
     }
-    fn to_vert_ind(&self) -> (Vec<super::vertex::Vertex2D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
         (*self).to_vert_ind()
     }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        (*self).append_vert_ind(verts, inds)
+    }
+}
+
+// anywhere on the shaded squares or the lines round them, not the gaps between
+impl HitTest for Net {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        self.squares.iter().flatten().any(|s| s.fill && s.hit(p)) ||
+        self.outlines.iter().any(|l| l.hit(p))
+    }
 }
 
 impl Shape for Net {
@@ -139,13 +222,51 @@ impl Shape for Net {
         self.pos
     }
     fn set_pos(&mut self, p: Vector2<f32>) {
+        // the squares store their own positions, so they need to move with the net
+        let delta = p - self.pos;
+        for s in self.squares.iter_mut().flatten() {
+            s.pos += delta;
+        }
+        for l in self.outlines.iter_mut() {
+            l.pos = p;
+        }
         self.pos = p
     }
+    // nets are always drawn upright, which hit testing relies on
     fn get_rot(&self) -> f32 {
-        self.rot
+        0.0
     }
-    fn set_rot(&mut self, r: f32) {
-        self.rot = r
+    fn set_rot(&mut self, _: f32) {}
+}
+
+impl Tweenable for Net {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Position => Some(Value::Vec2(self.pos)),
+            // nets are never drawn rotated, so there's nothing to tween
+            Property::Rotation => None,
+            Property::Opacity => Some(Value::Float(self.opacity)),
+            Property::Scale => self.squares.first()
+                .and_then(|c| c.first())
+                .map(|s| Value::Float(s.scale)),
+            Property::Colour => None
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        match (p, v) {
+            (Property::Position, Value::Vec2(pos)) => self.set_pos(pos),
+            (Property::Opacity, Value::Float(o)) => self.set_opacity(o),
+            (Property::Scale, Value::Float(s)) => {
+                // edges stay in proportion to the squares
+                let Some(sq) = self.squares.first().and_then(|c| c.first()) else {
+                    return
+                };
+                let width = self.outlines.first().map_or(0.0, |l| l.width);
+                let thickness = if sq.scale == 0.0 {0.0} else {width * s / sq.scale};
+                self.place(self.pos, s, thickness);
+            }
+            _ => {}
+        }
     }
 }
 
@@ -161,7 +282,6 @@ pub struct NetSquare {
     pos: Vector2<f32>,
     rot: f32,
     scale: f32,
-    thickness: f32,
     pub fill: bool,
     opacity: f32
 }
@@ -173,7 +293,6 @@ impl NetSquare {
         pos: Vector2<f32>,
         rot: f32,
         scale: f32,
-        thickness: f32,
         fill: bool,
         opacity: f32
     ) -> Self {
@@ -187,7 +306,6 @@ impl NetSquare {
             pos,
             rot,
             scale,
-            thickness,
             fill,
             opacity
         }
@@ -209,20 +327,14 @@ impl ToVertInd2D for NetSquare {
     fn layer(&self) -> u8 {
         self.layer
     }
-    fn to_vert_ind(&self) -> (Vec<super::vertex::Vertex2D>, Vec<u16>) {
-        let mut rects: Vec<Rectangle> = vec![];
-
-        let base = Rectangle::new(
-            self.thickness,
-            self.scale + self.thickness * 0.9,
-            [0.0;2].into(), // to be reset on each clone
-            0.0, // same here
-            self.layer,
-            self.texindex,
-            false,
-            self.opacity
-        );
-
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
+        let mut verts = vec![];
+        let mut inds = vec![];
+        self.append_vert_ind(&mut verts, &mut inds);
+        (verts, inds)
+    }
+    // just the shading, the edges are drawn by the net so they can join up across squares
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
         let selfrepr = Rectangle::new(
             self.scale,
             self.scale,
@@ -233,50 +345,26 @@ impl ToVertInd2D for NetSquare {
             false,
             if self.fill {0.2 * self.opacity} else {0.0}
         );
-        rects.push(selfrepr);
-
-        if self.top {
-            let mut top = base.clone();
-            top.translate(self.pos);
-            top.translate([0.0,self.scale/2.0].into());
-            top.rotate_deg(90.0);
-            top.rotate_around_point_rad(self.rot, self.pos);
-            rects.push(top);
-        }
-        if self.bottom {
-            let mut bottom = base.clone();
-            bottom.translate(self.pos);
-            bottom.translate([0.0,-self.scale/2.0].into());
-            bottom.rotate_deg(90.0);
-            bottom.rotate_around_point_rad(self.rot, self.pos);
-            rects.push(bottom);
-        }
-        if self.left {
-            let mut left = base.clone();
-            left.translate(self.pos);
-            left.translate([self.scale/2.0,0.0].into());
-            left.rotate_around_point_rad(self.rot, self.pos);
-            rects.push(left);
-        }
-        if self.right {
-            let mut right = base.clone();
-            right.translate(self.pos);
-            right.translate([-self.scale/2.0,0.0].into());
-            right.rotate_around_point_rad(self.rot, self.pos);
-            rects.push(right);
-        }
-
-        rects.to_vert_ind()
+        selfrepr.append_vert_ind(verts, inds);
     }
 }
 
 impl ToVertInd2D for &NetSquare {
     fn layer(&self) -> u8 {
-        (*self).layer
+        self.layer
     }
-    fn to_vert_ind(&self) -> (Vec<super::vertex::Vertex2D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
         (*self).to_vert_ind()
     }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        (*self).append_vert_ind(verts, inds)
+    }
+}
+
+impl HitTest for NetSquare {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        Rectangle::new(self.scale, self.scale, self.pos, self.rot, self.layer, self.texindex, false, 0.0).hit(p)
+    }
 }
 
 impl Shape for NetSquare {
@@ -296,11 +384,130 @@ impl Shape for NetSquare {
 
 fn sample_column_grid(x: i16, y: i16, cg: &ColumnGrid) -> u8 {
     let sidelen: i16 = cg.sidelen.into();
-    if x < 0 || y < 0 {
-        0
-    } else if x >= sidelen || y >= sidelen {
+    if x < 0 || y < 0 || x >= sidelen || y >= sidelen {
         0
     } else {
         cg.columns[x as usize][y as usize]
     }
 }
+
+// joins up the edges drawn between squares into as few lines as possible,
+// carrying straight on through crossings so lines only end where an edge does
+fn trace_outlines(squares: &[Vec<NetSquare>]) -> Vec<Outline> {
+    // ordered collections, so the same net always traces the same way
+    let mut edges: BTreeSet<(Corner, Corner)> = BTreeSet::new();
+    let mut add = |a: Corner, b: Corner| {
+        edges.insert(if a < b {(a, b)} else {(b, a)});
+    };
+    for (x, col) in squares.iter().enumerate() {
+        for (y, sq) in col.iter().enumerate() {
+            let (x, y) = (x as i16, y as i16);
+            if sq.top {add([x, y], [x + 1, y])}
+            if sq.bottom {add([x, y + 1], [x + 1, y + 1])}
+            if sq.left {add([x + 1, y], [x + 1, y + 1])}
+            if sq.right {add([x, y], [x, y + 1])}
+        }
+    }
+
+    let mut neighbours: BTreeMap<Corner, Vec<Corner>> = BTreeMap::new();
+    for (a, b) in edges.iter() {
+        neighbours.entry(*a).or_default().push(*b);
+        neighbours.entry(*b).or_default().push(*a);
+    }
+    let degree = |c: Corner| neighbours.get(&c).map_or(0, |n| n.len());
+    let key = |a: Corner, b: Corner| if a < b {(a, b)} else {(b, a)};
+
+    let mut paths = vec![];
+    // lines that end somewhere first, then whatever is left is loops
+    let starts: Vec<Corner> = neighbours.keys()
+        .filter(|c| degree(**c) % 2 == 1)
+        .chain(neighbours.keys().filter(|c| degree(**c) % 2 == 0))
+        .copied()
+        .collect();
+
+    // follows a line on from its last two corners as far as it goes, using up the edges it takes
+    let extend = |path: &mut Vec<Corner>, edges: &mut BTreeSet<(Corner, Corner)>| loop {
+        let cur = path[path.len() - 1];
+        let prev = path[path.len() - 2];
+        let dir = [cur[0] - prev[0], cur[1] - prev[1]];
+        let free: Vec<Corner> = neighbours[&cur].iter()
+            .filter(|n| edges.contains(&key(cur, **n)))
+            .copied()
+            .collect();
+
+        // turn corners, go straight over crossings, and stop where lines meet any other way
+        let straight = [cur[0] + dir[0], cur[1] + dir[1]];
+        let next = match degree(cur) {
+            2 => free.first().copied(),
+            _ => free.iter().find(|n| **n == straight).copied()
+        };
+        let Some(next) = next else {
+            break
+        };
+        edges.remove(&key(cur, next));
+        path.push(next);
+    };
+
+    for start in starts {
+        while let Some(first) = neighbours[&start].iter().find(|n| edges.contains(&key(start, **n))).copied() {
+            edges.remove(&key(start, first));
+            let mut path = vec![start, first];
+            extend(&mut path, &mut edges);
+
+            // starting where lines meet, a line carrying on through is followed back the other way too,
+            // so it isn't split in two there
+            let behind = [start[0] * 2 - first[0], start[1] * 2 - first[1]];
+            if degree(start) > 2 && edges.remove(&key(start, behind)) {
+                let mut back = vec![start, behind];
+                extend(&mut back, &mut edges);
+                back.reverse();
+                back.pop();
+                back.extend(path);
+                path = back;
+            }
+
+            // back where it started, it's a loop if it turns the corner there or carries straight on through
+            let n = path.len();
+            let dir = |a: Corner, b: Corner| [b[0] - a[0], b[1] - a[1]];
+            let closed = n > 3 && path[0] == path[n - 1]
+                && (degree(path[0]) == 2 || dir(path[0], path[1]) == dir(path[n - 2], path[n - 1]));
+            if closed {
+                path.pop();
+            }
+            let meets = [degree(path[0]) > 2, degree(path[path.len() - 1]) > 2];
+            paths.push(Outline { corners: path, closed, meets });
+        }
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use super::*;
+
+    #[test]
+    fn lines_carry_on_through_where_others_end() {
+        let mut rng = Pcg32::seed_from_u64(1);
+        for sidelen in 3..=8 {
+            for _ in 0..50 {
+                let cg = ColumnGrid::new_random([0.0;3].into(), None, sidelen, &mut rng);
+                let net = Net::from_columngrid(&cg, 0, 0, [0.0;2].into(), 1.0, 0.1, 1.0);
+                for o in net.paths.iter().filter(|o| !o.closed) {
+                    let ends = [(o.corners[0], o.meets[0]), (o.corners[o.corners.len() - 1], o.meets[1])];
+                    for (corner, _) in ends.into_iter().filter(|(_, meets)| *meets) {
+                        // the line it stops against passes through the corner, rather than ending there too
+                        let through = net.paths.iter().any(|p| {
+                            let inner = if p.closed {&p.corners[..]} else {&p.corners[1..p.corners.len() - 1]};
+                            inner.contains(&corner)
+                        });
+                        assert!(through, "{:?} ends at {corner:?} with nothing carrying on through", o.corners);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,12 +1,53 @@
 use cgmath::Vector2;
 
-use crate::config::NUMBER_TEX_INDEX_START;
+use crate::{config::{NUMBER_NARROW_ADVANCE, NUMBER_SYMBOLS, NUMBER_TEX_INDEX_START}, tween::{Easing, Property, Tweenable, Value}};
 
-use super::{rectangle::Rectangle, shape::Shape, tovertind2d::ToVertInd2D, vertex::Vertex2D};
+use super::{hittest::{to_local, HitTest}, rectangle::Rectangle, shape::Shape, text::Align, tovertind2d::ToVertInd2D, vertex::Vertex2D};
 
+/// How a `Number` turns its value into characters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumberFormat {
+    Integer, // rounded to the nearest whole number
+    Signed, // always shows a sign, for deltas like +3
+    Percent(u8), // the value is a fraction, shown as a percentage with this many decimal places
+    #[cfg_attr(not(test), allow(dead_code))] // rounds are at most a minute, so every timer fits Tenths
+    Minutes, // seconds shown as m:ss, rounded up so a countdown only reads 0:00 once it's done
+    Tenths // seconds shown as s.t, rounded up like Minutes
+}
+
+impl NumberFormat {
+    pub fn format(&self, v: f32) -> String {
+        match *self {
+            NumberFormat::Integer => format!("{}", v.round() as i64),
+            NumberFormat::Signed => format!("{:+}", v.round() as i64),
+            NumberFormat::Percent(places) => format!("{:.*}%", places as usize, v * 100.0),
+            NumberFormat::Minutes => {
+                let s = v.max(0.0).ceil() as u64;
+                format!("{}:{:02}", s / 60, s % 60)
+            }
+            NumberFormat::Tenths => format!("{:.1}", (v.max(0.0) * 10.0).ceil() / 10.0)
+        }
+    }
+}
+
+// an animated change from one value to another
+#[derive(Clone, Copy, Debug)]
+struct Roll {
+    from: f32,
+    elapsed: f32,
+    len: f32
+}
+
+/// A number drawn with the digit textures.
+/// `pos` is the centre of the first character for left aligned numbers, the middle of the
+/// whole number for centred ones, and a character's width right of the last character for right
+/// aligned ones, so right aligned numbers get a character's worth of padding from whatever they're against.
 #[derive(Clone)]
 pub struct Number {
-    value: u32,
+    value: f32,
+    shown: f32, // differs from value while rolling
+    roll: Option<Roll>,
+    format: NumberFormat,
     pos: Vector2<f32>,
     rot: f32,
     layer: u8,
@@ -14,12 +55,14 @@ pub struct Number {
     digit_w: f32,
     digit_h: f32,
     digit_gap: f32,
-    right_aligned: bool,
+    align: Align,
 }
 
 impl Number {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        value: u32,
+        value: f32,
+        format: NumberFormat,
         pos: Vector2<f32>,
         rot: f32,
         layer: u8,
@@ -27,13 +70,104 @@ impl Number {
         digit_w: f32,
         digit_h: f32,
         digit_gap: f32,
-        right_aligned: bool,
+        align: Align,
     ) -> Self {
-        Self {value, pos, rot, layer, opacity, digit_w, digit_h, digit_gap, right_aligned}
+        Self {value, shown: value, roll: None, format, pos, rot, layer, opacity, digit_w, digit_h, digit_gap, align}
+    }
+    /// Jumps straight to a value, cancelling any roll
+    pub fn set(&mut self, v: f32) {
+        self.value = v;
+        self.shown = v;
+        self.roll = None;
     }
-    pub fn set(&mut self, v: u32) {
+    /// Counts from whatever is currently shown up (or down) to a value over `len` seconds
+    pub fn roll_to(&mut self, v: f32, len: f32) {
+        if len <= 0.0 {
+            self.set(v);
+            return
+        }
+        self.roll = Some(Roll { from: self.shown, elapsed: 0.0, len });
         self.value = v;
     }
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+    pub fn set_opacity(&mut self, o: f32) {
+        self.opacity = o;
+    }
+    /// Whether `update` has anything to do. Worth checking first for a
+    /// `Retained` number, so it isn't re-tessellated when nothing changes
+    pub fn is_rolling(&self) -> bool {
+        self.roll.is_some()
+    }
+    /// Advances any roll by `dt` seconds
+    pub fn update(&mut self, dt: f32) {
+        let Some(roll) = self.roll.as_mut() else {
+            return
+        };
+        roll.elapsed += dt;
+        let t = Easing::CubicOut.apply(roll.elapsed / roll.len);
+        self.shown = roll.from + (self.value - roll.from) * t;
+        if roll.elapsed >= roll.len {
+            self.set(self.value);
+        }
+    }
+}
+
+// the texture for a character, and how far along it moves the next one.
+// anything without a texture is drawn as a gap rather than failing
+fn glyph(c: char) -> (Option<u32>, bool) {
+    let narrow = c == '.' || c == ':';
+    let tex = match c.to_digit(10) {
+        Some(d) => Some(d + NUMBER_TEX_INDEX_START),
+        None => NUMBER_SYMBOLS.chars().position(|s| s == c).map(|i| i as u32 + 10 + NUMBER_TEX_INDEX_START)
+    };
+    (tex, narrow)
+}
+
+impl Number {
+    // the texture for each character and where its centre goes, relative to pos before rotating
+    fn layout(&self) -> Vec<(Option<u32>, f32)> {
+        let text = self.format.format(self.shown);
+        let step = self.digit_w + self.digit_gap;
+
+        // lay the characters out left to right from 0, by their centres
+        let mut x = 0.0;
+        let mut prev_half = 0.0;
+        let mut chars = vec![];
+        for c in text.chars() {
+            let (tex, narrow) = glyph(c);
+            let half = if narrow {step * NUMBER_NARROW_ADVANCE} else {step} * 0.5;
+            if !chars.is_empty() {
+                x += prev_half + half;
+            }
+            prev_half = half;
+            chars.push((tex, x));
+        }
+
+        let offset = match self.align {
+            Align::Left => 0.0,
+            Align::Centre => -x * 0.5,
+            Align::Right => -x - step
+        };
+        for (_, x) in chars.iter_mut() {
+            *x += offset;
+        }
+        chars
+    }
+}
+
+// anywhere in the box round the characters, so the gaps between digits count too
+impl HitTest for Number {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        let chars = self.layout();
+        let (Some(first), Some(last)) = (chars.first(), chars.last()) else {
+            return false
+        };
+        let p = to_local(p, self.pos, self.rot);
+        let half_w = self.digit_w * 0.5;
+        first.1 - half_w < p.x && p.x < last.1 + half_w && p.y.abs() < self.digit_h * 0.5
+    }
 }
 
 impl Shape for Number {
@@ -51,44 +185,90 @@ impl Shape for Number {
     }
 }
 
+impl Tweenable for Number {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Position => Some(Value::Vec2(self.pos)),
+            Property::Rotation => Some(Value::Float(self.rot)),
+            Property::Opacity => Some(Value::Float(self.opacity)),
+            _ => None
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        match (p, v) {
+            (Property::Position, Value::Vec2(pos)) => self.pos = pos,
+            (Property::Rotation, Value::Float(r)) => self.rot = r,
+            (Property::Opacity, Value::Float(o)) => self.opacity = o,
+            _ => {}
+        }
+    }
+}
+
 impl ToVertInd2D for Number {
     fn layer(&self) -> u8 {
         self.layer
     }
-    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u16>) {
-        let v_str = format!("{}", self.value);
-
-        let mut rects: Vec<Rectangle> = vec![];
-
-        for (i,d) in v_str.chars().enumerate() {
-            let texindex = d.to_digit(10).unwrap() + NUMBER_TEX_INDEX_START;
-            let pos: Vector2<f32> = match self.right_aligned {
-                false => {
-				    [(self.digit_w + self.digit_gap) * i as f32,0.0].into()
-                }
-                true => {
-                    let total_digits = v_str.len();
-                    let offset_per_digit = ( self.digit_w + self.digit_gap ) * -1.0;
-                    let x = offset_per_digit * (total_digits - i) as f32;
-                    [x,0.0].into()
-                }
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
+        let mut verts = vec![];
+        let mut inds = vec![];
+        self.append_vert_ind(&mut verts, &mut inds);
+        (verts, inds)
+    }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        for (tex, x) in self.layout() {
+            // narrow characters keep the full texture size, their glyphs are thin enough
+            // to sit in the gap without squashing them
+            let Some(texindex) = tex else {
+                continue
             };
             let mut r = Rectangle::new(
                 self.digit_w,
-				self.digit_h,
-				pos,
-				0.0,
-				self.layer,
-				texindex,
-				true,
-				self.opacity
-			);
-			r.translate(self.pos);
-			r.rotate_around_point_rad(self.rot, self.pos);
-			rects.push(r);
+                self.digit_h,
+                [x, 0.0].into(),
+                0.0,
+                self.layer,
+                texindex,
+                true,
+                self.opacity
+            );
+            r.translate(self.pos);
+            r.rotate_around_point_rad(self.rot, self.pos);
+            r.append_vert_ind(verts, inds);
         }
-
-        rects.to_vert_ind()
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minutes_round_up_to_the_next_second() {
+        let f = NumberFormat::Minutes;
+        assert_eq!(f.format(59.2), "1:00");
+        assert_eq!(f.format(60.0), "1:00");
+        assert_eq!(f.format(60.01), "1:01");
+        assert_eq!(f.format(125.0), "2:05");
+    }
+
+    #[test]
+    fn countdowns_only_read_zero_once_done() {
+        for f in [NumberFormat::Minutes, NumberFormat::Tenths] {
+            assert_eq!(f.format(0.0), f.format(-3.0));
+            assert_ne!(f.format(0.001), f.format(0.0));
+        }
+        assert_eq!(NumberFormat::Minutes.format(0.001), "0:01");
+        assert_eq!(NumberFormat::Minutes.format(0.0), "0:00");
+        assert_eq!(NumberFormat::Tenths.format(0.001), "0.1");
+        assert_eq!(NumberFormat::Tenths.format(0.0), "0.0");
+    }
+
+    #[test]
+    fn signed_always_shows_a_sign() {
+        let f = NumberFormat::Signed;
+        assert_eq!(f.format(3.0), "+3");
+        assert_eq!(f.format(0.0), "+0");
+        assert_eq!(f.format(-2.6), "-3");
+        assert_eq!(NumberFormat::Integer.format(3.0), "3");
+    }
+}
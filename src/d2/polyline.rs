@@ -0,0 +1,104 @@
+use cgmath::Vector2;
+
+use crate::tween::{Property, Tweenable, Value};
+
+use super::{hittest::{on_stroke, to_local, HitTest}, shape::Shape, tessellate::{Cap, Join, Mesh}, tovertind2d::ToVertInd2D, vertex::Vertex2D};
+
+/// A solid colour line through a list of points.
+/// Points are relative to `pos`, and the whole line rotates about it
+#[derive(Clone, Debug)]
+pub struct Polyline {
+    pub points: Vec<Vector2<f32>>,
+    pub pos: Vector2<f32>,
+    pub rot: f32, // radians
+    pub width: f32,
+    pub join: Join,
+    pub cap: Cap, // unused if closed
+    pub closed: bool, // joins the last point back to the first
+    pub layer: u8,
+    pub colour: [f32;3],
+    pub opacity: f32
+}
+
+impl Polyline {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        points: Vec<Vector2<f32>>,
+        pos: Vector2<f32>,
+        width: f32,
+        join: Join,
+        cap: Cap,
+        closed: bool,
+        layer: u8,
+        colour: [f32;3],
+        opacity: f32
+    ) -> Self {
+        Self { points, pos, rot: 0.0, width, join, cap, closed, layer, colour, opacity }
+    }
+}
+
+impl ToVertInd2D for Polyline {
+    fn layer(&self) -> u8 {
+        self.layer
+    }
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
+        let mut verts = vec![];
+        let mut inds = vec![];
+        self.append_vert_ind(&mut verts, &mut inds);
+        (verts, inds)
+    }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        let mut mesh = Mesh::new();
+        mesh.stroke(&self.points, self.closed, self.width, self.join, self.cap);
+        mesh.append_to(verts, inds, self.pos, self.rot, self.layer, self.colour, self.opacity);
+    }
+}
+
+impl HitTest for Polyline {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        on_stroke(to_local(p, self.pos, self.rot), &self.points, self.closed, self.width, self.cap)
+    }
+}
+
+impl Shape for Polyline {
+    fn get_pos(&self) -> Vector2<f32> {
+        self.pos
+    }
+    fn set_pos(&mut self, p: Vector2<f32>) {
+        self.pos = p
+    }
+    fn get_rot(&self) -> f32 {
+        self.rot
+    }
+    fn set_rot(&mut self, r: f32) {
+        self.rot = r
+    }
+}
+
+impl Tweenable for Polyline {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Position => Some(Value::Vec2(self.pos)),
+            Property::Rotation => Some(Value::Float(self.rot)),
+            Property::Opacity => Some(Value::Float(self.opacity)),
+            Property::Colour => {
+                let [r, g, b] = self.colour;
+                Some(Value::Colour([r, g, b, self.opacity]))
+            }
+            Property::Scale => Some(Value::Float(self.width))
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        match (p, v) {
+            (Property::Position, Value::Vec2(pos)) => self.pos = pos,
+            (Property::Rotation, Value::Float(r)) => self.rot = r,
+            (Property::Opacity, Value::Float(o)) => self.opacity = o,
+            (Property::Colour, Value::Colour([r, g, b, a])) => {
+                self.colour = [r, g, b];
+                self.opacity = a;
+            }
+            (Property::Scale, Value::Float(w)) => self.width = w,
+            _ => {}
+        }
+    }
+}
@@ -1,7 +1,9 @@
 
 use cgmath::Vector2;
 
-use super::{shape::Shape, tovertind2d::ToVertInd2D, vertex::Vertex2D};
+use crate::tween::{Property, Tweenable, Value};
+
+use super::{hittest::HitTest, mouseutils::is_in_rect, shape::Shape, tovertind2d::ToVertInd2D, vertex::Vertex2D};
 
 #[derive(Clone, Debug)]
 pub struct Rectangle {
@@ -16,6 +18,7 @@ pub struct Rectangle {
 }
 
 impl Rectangle {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         width: f32,
         height: f32,
@@ -28,6 +31,7 @@ impl Rectangle {
     ) -> Self {
         Self {width, height, pos, rot, layer, texindex, opacity, flipped}
     }
+    #[allow(dead_code)] // nothing textured fades at the moment
     pub fn set_opacity(&mut self, new_opacity: f32) {
         self.opacity = new_opacity
     }
@@ -48,41 +52,74 @@ impl Shape for Rectangle {
     }
 }
 
+impl HitTest for Rectangle {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        is_in_rect(p, self)
+    }
+}
+
+impl Tweenable for Rectangle {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Position => Some(Value::Vec2(self.pos)),
+            Property::Rotation => Some(Value::Float(self.rot)),
+            Property::Opacity => Some(Value::Float(self.opacity)),
+            Property::Scale => Some(Value::Vec2([self.width, self.height].into())),
+            Property::Colour => None
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        match (p, v) {
+            (Property::Position, Value::Vec2(pos)) => self.pos = pos,
+            (Property::Rotation, Value::Float(r)) => self.rot = r,
+            (Property::Opacity, Value::Float(o)) => self.opacity = o,
+            (Property::Scale, Value::Vec2(s)) => {
+                self.width = s.x;
+                self.height = s.y;
+            }
+            _ => {}
+        }
+    }
+}
+
 impl ToVertInd2D for Rectangle {
-    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
+        let mut verts = vec![];
+        let mut inds = vec![];
+        self.append_vert_ind(&mut verts, &mut inds);
+        (verts, inds)
+    }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
         let xoff = self.width / 2.0;
         let yoff = self.height / 2.0;
         let tex_index = self.texindex;
         let alpha = self.opacity;
         let depth = 0.5 - (self.layer as f32 / 32.0);
         let tx = if self.flipped {0.0} else {1.0};
-        let mut verts = vec![
-            Vertex2D {pos: [-xoff, -yoff ], tex_coords: [    tx, 1.0], depth, tex_index, alpha},
-            Vertex2D {pos: [ xoff, -yoff ], tex_coords: [1.0-tx, 1.0], depth, tex_index, alpha},
-            Vertex2D {pos: [-xoff,  yoff ], tex_coords: [    tx, 0.0], depth, tex_index, alpha},
-            Vertex2D {pos: [ xoff,  yoff ], tex_coords: [1.0-tx, 0.0], depth, tex_index, alpha}
+        let mut corners = [
+            Vertex2D {pos: [-xoff, -yoff ], tex_coords: [    tx, 1.0], depth, tex_index, alpha, colour: [1.0;3]},
+            Vertex2D {pos: [ xoff, -yoff ], tex_coords: [1.0-tx, 1.0], depth, tex_index, alpha, colour: [1.0;3]},
+            Vertex2D {pos: [-xoff,  yoff ], tex_coords: [    tx, 0.0], depth, tex_index, alpha, colour: [1.0;3]},
+            Vertex2D {pos: [ xoff,  yoff ], tex_coords: [1.0-tx, 0.0], depth, tex_index, alpha, colour: [1.0;3]}
         ];
 
         // rotate all vertices
-        for v in verts.iter_mut() {
+        for v in corners.iter_mut() {
             v.rotate_around_point_rad(self.rot, [0.0;2].into());
         }
 
         // translate all vertices
-        for v in verts.iter_mut() {
-            v.pos[0] = v.pos[0] + self.pos.x;
-            v.pos[1] = v.pos[1] + self.pos.y;
+        for v in corners.iter_mut() {
+            v.pos[0] += self.pos.x;
+            v.pos[1] += self.pos.y;
         }
 
-        let inds = vec![
-            0, 1, 2,
-            1, 3, 2
-        ];
-
-        (
-            verts,
-            inds
-        )
+        let base = verts.len() as u32;
+        verts.extend_from_slice(&corners);
+        inds.extend_from_slice(&[
+            base, base + 1, base + 2,
+            base + 1, base + 3, base + 2
+        ]);
     }
     fn layer(&self) -> u8 {
         self.layer
@@ -90,15 +127,13 @@ impl ToVertInd2D for Rectangle {
 }
 
 impl ToVertInd2D for &Rectangle {
-    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
         (*self).to_vert_ind()
     }
     fn layer(&self) -> u8 {
         (*self).layer()
     }
-}
-
-pub fn depth_sort(mut inp: Vec<impl ToVertInd2D>) -> Vec<impl ToVertInd2D> {
-    inp.sort_unstable_by(|a, b| a.layer().partial_cmp(&b.layer()).unwrap());
-    inp
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        (*self).append_vert_ind(verts, inds)
+    }
 }
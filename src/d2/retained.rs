@@ -0,0 +1,89 @@
+use std::{ops::{Deref, DerefMut}, sync::atomic::{AtomicU64, Ordering}};
+
+use crate::tween::{Property, Tweenable, Value};
+
+use super::{tovertind2d::ToVertInd2D, vertex::Vertex2D};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Wraps a shape and caches its tessellated geometry.
+/// Any mutable access to the shape marks the cache as dirty, so it is only
+/// re-tessellated on frames where something about it actually changed.
+pub struct Retained<T> {
+    shape: T,
+    id: u64,
+    version: u64,
+    dirty: bool,
+    verts: Vec<Vertex2D>,
+    inds: Vec<u32>
+}
+
+impl<T: ToVertInd2D> Retained<T> {
+    pub fn new(shape: T) -> Self {
+        Self {
+            shape,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            version: 0,
+            dirty: true,
+            verts: vec![],
+            inds: vec![]
+        }
+    }
+}
+
+impl<T> Deref for Retained<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.shape
+    }
+}
+
+impl<T> DerefMut for Retained<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.dirty = true;
+        &mut self.shape
+    }
+}
+
+/// Object-safe view of a `Retained`, so differently typed shapes can be drawn together
+pub trait RetainedShape {
+    /// Re-tessellates the shape if it has changed since the last call
+    fn refresh(&mut self);
+    fn geometry(&self) -> (&[Vertex2D], &[u32]);
+    /// (id, version), changes whenever the cached geometry does
+    fn key(&self) -> (u64, u64);
+    fn layer(&self) -> u8;
+}
+
+impl<T: ToVertInd2D> RetainedShape for Retained<T> {
+    fn refresh(&mut self) {
+        if !self.dirty {
+            return
+        }
+        // clearing keeps the allocations around for next time
+        self.verts.clear();
+        self.inds.clear();
+        self.shape.append_vert_ind(&mut self.verts, &mut self.inds);
+        self.version += 1;
+        self.dirty = false;
+    }
+    fn geometry(&self) -> (&[Vertex2D], &[u32]) {
+        (&self.verts, &self.inds)
+    }
+    fn key(&self) -> (u64, u64) {
+        (self.id, self.version)
+    }
+    fn layer(&self) -> u8 {
+        self.shape.layer()
+    }
+}
+
+// goes through DerefMut, so tweening a shape marks it as needing re-tessellation
+impl<T: Tweenable> Tweenable for Retained<T> {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        self.shape.get_property(p)
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        (**self).set_property(p, v)
+    }
+}
@@ -0,0 +1,134 @@
+use std::f32::consts::PI;
+
+use cgmath::Vector2;
+
+use crate::tween::{Property, Tweenable, Value};
+
+use super::{hittest::HitTest, mouseutils::{is_in_rounded_rect, rounded_rect_distance}, shape::Shape, tessellate::{arc_points, Cap, Join, Mesh, Style}, tovertind2d::ToVertInd2D, vertex::Vertex2D};
+
+/// A solid colour rectangle with rounded corners, filled or outlined.
+/// A radius of 0 gives square corners, and radii too big for the rectangle are clamped
+#[derive(Clone, Debug)]
+pub struct RoundedRect {
+    pub pos: Vector2<f32>, // centre
+    pub rot: f32, // radians
+    pub width: f32,
+    pub height: f32,
+    pub radius: f32,
+    pub style: Style, // strokes are centred on the edge
+    pub layer: u8,
+    pub colour: [f32;3],
+    pub opacity: f32
+}
+
+impl RoundedRect {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        width: f32,
+        height: f32,
+        radius: f32,
+        pos: Vector2<f32>,
+        style: Style,
+        layer: u8,
+        colour: [f32;3],
+        opacity: f32
+    ) -> Self {
+        Self { pos, rot: 0.0, width, height, radius, style, layer, colour, opacity }
+    }
+    pub fn set_opacity(&mut self, o: f32) {
+        self.opacity = o;
+    }
+
+    // anticlockwise from the end of the bottom edge
+    fn outline(&self) -> Vec<Vector2<f32>> {
+        let (hw, hh) = (self.width * 0.5, self.height * 0.5);
+        let r = self.radius.clamp(0.0, hw.min(hh));
+        let corners = [
+            ([hw - r, -hh + r], -PI * 0.5),
+            ([hw - r, hh - r], 0.0),
+            ([-hw + r, hh - r], PI * 0.5),
+            ([-hw + r, -hh + r], PI)
+        ];
+        let mut out = vec![];
+        for (c, start) in corners {
+            arc_points(c.into(), r, start, PI * 0.5, &mut out);
+        }
+        out
+    }
+}
+
+impl ToVertInd2D for RoundedRect {
+    fn layer(&self) -> u8 {
+        self.layer
+    }
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
+        let mut verts = vec![];
+        let mut inds = vec![];
+        self.append_vert_ind(&mut verts, &mut inds);
+        (verts, inds)
+    }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        let outline = self.outline();
+        let mut mesh = Mesh::new();
+        match self.style {
+            Style::Fill => mesh.fill_convex(&outline),
+            Style::Stroke(w) => mesh.stroke(&outline, true, w, Join::Miter, Cap::Butt)
+        }
+        mesh.append_to(verts, inds, self.pos, self.rot, self.layer, self.colour, self.opacity);
+    }
+}
+
+impl HitTest for RoundedRect {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        match self.style {
+            Style::Fill => is_in_rounded_rect(p, self),
+            Style::Stroke(w) => rounded_rect_distance(p, self).abs() <= w * 0.5
+        }
+    }
+}
+
+impl Shape for RoundedRect {
+    fn get_pos(&self) -> Vector2<f32> {
+        self.pos
+    }
+    fn set_pos(&mut self, p: Vector2<f32>) {
+        self.pos = p
+    }
+    fn get_rot(&self) -> f32 {
+        self.rot
+    }
+    fn set_rot(&mut self, r: f32) {
+        self.rot = r
+    }
+}
+
+impl Tweenable for RoundedRect {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Position => Some(Value::Vec2(self.pos)),
+            Property::Rotation => Some(Value::Float(self.rot)),
+            Property::Opacity => Some(Value::Float(self.opacity)),
+            Property::Colour => {
+                let [r, g, b] = self.colour;
+                Some(Value::Colour([r, g, b, self.opacity]))
+            }
+            Property::Scale => Some(Value::Vec2([self.width, self.height].into()))
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        match (p, v) {
+            (Property::Position, Value::Vec2(pos)) => self.pos = pos,
+            (Property::Rotation, Value::Float(r)) => self.rot = r,
+            (Property::Opacity, Value::Float(o)) => self.opacity = o,
+            (Property::Colour, Value::Colour([r, g, b, a])) => {
+                self.colour = [r, g, b];
+                self.opacity = a;
+            }
+            (Property::Scale, Value::Vec2(s)) => {
+                self.width = s.x;
+                self.height = s.y;
+            }
+            _ => {}
+        }
+    }
+}
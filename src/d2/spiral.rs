@@ -1,6 +1,8 @@
-use cgmath::Vector2;
+use cgmath::{InnerSpace, Vector2};
 
-use super::{shape::Shape, tovertind2d::ToVertInd2D, vertex::Vertex2D};
+use crate::tween::{Property, Tweenable, Value};
+
+use super::{hittest::HitTest, shape::Shape, tovertind2d::ToVertInd2D, vertex::Vertex2D};
 
 #[derive(Clone)]
 pub struct Spiral {
@@ -28,6 +30,9 @@ impl Spiral {
     pub fn set_depth(&mut self, d: f32) {
         self.depth = d;
     }
+    pub fn depth(&self) -> f32 {
+        self.depth
+    }
     pub fn set_opacity(&mut self, o: f32) {
         self.alpha = o;
     }
@@ -37,9 +42,14 @@ impl ToVertInd2D for Spiral {
     fn layer(&self) -> u8 {
         0
     }
-    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
         let mut verts: Vec<Vertex2D> = vec![];
-        let mut inds: Vec<u16> = vec![];
+        let mut inds: Vec<u32> = vec![];
+        self.append_vert_ind(&mut verts, &mut inds);
+        (verts, inds)
+    }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        let start = verts.len();
 
         let depth = self.depth;
         let tex_coords = [0.0,0.0];
@@ -47,12 +57,12 @@ impl ToVertInd2D for Spiral {
         let alpha = self.alpha;
 
         let mut first_out_point = Vertex2D {
-            pos: [0.0,self.outer_radius], depth, tex_coords, tex_index, alpha
+            pos: [0.0,self.outer_radius], depth, tex_coords, tex_index, alpha, colour: [1.0;3]
         };
         first_out_point.rotate_deg(0.0);
 
         let mut first_in_point = Vertex2D {
-            pos: [0.0,self.inner_radius], depth, tex_coords, tex_index, alpha
+            pos: [0.0,self.inner_radius], depth, tex_coords, tex_index, alpha, colour: [1.0;3]
         };
         first_in_point.rotate_deg(0.0);
 
@@ -67,19 +77,17 @@ impl ToVertInd2D for Spiral {
                 self.outer_radius,
                 r,
                 depth,
-                &mut verts,
-                &mut inds
+                verts,
+                inds
             );
         }
 
-        for v in verts.iter_mut() {
+        for v in verts[start..].iter_mut() {
             v.translate(self.pos);
             v.alpha = self.alpha;
             v.tex_index = self.tex_index;
             v.tex_coords = v.pos;
         }
-
-        (verts, inds)
     }
 }
 
@@ -98,26 +106,53 @@ impl Shape for Spiral {
     }
 }
 
-fn gen_verts_deg(in_r: f32, out_r: f32, rot: f32, depth: f32, vb: &mut Vec<Vertex2D>, ib: &mut Vec<u16>) {
+// the whole band, however much of it the timer is currently showing
+impl HitTest for Spiral {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        let d = (p - self.pos).magnitude();
+        self.inner_radius <= d && d <= self.outer_radius
+    }
+}
+
+impl Tweenable for Spiral {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Position => Some(Value::Vec2(self.pos)),
+            Property::Rotation => Some(Value::Float(self.rot)),
+            Property::Opacity => Some(Value::Float(self.alpha)),
+            _ => None
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        match (p, v) {
+            (Property::Position, Value::Vec2(pos)) => self.pos = pos,
+            (Property::Rotation, Value::Float(r)) => self.rot = r,
+            (Property::Opacity, Value::Float(o)) => self.set_opacity(o),
+            _ => {}
+        }
+    }
+}
+
+fn gen_verts_deg(in_r: f32, out_r: f32, rot: f32, depth: f32, vb: &mut Vec<Vertex2D>, ib: &mut Vec<u32>) {
     // most of these are overriden in the to_vert_ind impl
     let alpha = 0.0;
     let tex_coords = [0.0,0.0];
     let tex_index = 0;
 
     let mut in_point = Vertex2D {
-        pos: [0.0,in_r], depth, tex_coords, tex_index, alpha
+        pos: [0.0,in_r], depth, tex_coords, tex_index, alpha, colour: [1.0;3]
     };
     in_point.rotate_around_point_deg(rot, [0.0;2].into());
 
     let mut out_point = Vertex2D {
-        pos: [0.0,out_r], depth, tex_coords, tex_index, alpha
+        pos: [0.0,out_r], depth, tex_coords, tex_index, alpha, colour: [1.0;3]
     };
     out_point.rotate_around_point_deg(rot, [0.0;2].into());
 
-    let last_out_ind = vb.len() as u16 - 2;
-    let last_in_ind = vb.len() as u16 - 1;
-    let this_out_ind = vb.len() as u16;
-    let this_in_ind = vb.len() as u16 + 1;
+    let last_out_ind = vb.len() as u32 - 2;
+    let last_in_ind = vb.len() as u32 - 1;
+    let this_out_ind = vb.len() as u32;
+    let this_in_ind = vb.len() as u32 + 1;
 
     vb.push(out_point);
     vb.push(in_point);
@@ -0,0 +1,392 @@
+use std::f32::consts::PI;
+
+use cgmath::{InnerSpace, Vector2};
+
+use crate::config::{SHAPE_MAX_SEGMENTS, SHAPE_MITER_LIMIT, SHAPE_TOLERANCE, SOLID_TEX_INDEX};
+
+use super::vertex::Vertex2D;
+
+/// Whether a closed shape is filled in or just outlined
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    Fill,
+    Stroke(f32) // outline width, in GUI units
+}
+
+/// How the corners between the segments of a stroke are drawn
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Join {
+    Miter, // sharp, falls back to Bevel past SHAPE_MITER_LIMIT
+    Bevel,
+    #[cfg_attr(not(test), allow(dead_code))] // every stroke in the game has sharp or bevelled corners so far
+    Round
+}
+
+/// How the open ends of a stroke are drawn
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cap {
+    Butt, // stops dead at the end point
+    Square, // carries on for half the width
+    Round
+}
+
+/// Number of segments needed for an arc to stay within SHAPE_TOLERANCE of a true circle,
+/// so curves stay smooth however large they're drawn without wasting vertices on small ones
+pub fn segments_for(radius: f32, sweep: f32) -> usize {
+    if radius <= SHAPE_TOLERANCE {
+        return 1
+    }
+    let step = 2.0 * (1.0 - SHAPE_TOLERANCE / radius).acos();
+    ((sweep.abs() / step).ceil() as usize).clamp(1, SHAPE_MAX_SEGMENTS)
+}
+
+/// Appends points along an arc, including both ends. Angles are in radians, anticlockwise from +x
+pub fn arc_points(centre: Vector2<f32>, radius: f32, start: f32, sweep: f32, out: &mut Vec<Vector2<f32>>) {
+    let n = segments_for(radius, sweep);
+    for i in 0..=n {
+        let a = start + sweep * i as f32 / n as f32;
+        out.push(centre + Vector2::new(a.cos(), a.sin()) * radius);
+    }
+}
+
+// left hand normal
+fn normal(d: Vector2<f32>) -> Vector2<f32> {
+    Vector2::new(-d.y, d.x)
+}
+
+fn angle(v: Vector2<f32>) -> f32 {
+    v.y.atan2(v.x)
+}
+
+/// Triangles in a shape's local space, turned into vertices once the shape's position is applied
+#[derive(Default)]
+pub struct Mesh {
+    points: Vec<Vector2<f32>>,
+    inds: Vec<u32>
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, p: Vector2<f32>) -> u32 {
+        self.points.push(p);
+        self.points.len() as u32 - 1
+    }
+
+    fn tri(&mut self, a: u32, b: u32, c: u32) {
+        self.inds.extend_from_slice(&[a, b, c]);
+    }
+
+    // triangles fanning out from `centre` to each consecutive pair of `rim` points
+    fn fan(&mut self, centre: u32, rim: &[Vector2<f32>]) {
+        let mut prev = None;
+        for p in rim {
+            let i = self.push(*p);
+            if let Some(prev) = prev {
+                self.tri(centre, prev, i);
+            }
+            prev = Some(i);
+        }
+    }
+
+    /// Fills a convex outline
+    pub fn fill_convex(&mut self, outline: &[Vector2<f32>]) {
+        if outline.len() < 3 {
+            return
+        }
+        let first = self.push(outline[0]);
+        self.fan(first, &outline[1..]);
+    }
+
+    /// Fills the band between two circles
+    pub fn ring(&mut self, centre: Vector2<f32>, inner: f32, outer: f32, start: f32, sweep: f32) {
+        let n = segments_for(outer, sweep);
+        let base = self.points.len() as u32;
+        for i in 0..=n {
+            let a = start + sweep * i as f32 / n as f32;
+            let dir = Vector2::new(a.cos(), a.sin());
+            self.push(centre + dir * outer);
+            self.push(centre + dir * inner);
+        }
+        for i in 0..n as u32 {
+            let (o0, i0, o1, i1) = (base + i * 2, base + i * 2 + 1, base + i * 2 + 2, base + i * 2 + 3);
+            self.tri(o0, o1, i0);
+            self.tri(o1, i1, i0);
+        }
+    }
+
+    /// Strokes a line through `points`, joining the last point back to the first if `closed`.
+    /// Neighbouring segments share the vertices at their joins, so nothing is drawn twice
+    /// and translucent strokes don't get darker at the corners.
+    pub fn stroke(&mut self, points: &[Vector2<f32>], closed: bool, width: f32, join: Join, cap: Cap) {
+        let h = width * 0.5;
+
+        // repeated points have no direction, so would break the normals
+        let mut pts: Vec<Vector2<f32>> = Vec::with_capacity(points.len());
+        for p in points {
+            if pts.last().is_none_or(|l| (p - l).magnitude2() > 1e-12) {
+                pts.push(*p);
+            }
+        }
+        if closed && pts.len() > 2 && (pts[0] - pts[pts.len() - 1]).magnitude2() <= 1e-12 {
+            pts.pop();
+        }
+        let closed = closed && pts.len() > 2;
+        let n = pts.len();
+        if n < 2 {
+            return
+        }
+
+        let seg_count = if closed {n} else {n - 1};
+        let dirs: Vec<Vector2<f32>> = (0..seg_count)
+            .map(|i| (pts[(i + 1) % n] - pts[i]).normalize())
+            .collect();
+
+        // the (left, right) vertices each segment starts and ends on, per point
+        let mut starts = vec![(0, 0); n];
+        let mut ends = vec![(0, 0); n];
+
+        for i in 0..n {
+            let p = pts[i];
+            let incoming = if closed || i > 0 {Some(dirs[(i + seg_count - 1) % seg_count])} else {None};
+            let outgoing = if closed || i < n - 1 {Some(dirs[i % seg_count])} else {None};
+
+            match (incoming, outgoing) {
+                (None, Some(d)) => {
+                    starts[i] = self.cap(p, d, h, cap, true);
+                }
+                (Some(d), None) => {
+                    ends[i] = self.cap(p, d, h, cap, false);
+                }
+                (Some(d0), Some(d1)) => {
+                    (ends[i], starts[i]) = self.join(p, d0, d1, h, join);
+                }
+                (None, None) => unreachable!()
+            }
+        }
+
+        for s in 0..seg_count {
+            let (l0, r0) = starts[s];
+            let (l1, r1) = ends[(s + 1) % n];
+            self.tri(l0, r0, l1);
+            self.tri(r0, r1, l1);
+        }
+    }
+
+    // the end of an open stroke, returns its (left, right) vertices
+    fn cap(&mut self, p: Vector2<f32>, d: Vector2<f32>, h: f32, cap: Cap, start: bool) -> (u32, u32) {
+        let nrm = normal(d);
+        // square caps push the end out by half the width
+        let p = match (cap, start) {
+            (Cap::Square, true) => p - d * h,
+            (Cap::Square, false) => p + d * h,
+            _ => p
+        };
+        let l = self.push(p + nrm * h);
+        let r = self.push(p - nrm * h);
+
+        if cap == Cap::Round {
+            // a half circle round the back of the end, from one side to the other
+            let mut rim = vec![];
+            let from = if start {nrm} else {-nrm};
+            arc_points(p, h, angle(from), PI, &mut rim);
+            let centre = self.push(p);
+            self.fan(centre, &rim);
+        }
+
+        (l, r)
+    }
+
+    // the corner where one segment meets the next, returns the (left, right) vertices
+    // the incoming segment ends on and the ones the outgoing segment starts on
+    fn join(&mut self, p: Vector2<f32>, d0: Vector2<f32>, d1: Vector2<f32>, h: f32, join: Join) -> ((u32, u32), (u32, u32)) {
+        let (n0, n1) = (normal(d0), normal(d1));
+        let turn = d0.x * d1.y - d0.y * d1.x;
+
+        // carrying straight on
+        if turn.abs() < 1e-6 && d0.dot(d1) > 0.0 {
+            let pair = (self.push(p + n0 * h), self.push(p - n0 * h));
+            return (pair, pair)
+        }
+
+        // the inside of the corner is on the left for a left turn
+        let side = if turn > 0.0 {1.0} else {-1.0};
+        let m = n0 + n1;
+        let (m, miter_len) = if m.magnitude2() < 1e-12 {
+            // doubling straight back, there's no sensible miter
+            (d0, f32::INFINITY)
+        } else {
+            let m = m.normalize();
+            (m, h / m.dot(n0))
+        };
+
+        let inner_pt = if miter_len.is_finite() {
+            p + m * side * miter_len.min(h * SHAPE_MITER_LIMIT)
+        } else {
+            p
+        };
+        let inner = self.push(inner_pt);
+        let order = |inner: u32, outer: u32| if side > 0.0 {(inner, outer)} else {(outer, inner)};
+
+        if join == Join::Miter && miter_len <= h * SHAPE_MITER_LIMIT {
+            let outer = self.push(p - m * side * miter_len);
+            let pair = order(inner, outer);
+            return (pair, pair)
+        }
+
+        let a_pt = p - n0 * side * h;
+        let b_pt = p - n1 * side * h;
+        let a = self.push(a_pt);
+        let b = self.push(b_pt);
+        match join {
+            Join::Round => {
+                let from = angle(a_pt - p);
+                let mut sweep = angle(b_pt - p) - from;
+                // the short way round is always the outside of the corner
+                if sweep > PI {sweep -= 2.0 * PI}
+                if sweep < -PI {sweep += 2.0 * PI}
+                let mut rim = vec![];
+                arc_points(p, h, from, sweep, &mut rim);
+                self.fan(inner, &rim);
+            }
+            _ => self.tri(inner, a, b)
+        }
+
+        (order(inner, a), order(inner, b))
+    }
+
+    /// Places the mesh at `pos`, rotated about it, and appends it to the draw buffers
+    #[allow(clippy::too_many_arguments)]
+    pub fn append_to(
+        &self,
+        verts: &mut Vec<Vertex2D>,
+        inds: &mut Vec<u32>,
+        pos: Vector2<f32>,
+        rot: f32,
+        layer: u8,
+        colour: [f32;3],
+        opacity: f32
+    ) {
+        let depth = 0.5 - (layer as f32 / 32.0);
+        let (sin, cos) = rot.sin_cos();
+        let base = verts.len() as u32;
+
+        verts.extend(self.points.iter().map(|p| Vertex2D {
+            pos: [p.x * cos - p.y * sin + pos.x, p.x * sin + p.y * cos + pos.y],
+            depth,
+            tex_coords: [0.0, 0.0],
+            tex_index: SOLID_TEX_INDEX,
+            alpha: opacity,
+            colour
+        }));
+        inds.extend(self.inds.iter().map(|i| i + base));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: f32 = 0.2;
+    const H: f32 = W * 0.5;
+    const EPS: f32 = 1e-4;
+
+    fn stroke(points: &[[f32;2]], closed: bool, join: Join, cap: Cap) -> Mesh {
+        let points: Vec<Vector2<f32>> = points.iter().map(|p| (*p).into()).collect();
+        let mut mesh = Mesh::new();
+        mesh.stroke(&points, closed, W, join, cap);
+        mesh
+    }
+
+    fn distance_to_segment(p: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+        let ab = b - a;
+        let t = ((p - a).dot(ab) / ab.magnitude2()).clamp(0.0, 1.0);
+        (p - (a + ab * t)).magnitude()
+    }
+
+    // furthest any point goes in the direction `d`
+    fn furthest(mesh: &Mesh, d: [f32;2]) -> f32 {
+        let d: Vector2<f32> = d.into();
+        mesh.points.iter().map(|p| p.dot(d)).fold(f32::MIN, f32::max)
+    }
+
+    #[test]
+    fn caps_end_where_they_should() {
+        let line = [[0.0, 0.0], [1.0, 0.0]];
+
+        let butt = stroke(&line, false, Join::Miter, Cap::Butt);
+        assert!(furthest(&butt, [-1.0, 0.0]).abs() < EPS);
+        assert!((furthest(&butt, [1.0, 0.0]) - 1.0).abs() < EPS);
+        assert!((furthest(&butt, [0.0, 1.0]) - H).abs() < EPS);
+
+        let square = stroke(&line, false, Join::Miter, Cap::Square);
+        assert!((furthest(&square, [-1.0, 0.0]) - H).abs() < EPS);
+        assert!((furthest(&square, [1.0, 0.0]) - (1.0 + H)).abs() < EPS);
+
+        // as far out as a square cap straight ahead, but never past half the width from the end
+        let round = stroke(&line, false, Join::Miter, Cap::Round);
+        assert!((furthest(&round, [-1.0, 0.0]) - H).abs() < EPS);
+        assert!((furthest(&round, [1.0, 0.0]) - (1.0 + H)).abs() < EPS);
+        let (a, b) = (line[0].into(), line[1].into());
+        assert!(round.points.iter().all(|p| distance_to_segment(*p, a, b) <= H + EPS));
+        assert!(furthest(&round, [-1.0, -1.0]) < furthest(&square, [-1.0, -1.0]) - EPS);
+    }
+
+    #[test]
+    fn joins_round_off_the_outside_of_a_corner() {
+        // a left turn, so the outside of the corner points down and right
+        let corner = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+        let out = [1.0, -1.0];
+        let tip = 1.0 + H * 2.0; // where the edges meet, x - y
+        let (p, a, b) = (corner[1].into(), corner[0].into(), corner[2].into());
+
+        let miter = stroke(&corner, false, Join::Miter, Cap::Butt);
+        assert!(miter.points.iter().any(|q| (q - Vector2::new(1.0 + H, -H)).magnitude() < EPS));
+        assert!((furthest(&miter, out) - tip).abs() < EPS);
+
+        // cut straight across from one edge to the other
+        let bevel = stroke(&corner, false, Join::Bevel, Cap::Butt);
+        assert!((furthest(&bevel, out) - (1.0 + H)).abs() < EPS);
+
+        // half the width from the corner all the way round
+        let round = stroke(&corner, false, Join::Round, Cap::Butt);
+        assert!((furthest(&round, out) - (1.0 + H * 2.0_f32.sqrt())).abs() < EPS);
+        assert!(round.points.iter().all(|q| distance_to_segment(*q, a, p).min(distance_to_segment(*q, p, b)) <= H + EPS));
+    }
+
+    #[test]
+    fn sharp_miters_are_bevelled() {
+        // nearly doubling back, where a miter would reach out about 40 half widths
+        let corner = [[0.0, 0.0], [1.0, 0.0], [0.0, 0.05]];
+        let miter = stroke(&corner, false, Join::Miter, Cap::Butt);
+        assert_eq!(miter.points, stroke(&corner, false, Join::Bevel, Cap::Butt).points);
+        assert!(furthest(&miter, [1.0, 0.0]) <= 1.0 + H);
+    }
+
+    #[test]
+    fn degenerate_strokes_stay_finite() {
+        let cases: [(&[[f32;2]], bool); 8] = [
+            (&[], false),
+            (&[[0.5, 0.5]], false),
+            (&[[0.5, 0.5], [0.5, 0.5]], false),
+            (&[[0.0, 0.0], [0.0, 0.0], [1.0, 0.0], [1.0, 0.0]], false),
+            (&[[0.0, 0.0], [1.0, 0.0], [0.0, 0.0]], false),
+            (&[[0.0, 0.0], [1.0, 0.0], [0.0, 0.0]], true),
+            (&[[0.0, 0.0], [1.0, 0.0], [1.0, 0.0], [0.0, 0.0]], true),
+            (&[[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [0.0, 0.0]], true)
+        ];
+        for (points, closed) in cases {
+            for join in [Join::Miter, Join::Bevel, Join::Round] {
+                for cap in [Cap::Butt, Cap::Square, Cap::Round] {
+                    let mesh = stroke(points, closed, join, cap);
+                    let what = format!("{points:?} closed {closed}, {join:?} join, {cap:?} cap");
+                    assert!(mesh.points.iter().all(|p| p.x.is_finite() && p.y.is_finite()), "{what} has a point that isn't finite");
+                    assert!(mesh.inds.len().is_multiple_of(3), "{what} has a partial triangle");
+                    assert!(mesh.inds.iter().all(|i| (*i as usize) < mesh.points.len()), "{what} indexes past its points");
+                }
+            }
+        }
+    }
+}
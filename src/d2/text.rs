@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use cgmath::Vector2;
+
+use crate::{config::FONT_TEX_INDEX, tween::{Property, Tweenable, Value}};
+
+use super::{font::{FontAtlas, GlyphInfo}, hittest::{to_local, HitTest}, shape::Shape, tovertind2d::ToVertInd2D, vertex::Vertex2D};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Centre,
+    Right
+}
+
+/// A block of text drawn from a font atlas.
+/// `pos` is the vertical centre of the block, and its left edge, centre or right edge
+/// depending on the alignment. Lines longer than `wrap_width` are broken between words.
+#[derive(Clone)]
+pub struct Text {
+    text: String,
+    font: Arc<FontAtlas>,
+    pos: Vector2<f32>,
+    rot: f32, // radians
+    size: f32, // height of an em, in GUI units
+    layer: u8,
+    colour: [f32;3],
+    opacity: f32,
+    align: Align,
+    wrap_width: Option<f32>
+}
+
+impl Text {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        text: &str,
+        font: Arc<FontAtlas>,
+        pos: Vector2<f32>,
+        rot: f32,
+        size: f32,
+        layer: u8,
+        colour: [f32;3],
+        opacity: f32,
+        align: Align,
+        wrap_width: Option<f32>
+    ) -> Self {
+        Self { text: text.into(), font, pos, rot, size, layer, colour, opacity, align, wrap_width }
+    }
+    pub fn set_text(&mut self, text: &str) {
+        if self.text != text {
+            self.text.clear();
+            self.text.push_str(text);
+        }
+    }
+    pub fn set_opacity(&mut self, o: f32) {
+        self.opacity = o;
+    }
+    pub fn set_wrap_width(&mut self, w: Option<f32>) {
+        self.wrap_width = w;
+    }
+
+    // width of a run of characters in ems, including kerning between them
+    fn measure(&self, s: &str) -> f32 {
+        let mut w = 0.0;
+        let mut prev: Option<&GlyphInfo> = None;
+        for c in s.chars() {
+            let Some(g) = self.font.glyph(c) else {
+                continue
+            };
+            if let Some(p) = prev {
+                w += self.font.kern(p, g);
+            }
+            w += g.advance;
+            prev = Some(g);
+        }
+        w
+    }
+
+    // splits the text into lines, wrapping at spaces where a line would be too wide
+    fn lines(&self) -> Vec<String> {
+        let max = self.wrap_width.map(|w| w / self.size);
+        let mut out = vec![];
+
+        for para in self.text.split('\n') {
+            let Some(max) = max else {
+                out.push(para.to_string());
+                continue
+            };
+
+            let mut line = String::new();
+            for word in para.split(' ') {
+                if line.is_empty() {
+                    line.push_str(word);
+                    continue
+                }
+                let candidate = format!("{line} {word}");
+                if self.measure(&candidate) > max {
+                    // a single word wider than the limit gets a line to itself rather than being split
+                    out.push(std::mem::take(&mut line));
+                    line.push_str(word);
+                } else {
+                    line = candidate;
+                }
+            }
+            out.push(line);
+        }
+
+        out
+    }
+}
+
+impl ToVertInd2D for Text {
+    fn layer(&self) -> u8 {
+        self.layer
+    }
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
+        let mut verts = vec![];
+        let mut inds = vec![];
+        self.append_vert_ind(&mut verts, &mut inds);
+        (verts, inds)
+    }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        let font = &self.font;
+        let depth = 0.5 - (self.layer as f32 / 32.0);
+        let lines = self.lines();
+
+        // centre the block vertically on pos, ignoring the gap after the last line
+        let line_h = font.line_height();
+        let block_h = line_h * lines.len() as f32 - font.line_gap;
+        let mut baseline = block_h * 0.5 - font.ascent;
+
+        let (sin, cos) = self.rot.sin_cos();
+        let place = |x: f32, y: f32| -> [f32;2] {
+            let (x, y) = (x * self.size, y * self.size);
+            [
+                x * cos - y * sin + self.pos.x,
+                x * sin + y * cos + self.pos.y
+            ]
+        };
+
+        for line in lines.iter() {
+            let width = self.measure(line);
+            let mut pen = match self.align {
+                Align::Left => 0.0,
+                Align::Centre => -width * 0.5,
+                Align::Right => -width
+            };
+
+            let mut prev: Option<&GlyphInfo> = None;
+            for c in line.chars() {
+                let Some(g) = font.glyph(c) else {
+                    continue
+                };
+                if let Some(p) = prev {
+                    pen += font.kern(p, g);
+                }
+                prev = Some(g);
+
+                if g.max[0] > g.min[0] {
+                    let base = verts.len() as u32;
+                    let corners = [
+                        (g.min[0], g.min[1], g.uv_min[0], g.uv_max[1]),
+                        (g.max[0], g.min[1], g.uv_max[0], g.uv_max[1]),
+                        (g.min[0], g.max[1], g.uv_min[0], g.uv_min[1]),
+                        (g.max[0], g.max[1], g.uv_max[0], g.uv_min[1])
+                    ];
+                    for (x, y, u, v) in corners {
+                        verts.push(Vertex2D {
+                            pos: place(pen + x, baseline + y),
+                            depth,
+                            tex_coords: [u, v],
+                            tex_index: FONT_TEX_INDEX,
+                            alpha: self.opacity,
+                            colour: self.colour
+                        });
+                    }
+                    inds.extend_from_slice(&[base, base+1, base+2, base+1, base+3, base+2]);
+                }
+
+                pen += g.advance;
+            }
+
+            baseline -= line_h;
+        }
+    }
+}
+
+// anywhere in the box round the lines, relative to where each line starts
+impl HitTest for Text {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        let lines = self.lines();
+        let line_h = self.font.line_height();
+        let block_h = (line_h * lines.len() as f32 - self.font.line_gap) * self.size;
+        let p = to_local(p, self.pos, self.rot);
+        if p.y.abs() > block_h * 0.5 {
+            return false
+        }
+
+        // which line the point is level with, counting down from the top
+        let i = (((block_h * 0.5 - p.y) / (line_h * self.size)) as usize).min(lines.len() - 1);
+        let width = self.measure(&lines[i]) * self.size;
+        let left = match self.align {
+            Align::Left => 0.0,
+            Align::Centre => -width * 0.5,
+            Align::Right => -width
+        };
+        left <= p.x && p.x <= left + width
+    }
+}
+
+impl Shape for Text {
+    fn get_pos(&self) -> Vector2<f32> {
+        self.pos
+    }
+    fn set_pos(&mut self, p: Vector2<f32>) {
+        self.pos = p
+    }
+    fn get_rot(&self) -> f32 {
+        self.rot
+    }
+    fn set_rot(&mut self, r: f32) {
+        self.rot = r
+    }
+}
+
+impl Tweenable for Text {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Position => Some(Value::Vec2(self.pos)),
+            Property::Rotation => Some(Value::Float(self.rot)),
+            Property::Opacity => Some(Value::Float(self.opacity)),
+            Property::Colour => {
+                let [r, g, b] = self.colour;
+                Some(Value::Colour([r, g, b, self.opacity]))
+            }
+            Property::Scale => Some(Value::Float(self.size))
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        match (p, v) {
+            (Property::Position, Value::Vec2(pos)) => self.pos = pos,
+            (Property::Rotation, Value::Float(r)) => self.rot = r,
+            (Property::Opacity, Value::Float(o)) => self.opacity = o,
+            (Property::Colour, Value::Colour([r, g, b, a])) => {
+                self.colour = [r, g, b];
+                self.opacity = a;
+            }
+            (Property::Scale, Value::Float(s)) => self.size = s,
+            _ => {}
+        }
+    }
+}
@@ -1,14 +1,46 @@
-use image::{GenericImageView, ImageError};
+use image::{ImageError, RgbaImage};
+
+/// A texture either still encoded (e.g. an embedded PNG) or already decoded/generated
+pub enum TextureSource<'b> {
+    Encoded(&'b [u8]),
+    Rgba(RgbaImage)
+}
+
+impl TextureSource<'_> {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            TextureSource::Encoded(b) => b.is_empty(),
+            TextureSource::Rgba(i) => i.width() == 0 || i.height() == 0
+        }
+    }
+}
+
+pub fn tex_from_source(
+    source: &TextureSource,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue
+) -> Result<(wgpu::Texture, wgpu::TextureView), ImageError> {
+    match source {
+        TextureSource::Encoded(bytes) => tex_from_bytes(bytes, device, queue),
+        TextureSource::Rgba(image) => Ok(tex_from_rgba(image, device, queue))
+    }
+}
 
 pub fn tex_from_bytes(
     bytes: &[u8],
     device: &wgpu::Device,
     queue: &wgpu::Queue
 ) -> Result<(wgpu::Texture, wgpu::TextureView), ImageError> {
-
     let image = image::load_from_memory(bytes)?;
-    let dim = image.dimensions();
-    let image_rgba = image.to_rgba8();
+    Ok(tex_from_rgba(&image.to_rgba8(), device, queue))
+}
+
+pub fn tex_from_rgba(
+    image_rgba: &RgbaImage,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let dim = image_rgba.dimensions();
 
     let size = wgpu::Extent3d {
         width: dim.0,
@@ -36,7 +68,7 @@ pub fn tex_from_bytes(
             origin: wgpu::Origin3d::ZERO,
             aspect: wgpu::TextureAspect::All
         },
-        &image_rgba,
+        image_rgba,
         wgpu::ImageDataLayout {
             offset: 0,
             bytes_per_row: Some(4 * dim.0),
@@ -51,5 +83,5 @@ pub fn tex_from_bytes(
 
     let view = tex.create_view(&wgpu::TextureViewDescriptor::default());
 
-    Ok((tex, view))
+    (tex, view)
 }
@@ -1,58 +1,74 @@
 use super::vertex::Vertex2D;
 
 pub trait ToVertInd2D {
-    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u16>);
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>);
     fn layer(&self) -> u8;
+    /// Appends this shape's geometry to existing buffers, offsetting the indices to match.
+    /// Shapes that can tessellate without allocating should override this.
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        let (v, i) = self.to_vert_ind();
+
+        debug_assert!(
+            verts.len() + v.len() <= u32::MAX as usize,
+            "2D geometry has too many vertices to be indexed with u32"
+        );
+
+        let base = verts.len() as u32;
+        verts.extend_from_slice(&v);
+        inds.extend(i.iter().map(|ind| ind + base));
+    }
 }
 
 impl<T> ToVertInd2D for Vec<T>
 where
     T: ToVertInd2D,
 {
-    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
         let mut verts: Vec<Vertex2D> = vec![];
-        let mut inds: Vec<u16> = vec![];
+        let mut inds: Vec<u32> = vec![];
 
-        for tvi in self.iter() {
-            let (mut v, mut i) = tvi.to_vert_ind();
-
-            for ind in i.iter_mut() {
-                *ind += verts.len() as u16;
-            }
-
-            verts.extend_from_slice(&mut v);
-            inds.extend_from_slice(&mut i);
-        }
+        self.append_vert_ind(&mut verts, &mut inds);
 
         (verts, inds)
     }
     fn layer(&self) -> u8 {
         unimplemented!()
     }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        for tvi in self.iter() {
+            tvi.append_vert_ind(verts, inds);
+        }
+    }
 }
 
 impl<T> ToVertInd2D for Box<T>
 where
     T: ToVertInd2D + ?Sized
 {
-    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
         (**self).to_vert_ind()
     }
     fn layer(&self) -> u8 {
         (**self).layer()
     }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        (**self).append_vert_ind(verts, inds)
+    }
 }
 
 impl<T> ToVertInd2D for &Box<T>
 where
     T: ToVertInd2D + ?Sized
 {
-    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
         (**self).to_vert_ind()
     }
     fn layer(&self) -> u8 {
         (**self).layer()
     }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        (**self).append_vert_ind(verts, inds)
+    }
 }
 
 impl<T> ToVertInd2D for &Vec<T>
@@ -62,7 +78,10 @@ where
     fn layer(&self) -> u8 {
         (*self).layer()
     }
-    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex2D>, Vec<u32>) {
         (*self).to_vert_ind()
     }
+    fn append_vert_ind(&self, verts: &mut Vec<Vertex2D>, inds: &mut Vec<u32>) {
+        (*self).append_vert_ind(verts, inds)
+    }
 }
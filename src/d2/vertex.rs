@@ -9,17 +9,19 @@ pub struct Vertex2D {
     pub depth: f32,
     pub tex_coords: [f32;2],
     pub tex_index: u32,
-    pub alpha: f32
+    pub alpha: f32,
+    pub colour: [f32;3] // multiplies the texture's colour
 }
 
 impl Vertex2D {
-    const ATTRIBS: [wgpu::VertexAttribute; 5] =
+    const ATTRIBS: [wgpu::VertexAttribute; 6] =
         wgpu::vertex_attr_array![
             0 => Float32x2,
             1 => Float32,
             2 => Float32x2,
             3 => Uint32,
-            4 => Float32
+            4 => Float32,
+            5 => Float32x3
         ];
 
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
@@ -1,6 +1,6 @@
 use cgmath::{Deg, Quaternion, Rotation3, Vector3};
 
-use super::{tovertind3d::ToVertInd3D, vertex::Vertex3D};
+use super::{instance::{ColumnInstance, ToInstances3D}, tovertind3d::ToVertInd3D, vertex::Vertex3D};
 
 pub struct Column {
     pos: Vector3<f32>,
@@ -12,9 +12,9 @@ pub struct Column {
 
 impl Column {
     pub fn new(pos: Vector3<f32>, rotopt: Option<Quaternion<f32>>, width: f32, height: f32, initcol: [f32;4]) -> Self {
-        let rot = rotopt.or(
-            Some(Quaternion::from_angle_y(Deg(0.0)))
-        ).unwrap();
+        let rot = rotopt.unwrap_or(
+            Quaternion::from_angle_y(Deg(0.0))
+        );
         Self {
             pos, rot, width, height, colour: [initcol;6]
         }
@@ -24,10 +24,27 @@ impl Column {
 
         self.colour[ind as usize] = col;
     }
+    pub fn to_instance(&self) -> ColumnInstance {
+        ColumnInstance {
+            pos: self.pos.into(),
+            height: self.height,
+            width: self.width,
+            rot: [self.rot.v.x, self.rot.v.y, self.rot.v.z, self.rot.s],
+            colours: self.colour
+        }
+    }
+}
+
+/// A column of width and height 1, to be scaled per instance
+pub fn unit_column_mesh() -> (Vec<Vertex3D>, Vec<u32>) {
+    (
+        get_column_vertices(1.0, 1.0, [1.0;4]).to_vec(),
+        COLUMN_INDICES.to_vec()
+    )
 }
 
 impl ToVertInd3D for Column {
-    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u32>) {
         let mut verts = get_column_vertices(self.height, self.width, [0.0;4]).to_vec();
 
         verts.iter_mut().for_each(|v| {
@@ -50,8 +67,14 @@ impl ToVertInd3D for Column {
     }
 }
 
+impl ToInstances3D for Column {
+    fn to_instances(&self) -> Vec<ColumnInstance> {
+        vec![self.to_instance()]
+    }
+}
+
 impl ToVertInd3D for &Column {
-    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u32>) {
         (*self).to_vert_ind()
     }
 }
@@ -92,7 +115,7 @@ fn get_column_vertices(height: f32, width: f32, initcol: [f32;4]) -> [Vertex3D;2
     ]
 }
 
-const COLUMN_INDICES: [u16;36] = [
+const COLUMN_INDICES: [u32;36] = [
     // bottom
     3, 0, 2,
     2, 0, 1,
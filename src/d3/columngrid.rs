@@ -1,9 +1,11 @@
 use cgmath::{Deg, Quaternion, Rotation, Rotation3, Vector3};
-use rand::{rngs::ThreadRng, Rng};
+use rand::Rng;
 
-use super::{column::Column, tovertind3d::ToVertInd3D, vertex::Vertex3D};
+use crate::tween::{Property, Tweenable, Value};
 
-#[derive(Clone, Debug)]
+use super::{column::Column, instance::{ColumnInstance, ToInstances3D}, tovertind3d::ToVertInd3D, vertex::Vertex3D};
+
+#[derive(Debug)]
 pub struct ColumnGrid {
     pub pos: Vector3<f32>,
     rot: Quaternion<f32>,
@@ -15,25 +17,44 @@ pub struct ColumnGrid {
     >
 }
 
+// clone_from reuses the columns' allocations, for copies made every frame
+impl Clone for ColumnGrid {
+    fn clone(&self) -> Self {
+        Self { pos: self.pos, rot: self.rot, sidelen: self.sidelen, columns: self.columns.clone() }
+    }
+    fn clone_from(&mut self, source: &Self) {
+        self.pos = source.pos;
+        self.rot = source.rot;
+        self.sidelen = source.sidelen;
+        self.columns.clone_from(&source.columns);
+    }
+}
+
 impl ColumnGrid {
     pub fn new(pos: Vector3<f32>, rotopt: Option<Quaternion<f32>>, sidelen: u8) -> Self {
-        let rot = rotopt.or(
-            Some(Quaternion::from_angle_y(Deg(0.0)))
-        ).unwrap();
+        let rot = rotopt.unwrap_or(
+            Quaternion::from_angle_y(Deg(0.0))
+        );
 
         Self {
             pos, rot, columns: vec![ vec![0;sidelen as usize];sidelen as usize ], sidelen
         }
     }
+    /// A grid with the given column heights, which need to be square
+    pub fn with_columns(pos: Vector3<f32>, rotopt: Option<Quaternion<f32>>, columns: Vec<Vec<u8>>) -> Self {
+        let mut out = Self::new(pos, rotopt, columns.len() as u8);
+        out.columns = columns;
+        out
+    }
     pub fn set_column(&mut self, x: u8, y: u8, height: u8) {
         self.columns[x as usize][y as usize] = height;
     }
     pub fn translate(&mut self, trn: Vector3<f32>) {
-        self.pos = self.pos + trn;
+        self.pos += trn;
     }
-    pub fn new_random(pos: Vector3<f32>, rotopt: Option<Quaternion<f32>>, sidelength: u8) -> Self {
+    pub fn new_random(pos: Vector3<f32>, rotopt: Option<Quaternion<f32>>, sidelength: u8, rng: &mut impl Rng) -> Self {
 
-        fn gen_random_column(x: u8, y: u8, r: &mut ThreadRng, size: u8) -> u8 {
+        fn gen_random_column(x: u8, y: u8, r: &mut impl Rng, size: u8) -> u8 {
             // carry out the weighted random generation
             // of one column's height
             //
@@ -53,19 +74,15 @@ impl ColumnGrid {
 
         let mut out = Self::new(pos, rotopt, sidelength);
 
-        let mut rng = rand::thread_rng();
-
         for x in 0..sidelength {
             for y in 0..sidelength {
-                out.set_column(x, y, gen_random_column(x, y, &mut rng, sidelength))
+                out.set_column(x, y, gen_random_column(x, y, rng, sidelength))
             }
         }
 
         out
     }
-    pub fn random_change(&mut self) {
-        let mut rng = rand::thread_rng();
-
+    pub fn random_change(&mut self, rng: &mut impl Rng) {
         let x = rng.gen_range(0..self.sidelen) as usize;
         let y = rng.gen_range(0..self.sidelen) as usize;
         let h = rng.gen_range(0..self.sidelen);
@@ -73,9 +90,9 @@ impl ColumnGrid {
 
         self.columns[x][y] = h;
     }
-    pub fn random_changes(&mut self, ch: u32) {
+    pub fn random_changes(&mut self, ch: u32, rng: &mut impl Rng) {
         for _ in 0..ch {
-            self.random_change()
+            self.random_change(rng)
         }
     }
     pub fn count_occupied_columns(&self) -> u32 {
@@ -98,8 +115,8 @@ impl ColumnGrid {
     }
 }
 
-impl ToVertInd3D for ColumnGrid {
-    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u16>) {
+impl ColumnGrid {
+    fn build_columns(&self) -> Vec<Column> {
 
         let mut columns: Vec<Column> = vec![];
 
@@ -167,12 +184,44 @@ impl ToVertInd3D for ColumnGrid {
             }
         }
 
-        columns.to_vert_ind()
+        columns
+    }
+}
+
+impl ToVertInd3D for ColumnGrid {
+    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u32>) {
+        self.build_columns().to_vert_ind()
+    }
+}
+
+impl ToInstances3D for ColumnGrid {
+    fn to_instances(&self) -> Vec<ColumnInstance> {
+        self.build_columns().iter().map(|c| c.to_instance()).collect()
+    }
+}
+
+impl ToInstances3D for &ColumnGrid {
+    fn to_instances(&self) -> Vec<ColumnInstance> {
+        (*self).to_instances()
     }
 }
 
 impl ToVertInd3D for &ColumnGrid {
-    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u32>) {
         (*self).to_vert_ind()
     }
 }
+
+impl Tweenable for ColumnGrid {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Position => Some(Value::Vec3(self.pos)),
+            _ => None
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        if let (Property::Position, Value::Vec3(pos)) = (p, v) {
+            self.pos = pos;
+        }
+    }
+}
@@ -0,0 +1,68 @@
+/// Per-instance data for drawing a column with the shared unit cube mesh
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ColumnInstance {
+    pub pos: [f32;3],
+    pub height: f32,
+    pub width: f32,
+    pub rot: [f32;4], // quaternion, (x, y, z, w)
+    pub colours: [[f32;4];6]
+}
+
+impl ColumnInstance {
+    // locations 0-2 are taken by Vertex3D
+    const ATTRIBS: [wgpu::VertexAttribute; 10] =
+        wgpu::vertex_attr_array![
+            3 => Float32x3,
+            4 => Float32,
+            5 => Float32,
+            6 => Float32x4,
+            7 => Float32x4,
+            8 => Float32x4,
+            9 => Float32x4,
+            10 => Float32x4,
+            11 => Float32x4,
+            12 => Float32x4
+        ];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS
+        }
+    }
+}
+
+pub trait ToInstances3D {
+    fn to_instances(&self) -> Vec<ColumnInstance>;
+}
+
+impl<T> ToInstances3D for Vec<T>
+where
+    T: ToInstances3D
+{
+    fn to_instances(&self) -> Vec<ColumnInstance> {
+        self.iter().flat_map(|t| t.to_instances()).collect()
+    }
+}
+
+impl ToInstances3D for () {
+    fn to_instances(&self) -> Vec<ColumnInstance> {
+        vec![]
+    }
+}
+
+impl<T> ToInstances3D for Option<T>
+where
+    T: ToInstances3D
+{
+    fn to_instances(&self) -> Vec<ColumnInstance> {
+        match self {
+            Some(t) => t.to_instances(),
+            None => vec![]
+        }
+    }
+}
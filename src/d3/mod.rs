@@ -3,3 +3,4 @@ pub mod camera;
 pub mod column;
 pub mod columngrid;
 pub mod vertex;
+pub mod instance;
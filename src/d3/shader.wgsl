@@ -1,36 +0,0 @@
-struct CameraUniform {
-    view_proj: mat4x4<f32>,
-    pos: vec3<f32>
-};
-@group(0) @binding(0)
-var<uniform> camera: CameraUniform;
-
-struct VertexInput {
-    @location(0) position: vec3<f32>,
-    @location(1) normal: vec3<f32>,
-    @location(2) colour: vec4<f32>
-};
-
-struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>,
-    @location(0) colour: vec4<f32>,
-    @location(1) normal: vec3<f32>,
-    @location(2) world_pos: vec3<f32>,
-};
-
-@vertex
-fn vs_main(
-    model: VertexInput
-) -> VertexOutput {
-    var out: VertexOutput;
-    out.colour = model.colour;
-    out.normal = model.normal;
-    out.world_pos = model.position;
-    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
-    return out;
-}
-
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.colour);
-}
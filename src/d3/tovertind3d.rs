@@ -1,7 +1,7 @@
 use super::vertex::Vertex3D;
 
 pub trait ToVertInd3D {
-    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u16>);
+    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u32>);
 }
 
 impl<T, U> ToVertInd3D for (T, U)
@@ -9,38 +9,54 @@ where
     T: ToVertInd3D,
     U: ToVertInd3D
 {
-    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u32>) {
         let (mut verts1, mut inds1) = self.0.to_vert_ind();
-        let (mut verts2, mut inds2) = self.1.to_vert_ind();
+        let (verts2, mut inds2) = self.1.to_vert_ind();
+
+        debug_assert!(
+            verts1.len() + verts2.len() <= u32::MAX as usize,
+            "3D geometry has too many vertices to be indexed with u32"
+        );
 
         for ind in inds2.iter_mut() {
-            *ind += verts1.len() as u16;
+            *ind += verts1.len() as u32;
         }
 
-        verts1.extend_from_slice(&mut verts2);
-        inds1.extend_from_slice(&mut inds2);
+        verts1.extend_from_slice(&verts2);
+        inds1.extend_from_slice(&inds2);
 
         (verts1, inds1)
     }
 }
 
+impl ToVertInd3D for () {
+    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u32>) {
+        (vec![], vec![])
+    }
+}
+
 impl<T> ToVertInd3D for Vec<T>
 where
     T: ToVertInd3D,
 {
-    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u16>) {
+    fn to_vert_ind(&self) -> (Vec<Vertex3D>, Vec<u32>) {
         let mut verts: Vec<Vertex3D> = vec![];
-        let mut inds: Vec<u16> = vec![];
+        let mut inds: Vec<u32> = vec![];
 
         for tvi in self.iter() {
-            let (mut v, mut i) = tvi.to_vert_ind();
+            let (v, mut i) = tvi.to_vert_ind();
+
+            debug_assert!(
+                verts.len() + v.len() <= u32::MAX as usize,
+                "3D geometry has too many vertices to be indexed with u32"
+            );
 
             for ind in i.iter_mut() {
-                *ind += verts.len() as u16;
+                *ind += verts.len() as u32;
             }
 
-            verts.extend_from_slice(&mut v);
-            inds.extend_from_slice(&mut i);
+            verts.extend_from_slice(&v);
+            inds.extend_from_slice(&i);
         }
 
         (verts, inds)
@@ -0,0 +1,209 @@
+use std::{collections::VecDeque, sync::Arc, time::{Duration, Instant}};
+
+use cgmath::Vector2;
+
+use crate::{config::*, console::{CommandHandler, CommandResult, CommandSpec}, d2::{font::FontAtlas, layout::Layout, polyline::Polyline, retained::Retained, roundedrect::RoundedRect, shape::Shape, tessellate::{Cap, Join, Style}, text::{Align, Text}}, gpubuffer::BufferUsage, gpuprofiler::TimingSource, gpustate::FrameStats, scenes::{Frame, Shared}};
+
+/// Frame timings, what the GPU is drawing and what the scenes are up to, drawn over everything.
+/// Timings are recorded all the time, so the graph is already full when it's shown.
+pub struct DebugOverlay {
+    visible: bool,
+    frame_times: VecDeque<f32>, // ms between frames, oldest first
+    last_frame: Option<Instant>,
+    // totals since the text was last updated, which it shows the averages of
+    since_refresh: f32, // seconds
+    frames: u32,
+    cpu: Duration,
+    wait: Duration,
+    last_stats: FrameStats,
+    origin: Vector2<f32>, // top left of the panel
+    font: Arc<FontAtlas>,
+    bg: Retained<RoundedRect>,
+    graph: Retained<Polyline>,
+    target: Retained<Polyline>,
+    lines: Vec<Retained<Text>>
+}
+
+impl DebugOverlay {
+    pub fn new(font: Arc<FontAtlas>, layout: &Layout) -> Self {
+        let graph_width = DEBUG_OVERLAY_WIDTH - DEBUG_OVERLAY_PADDING * 2.0;
+        let target_y = DEBUG_GRAPH_TARGET_MS / DEBUG_GRAPH_MAX_MS * DEBUG_GRAPH_HEIGHT;
+        let line = |points, colour, opacity| Retained::new(Polyline::new(
+            points,
+            [0.0, 0.0].into(),
+            DEBUG_GRAPH_LINE_WIDTH,
+            Join::Bevel,
+            Cap::Butt,
+            false,
+            DEBUG_OVERLAY_LAYER + 1,
+            colour,
+            opacity
+        ));
+
+        let mut overlay = Self {
+            visible: false,
+            frame_times: VecDeque::with_capacity(DEBUG_GRAPH_FRAMES),
+            last_frame: None,
+            since_refresh: 0.0,
+            frames: 0,
+            cpu: Duration::ZERO,
+            wait: Duration::ZERO,
+            last_stats: FrameStats::default(),
+            origin: layout.resolve(DEBUG_OVERLAY_ANCHOR),
+            font,
+            bg: Retained::new(RoundedRect::new(
+                DEBUG_OVERLAY_WIDTH,
+                0.0,
+                DEBUG_OVERLAY_PADDING,
+                [0.0, 0.0].into(),
+                Style::Fill,
+                DEBUG_OVERLAY_LAYER,
+                DEBUG_OVERLAY_COLOUR,
+                DEBUG_OVERLAY_OPACITY
+            )),
+            graph: line(vec![], DEBUG_GRAPH_COLOUR, 1.0),
+            target: line(vec![[0.0, target_y].into(), [graph_width, target_y].into()], DEBUG_GRAPH_TARGET_COLOUR, 0.6),
+            lines: vec![]
+        };
+        overlay.place();
+        overlay
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Whether the text is due an update, which needs the caller to gather what goes in it
+    pub fn wants_refresh(&self) -> bool {
+        self.visible && (self.lines.is_empty() || self.since_refresh >= DEBUG_OVERLAY_REFRESH)
+    }
+
+    /// Records a frame that has just been drawn
+    pub fn record(&mut self, stats: FrameStats) {
+        let now = Instant::now();
+        if let Some(last) = self.last_frame {
+            let dt = now - last;
+            if self.frame_times.len() == DEBUG_GRAPH_FRAMES {
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(dt.as_secs_f32() * 1000.0);
+            self.since_refresh += dt.as_secs_f32();
+        }
+        self.last_frame = Some(now);
+
+        self.frames += 1;
+        self.cpu += stats.cpu;
+        self.wait += stats.wait;
+        self.last_stats = stats;
+    }
+
+    /// Rewrites the text, with `scene_info` from the scenes and the GPU's `buffers`
+    pub fn refresh(&mut self, scene_info: &[String], buffers: &[BufferUsage]) {
+        let count = self.frame_times.len().max(1) as f32;
+        let avg = self.frame_times.iter().sum::<f32>() / count;
+        let peak = self.frame_times.iter().copied().fold(0.0, f32::max);
+        let frames = self.frames.max(1);
+        let ms = |d: Duration| d.as_secs_f32() * 1000.0 / frames as f32;
+        let (d3, d2) = (self.last_stats.pass_3d, self.last_stats.pass_2d);
+
+        let mut text = vec![
+            format!("Frame: {avg:.2}ms average, {peak:.2}ms peak ({:.0} fps)", if avg > 0.0 {1000.0 / avg} else {0.0}),
+            format!("CPU: {:.2}ms, waiting: {:.2}ms", ms(self.cpu), ms(self.wait)),
+            match (self.last_stats.timings, self.last_stats.timing_source) {
+                (Some(t), TimingSource::Gpu) => format!("GPU: {t}"),
+                // only what it took to record each pass, which is better than nothing
+                (Some(t), TimingSource::Cpu) => format!("Recording (no GPU timestamps): {t}"),
+                (None, _) => "GPU: waiting for timings".into()
+            },
+            format!("3D pass: {} vertices, {} indices", d3.vertices, d3.indices),
+            format!("2D pass: {} vertices, {} indices", d2.vertices, d2.indices)
+        ];
+        text.extend(buffers.iter().map(|b| b.to_string()));
+        text.extend(scene_info.iter().cloned());
+
+        self.lines.truncate(text.len());
+        for (i, t) in text.iter().enumerate() {
+            match self.lines.get_mut(i) {
+                Some(l) => l.set_text(t),
+                None => self.lines.push(Retained::new(Text::new(
+                    t,
+                    self.font.clone(),
+                    [0.0, 0.0].into(),
+                    0.0,
+                    DEBUG_OVERLAY_TEXT_SIZE,
+                    DEBUG_OVERLAY_LAYER + 1,
+                    DEBUG_OVERLAY_TEXT_COLOUR,
+                    1.0,
+                    Align::Left,
+                    None
+                )))
+            }
+        }
+
+        self.since_refresh = 0.0;
+        self.frames = 0;
+        self.cpu = Duration::ZERO;
+        self.wait = Duration::ZERO;
+        self.place();
+    }
+
+    pub fn relayout(&mut self, layout: &Layout) {
+        self.origin = layout.resolve(DEBUG_OVERLAY_ANCHOR);
+        self.place();
+    }
+
+    pub fn render<'f>(&'f mut self, frame: &mut Frame<'f>) {
+        if !self.visible {
+            return
+        }
+
+        // newest on the right, so the graph scrolls left as frames come in
+        let step = (DEBUG_OVERLAY_WIDTH - DEBUG_OVERLAY_PADDING * 2.0) / (DEBUG_GRAPH_FRAMES - 1) as f32;
+        let start = DEBUG_GRAPH_FRAMES - self.frame_times.len();
+        self.graph.points = self.frame_times.iter().enumerate().map(|(i, ms)| Vector2::new(
+            (start + i) as f32 * step,
+            ms.min(DEBUG_GRAPH_MAX_MS) / DEBUG_GRAPH_MAX_MS * DEBUG_GRAPH_HEIGHT
+        )).collect();
+
+        frame.shapes.push(&mut self.bg);
+        frame.shapes.push(&mut self.target);
+        if self.graph.points.len() > 1 {
+            frame.shapes.push(&mut self.graph);
+        }
+        for l in self.lines.iter_mut() {
+            frame.shapes.push(l);
+        }
+    }
+
+    // the graph along the top of the panel and the text below it, the panel fitting around them
+    fn place(&mut self) {
+        let left = self.origin.x + DEBUG_OVERLAY_PADDING;
+        let graph_bottom = self.origin.y - DEBUG_OVERLAY_PADDING - DEBUG_GRAPH_HEIGHT;
+        self.graph.set_pos([left, graph_bottom].into());
+        self.target.set_pos([left, graph_bottom].into());
+
+        let text_top = graph_bottom - DEBUG_OVERLAY_PADDING;
+        for (i, l) in self.lines.iter_mut().enumerate() {
+            l.set_pos([left, text_top - DEBUG_OVERLAY_LINE_SPACING * (i as f32 + 0.5)].into());
+        }
+
+        let height = self.origin.y - text_top + DEBUG_OVERLAY_LINE_SPACING * self.lines.len() as f32 + DEBUG_OVERLAY_PADDING;
+        if self.bg.height != height {
+            self.bg.height = height;
+        }
+        self.bg.set_pos([self.origin.x + DEBUG_OVERLAY_WIDTH * 0.5, self.origin.y - height * 0.5].into());
+    }
+}
+
+impl CommandHandler for DebugOverlay {
+    fn commands(&self, out: &mut Vec<CommandSpec>) {
+        out.push(CommandSpec { name: "overlay", usage: "", help: "Shows or hides the debug overlay, as F3 does" });
+    }
+    fn run_command(&mut self, _shared: &mut Shared, name: &str, _args: &[&str]) -> Option<CommandResult> {
+        if name != "overlay" {
+            return None
+        }
+        self.toggle();
+        Some(Ok(String::new()))
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{game::GameMode, scenes::UserSettings};
+
+/// Something that happened which other parts of the game might want to react to,
+/// so whatever causes it doesn't need to know who's listening
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    /// The mouse moved onto a widget
+    HoverChanged,
+    AnswerCorrect { streak: u32 },
+    AnswerIncorrect { timed_out: bool },
+    RunEnded { mode: GameMode, score: u32 },
+    SettingsChanged(UserSettings),
+    /// A volume was changed, and the player wants to hear what it sounds like now
+    VolumeTested
+}
+
+/// Reacts to game events, given to `EventBus::dispatch` to hear about them
+pub trait Listener {
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+/// Collects the events raised during an update or input, to hand them to every listener
+/// together afterwards, so nothing reacts part way through a scene changing its state
+#[derive(Debug, Default)]
+pub struct EventBus {
+    queue: Vec<GameEvent>
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn emit(&mut self, event: GameEvent) {
+        self.queue.push(event);
+    }
+
+    /// Gives each queued event to every listener in turn, in the order they were raised
+    pub fn dispatch(&mut self, listeners: &mut [&mut dyn Listener]) {
+        for event in self.queue.drain(..) {
+            for l in listeners.iter_mut() {
+                l.on_event(&event);
+            }
+        }
+    }
+}
@@ -1,372 +1,856 @@
-use std::{sync::Arc, time::{Duration, Instant}};
+use std::path::PathBuf;
+
+use cgmath::{Deg, Quaternion, Rotation3, Vector3, VectorSpace};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{config::*, console::{parse_arg, CommandResult, CommandSpec}, scenes::{pause::Pause, results::{Results, RunSummary}, Frame, Message, Scene, Shared, Transition}, tween::{target, Easing, Property, Timeline, TweenHandle, Tweenable, Tweener, Value}, d2::{backgroundmanager::BackgroundManager, layout::{Anchor, Anchored, Layout}, shape::Shape, net::Net, number::{Number, NumberFormat}, text::Align, retained::{Retained, RetainedShape}, spiral::Spiral}, d3::columngrid::ColumnGrid, events::GameEvent, hotseat::{key_answer, HotSeat, RoundResult}, savegame::{self, RunSave, SAVE_VERSION}, mathsutils::lerp, music::{MusicCue, Track}, ui::{button::Button, KeyResult, Ui, UiMessage}};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameMode {
+    Classic, // endless, a wrong answer just resets the streak
+    SuddenDeath, // the run ends at the first wrong answer
+    HotSeat // two players race on the same puzzles, first to HOT_SEAT_WIN_SCORE rounds wins
+}
+
+impl GameMode {
+    /// In the order they're offered on the mode select screen
+    pub const ALL: [GameMode; 3] = [GameMode::Classic, GameMode::SuddenDeath, GameMode::HotSeat];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::SuddenDeath => "Sudden death",
+            GameMode::HotSeat => "Two player"
+        }
+    }
 
-use cgmath::{Deg, Quaternion, Rotation3, Vector2};
-use rand::{thread_rng, Rng};
-use rodio::OutputStreamHandle;
-use winit::{dpi::{PhysicalPosition, PhysicalSize}, event::{ElementState, MouseButton}, window::Window};
+    /// How the mode is written in the config file and on the command line
+    pub fn key(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "classic",
+            GameMode::SuddenDeath => "sudden-death",
+            GameMode::HotSeat => "hot-seat"
+        }
+    }
 
-use crate::{config::*, d2::{backgroundmanager::BackgroundManager, mouseutils::{convert_mouse_coords, is_in_rounded_rect}, net::Net, number::Number, rectangle::{depth_sort, Rectangle}, spiral::Spiral, tovertind2d::ToVertInd2D}, d3::columngrid::ColumnGrid, mathsutils::lerp, soundmanager::SoundManager};
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.key() == key)
+    }
+}
 
-pub struct GameState<'a> {
-    gpustate: crate::gpustate::State<'a>,
+/// The in-game scene, one run of puzzles
+pub struct GameState {
+    mode: GameMode,
     animstate: AnimState,
+    tweens: Tweener<GameState>,
+    background_flash: Option<TweenHandle>,
     current_column_grid: ColumnGrid,
-    current_nets: Vec<Net>,
+    render_grid: ColumnGrid, // current_column_grid where it's drawn this frame, kept to save allocating one each frame
+    current_nets: Vec<Retained<Net>>,
     current_correct_index: usize,
     background_manager: BackgroundManager,
-    // option because the game can run without sound
-    sound_manager: Option<SoundManager>,
-    current_answer_buttons: Vec<Rectangle>,
-    timer_graphic: Spiral,
-    counter_graphic: Number,
-    best_counter_graphic: Number,
-    timer: f32, // frames
+    answer_buttons: Ui<Button>, // kept from round to round, only usable while Static
+    timer_graphic: Retained<Spiral>,
+    counter_graphic: Retained<Number>,
+    best_counter_graphic: Retained<Number>,
+    time_graphic: Retained<Number>,
+    accuracy_graphic: Retained<Number>,
+    hot_seat: Option<HotSeat>, // both players' scores, in two player
+    save_path: Option<PathBuf>, // where the run is kept, None if there's nowhere to keep it
+    layout: Layout,
+    timer: f32, // seconds
+    prev_timer: f32,
     timer_max: f32,
+    timer_start: f32, // timer_max for the first round, and after a wrong answer
+    timer_reduction: f32,
+    sidelen: u8,
+    rng: Pcg32, // everything random about the puzzles, so a seed repeats them
+    seed: u64, // what rng started from, made up when the config doesn't give one
+    puzzle_seed: u64, // what the current puzzle was made from
+    next_puzzle: Option<u64>, // a puzzle asked for from the console, instead of the next from rng
+    next_sidelen: Option<u8>, // a grid size asked for from the console, from the next puzzle on
+    timer_frozen: bool, // the timer override, set from the console
+    round_skipped: bool, // the round ending now was skipped rather than answered
     counter: u32,
     best_counter: u32,
-    frame: u64,
-    mouse_pos: Vector2<f32>,
-    mouse_is_on_button: Option<usize>,
-    mouse_was_on_button: Option<usize>,
-    mouse_clicked: (bool, bool),
-    last_mouse_clicked: (bool, bool),
+    run_best: u32, // best streak within this run
+    best_at_start: u32, // all time best before this run
+    answers: u32,
+    correct_answers: u32,
+    run_over: bool, // set once the last round has slid out
+    prev_grid_pos: Vector3<f32>,
     last_answer_was_correct: bool,
 }
 
-impl<'a> GameState<'a> {
-    pub async fn new(window: Arc<Window>, tex_arr: Vec<&[u8]>, sound_arr: Vec<(&'static[u8],f32)>, sound_stream_handle: Option<OutputStreamHandle>) -> Self {
-        let gpustate = crate::gpustate::State::new(window, tex_arr, DEFAULT_CLEARCOL).await;
+impl GameState {
+    pub fn new(shared: &Shared, mode: GameMode) -> Self {
+        let layout = shared.layout;
 
         let background_manager = BackgroundManager::new();
 
-        let sound_manager: Option<SoundManager>;
-        if let Some(osh) = sound_stream_handle {
-            sound_manager = Some(SoundManager::new(sound_arr, osh));
-        } else {
-            sound_manager = None;
-        }
-
-        let timer_graphic = Spiral::new(
-            [0.0,0.35].into(),
+        let timer_graphic = Retained::new(Spiral::new(
+            layout.resolve(TIMER_ANCHOR),
 			0.0,
 			0.5,
 			0.45,
 			0.5,
 			TIMER_TEX_INDEX,
-			0.7
-		);
+			0.0
+		));
 
-		let counter_graphic = Number::new(
-		    0,
-			[1.8,0.825].into(),
+		let counter_graphic = Retained::new(Number::new(
+		    0.0,
+		    NumberFormat::Integer,
+			layout.resolve(COUNTER_ANCHOR),
 			0.0,
 			0,
 			0.8,
 			0.15,
 			0.3,
 			0.0,
-			true
-		);
+			Align::Right
+		));
+
+		let best_counter_graphic = Retained::new(Number::new(
+		    0.0,
+		    NumberFormat::Integer,
+			layout.resolve(BEST_COUNTER_ANCHOR),
+			0.0,
+			0,
+			0.5,
+			0.075,
+			0.15,
+			0.0,
+			Align::Right
+		));
 
-		let best_counter_graphic = Number::new(
-		    0,
-			[1.76,0.6].into(),
+		let time_graphic = Retained::new(Number::new(
+		    shared.config.timer_max,
+		    NumberFormat::Tenths,
+			layout.resolve(TIME_ANCHOR),
 			0.0,
 			0,
 			0.5,
+			0.1,
+			0.2,
+			0.0,
+			Align::Left
+		));
+
+		// hidden until there's been an answer to be accurate about
+		let accuracy_graphic = Retained::new(Number::new(
+		    0.0,
+		    NumberFormat::Percent(0),
+			layout.resolve(ACCURACY_ANCHOR),
+			0.0,
+			0,
+			0.0,
 			0.075,
 			0.15,
 			0.0,
-			true
-		);
+			Align::Left
+		));
+
+		let config = &shared.config;
+		let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+		let mut rng = Pcg32::seed_from_u64(seed);
+		let puzzle_seed = rng.gen();
+		let (mut current_column_grid, mut current_nets, current_correct_index) = gen_puzzle(config.sidelen, puzzle_seed, &layout);
+
+		let (positions, scale) = layout.net_positions();
+		let mut answer_buttons = Ui::new();
+		for pos in positions {
+		    answer_buttons.add(Box::new(Button::new(
+		        "",
+		        Anchored::new(Anchor::Centre, pos.into()),
+		        BUTTON_SIZE * scale,
+		        BUTTON_SIZE * scale,
+		        BUTTON_CORNER_RADIUS * scale,
+		        2,
+		        MENU_LABEL_SIZE,
+		        shared.font.clone(),
+		        &layout
+		    )));
+		}
+		answer_buttons.set_all_disabled(true);
+
+        // start off screen, like every other round
+        current_column_grid.translate(Vector3::unit_x() * COLUMN_GRID_SLIDE_DISTANCE);
+        for net in current_nets.iter_mut() {
+            net.set_opacity(0.0)
+        }
 
-		let current_column_grid = gen_cg(COLUMN_GRID_SIDELEN);
-		let (
-		    current_answer_buttons,
-		    current_nets,
-		    current_correct_index
-		) = gen_next_nets(&current_column_grid);
 
+        let prev_grid_pos = current_column_grid.pos;
+        let render_grid = current_column_grid.clone();
 
-        let gs = Self {
-            gpustate,
-            animstate: AnimState::SlidingIn(ANIM_SLIDE_IN_LEN_FRAMES),
+        Self {
+            mode,
+            animstate: AnimState::SlidingIn,
+            tweens: Tweener::new(),
+            background_flash: None,
             background_manager,
-            sound_manager,
             current_column_grid,
+            render_grid,
             current_nets,
             current_correct_index,
-            current_answer_buttons,
+            answer_buttons,
             timer_graphic,
-            timer: TIMER_DEFAULT_MAX,
-            timer_max: TIMER_DEFAULT_MAX,
+            timer: config.timer_max,
+            prev_timer: config.timer_max,
+            timer_max: config.timer_max,
+            timer_start: config.timer_max,
+            timer_reduction: config.timer_reduction,
+            sidelen: config.sidelen,
+            rng,
+            seed,
+            puzzle_seed,
+            next_puzzle: None,
+            next_sidelen: None,
+            timer_frozen: false,
+            round_skipped: false,
             counter: 0,
             counter_graphic,
             best_counter: 0,
             best_counter_graphic,
-            frame: 0,
-            mouse_pos: [100.0;2].into(),
-            mouse_clicked: (false, false),
-            mouse_is_on_button: None,
-            mouse_was_on_button: None,
-            last_mouse_clicked: (false, false),
+            time_graphic,
+            accuracy_graphic,
+            hot_seat: (mode == GameMode::HotSeat).then(|| HotSeat::new(shared)),
+            save_path: savegame::save_path(),
+            layout,
+            prev_grid_pos,
+            run_best: 0,
+            best_at_start: 0,
+            answers: 0,
+            correct_answers: 0,
+            run_over: false,
             last_answer_was_correct: false
-        };
+        }
+    }
+    /// Carries on a saved run, from the start of the puzzle it was left on
+    pub fn resume(shared: &Shared, save: RunSave) -> Self {
+        let mut gs = Self::new(shared, save.mode);
+        let rot = Some(Quaternion::from_angle_y(Deg(135.0)));
+        let (positions, scale) = gs.layout.net_positions();
+
+        gs.sidelen = save.sidelen;
+        gs.current_column_grid = ColumnGrid::with_columns(column_grid_pos(save.sidelen), rot, save.grid);
+        gs.current_nets = save.nets.into_iter().zip(positions).map(|(columns, pos)| Retained::new(Net::from_columngrid(
+            &ColumnGrid::with_columns(column_grid_pos(save.sidelen), rot, columns),
+            NET_TEX_INDEX,
+            NET_LAYER,
+            pos,
+            net_scale(save.sidelen) * scale,
+            net_edge_thickness(save.sidelen) * scale,
+            1.0
+        ))).collect();
+        gs.current_correct_index = save.correct_index;
+        gs.timer = save.timer;
+        gs.prev_timer = save.timer;
+        gs.timer_max = save.timer_max;
+        gs.timer_start = save.timer_start;
+        gs.timer_reduction = save.timer_reduction;
+        gs.counter = save.counter;
+        gs.run_best = save.run_best;
+        gs.answers = save.answers;
+        gs.correct_answers = save.correct_answers;
+        gs.last_answer_was_correct = save.last_answer_was_correct;
+        gs.seed = save.seed;
+        gs.puzzle_seed = save.puzzle_seed;
+        gs.rng = save.rng;
+
+        gs.counter_graphic.set(gs.counter as f32);
+        if gs.answers > 0 {
+            gs.accuracy_graphic.set_opacity(0.5);
+            gs.accuracy_graphic.set(gs.correct_answers as f32 / gs.answers as f32);
+        }
+        gs.current_column_grid.translate(Vector3::unit_x() * COLUMN_GRID_SLIDE_DISTANCE);
+        for net in gs.current_nets.iter_mut() {
+            net.set_opacity(0.0)
+        }
+        gs.prev_grid_pos = gs.current_column_grid.pos;
 
+        // it was left between answering and the next puzzle coming in
+        if save.answered {
+            gs.start_next_round();
+        }
         gs
     }
-    pub fn update(&mut self) {
-        // dbg!(self.animstate.clone());
-
-        // stuff that is independent of current animstate
-        self.frame += 1;
-        self.gpustate.update();
-        self.background_manager.update(self.frame);
-        self.gpustate.set_bg_col(self.background_manager.current());
-
-        // stuff that is dependent on current animstate
-        match self.animstate {
-            AnimState::Static(mut should_switch) => {
-                // highlight buttons
-                self.mouse_is_on_button = None; // code after this will overwrite immediately
-                                                // if the mouse is on a button, so this is just
-                                                // set as a default
-                let mut clicked = -1;
-                for (i, b) in self.current_answer_buttons.iter_mut().enumerate() {
-                    if is_in_rounded_rect(self.mouse_pos, b, 0.14) {
-                        self.mouse_is_on_button = Some(i);
-                        if self.mouse_clicked.0 && !self.last_mouse_clicked.0 {
-                            clicked = i as i32;
-                        } else if self.mouse_clicked.0 {
-                            b.set_opacity(0.60);
-                        } else {
-                            b.set_opacity(0.3);
-                        }
-                    } else  {
-                        b.set_opacity(0.2);
-                    }
-                }
-                if self.mouse_is_on_button != self.mouse_was_on_button && self.mouse_is_on_button.is_some() {
-                    self.try_play_sound(0);
-                }
-                self.mouse_was_on_button = self.mouse_is_on_button;
-
-                // update timer graphic
-                self.timer -= 1.0;
-                let n = ( self.timer - self.timer_max ) / self.timer_max;
-                self.timer_graphic.set_depth(lerp(1.0-0.25, 1.0, -n));
-                if self.timer <= 10.0 {
-                    // return answer outside of bounds so it's always wrong
-                    self.process_answer(NET_COUNT + 1);
-                    should_switch = true;
-                }
-
-                if clicked >= 0 {
-                    self.process_answer(clicked as usize);
-                    should_switch = true;
-                    // remove hovered effect from any buttons
-                    for b in self.current_answer_buttons.iter_mut() {
-                        b.set_opacity(0.2);
-                    }
-                }
-
-                // switch if neccessary
-                if should_switch {
-                    self.animstate = AnimState::SlidingOut(ANIM_SLIDE_OUT_LEN_FRAMES);
-                }
+    // two player matches are short, and the save has nowhere to keep the second player
+    fn saves(&self) -> bool {
+        self.hot_seat.is_none()
+    }
+    /// Everything needed to carry the run on later, None once it's over
+    fn snapshot(&self) -> Option<RunSave> {
+        let answered = matches!(self.animstate, AnimState::SlidingOut);
+        // a skipped puzzle is shown again rather than remembering the skip
+        let answered = answered && !self.round_skipped;
+        let ending = answered && self.mode == GameMode::SuddenDeath && !self.last_answer_was_correct;
+        if self.run_over || ending || !self.saves() {
+            return None
+        }
+        Some(RunSave {
+            version: SAVE_VERSION,
+            mode: self.mode,
+            sidelen: self.sidelen,
+            grid: self.current_column_grid.columns.clone(),
+            nets: self.current_nets.iter().map(|n| n.columns().clone()).collect(),
+            correct_index: self.current_correct_index,
+            answered,
+            last_answer_was_correct: self.last_answer_was_correct,
+            timer: self.timer,
+            timer_max: self.timer_max,
+            timer_start: self.timer_start,
+            timer_reduction: self.timer_reduction,
+            counter: self.counter,
+            run_best: self.run_best,
+            answers: self.answers,
+            correct_answers: self.correct_answers,
+            seed: self.seed,
+            puzzle_seed: self.puzzle_seed,
+            rng: self.rng.clone()
+        })
+    }
+    fn save(&self) {
+        let Some(path) = self.save_path.as_deref().filter(|_| self.saves()) else {
+            return
+        };
+        match self.snapshot() {
+            Some(s) => if let Err(e) = savegame::save_to(path, &s) {
+                warn!("Failed to save the run: {e}");
             },
-            AnimState::SlidingIn(t) => {
-                let n = t / ANIM_SLIDE_IN_LEN_FRAMES;
-                self.timer_graphic.set_opacity(lerp(0.0, TIMER_OPACITY_MAX, 1.0-n));
-                for net in self.current_nets.iter_mut() {
-                    net.set_opacity(
-                        lerp(0.0, 1.0, 1.0-n)
-                    )
-                }
+            // lost, so an earlier save mustn't be left to carry on from
+            None => savegame::delete_at(path)
+        }
+    }
+    fn summary(&self) -> RunSummary {
+        RunSummary {
+            mode: self.mode,
+            score: self.score(),
+            previous_best: self.best_at_start,
+            scores: self.hot_seat.as_ref().map(|hs| [hs.score(0), hs.score(1)])
+        }
+    }
+    // the best streak in one player, the leader's rounds in two
+    fn score(&self) -> u32 {
+        match &self.hot_seat {
+            Some(hs) => hs.score(0).max(hs.score(1)),
+            None => self.run_best
+        }
+    }
+    fn end_run(&self, shared: &mut Shared) -> Transition {
+        if let Some(path) = self.save_path.as_deref().filter(|_| self.saves()) {
+            savegame::delete_at(path);
+        }
+        shared.events.emit(GameEvent::RunEnded { mode: self.mode, score: self.score() });
+        // back down to the title, with the results on top of it
+        Transition::Unwind(Some(Box::new(Results::new(self.summary(), shared))))
+    }
 
-                if self.current_column_grid.pos.x > 0.0 {
-                    let offset = ( self.current_column_grid.pos.x+0.2 ) * COLUMN_GRID_SLIDE_MULTIPLIER;
-                    self.current_column_grid.translate([-offset,0.0,0.0].into());
-                }
+    // fades the puzzle in while the grid slides to its resting place
+    fn start_slide_in(&mut self) {
+        self.animstate = AnimState::SlidingIn;
+
+        let mut parts = vec![
+            Timeline::tween(
+                target(|gs: &mut GameState| Some(&mut gs.current_column_grid as &mut dyn Tweenable)),
+                Property::Position,
+                Value::Vec3(column_grid_pos(self.sidelen)),
+                ANIM_SLIDE_IN_LEN,
+                Easing::ExpoOut
+            ),
+            Timeline::tween(
+                target(|gs: &mut GameState| Some(&mut gs.timer_graphic as &mut dyn Tweenable)),
+                Property::Opacity,
+                Value::Float(TIMER_OPACITY_MAX),
+                ANIM_SLIDE_IN_LEN,
+                Easing::Linear
+            )
+        ];
+        for i in 0..self.current_nets.len() {
+            parts.push(Timeline::tween(
+                target(move |gs: &mut GameState| gs.current_nets.get_mut(i).map(|n| n as &mut dyn Tweenable)),
+                Property::Opacity,
+                Value::Float(1.0),
+                ANIM_SLIDE_IN_LEN,
+                Easing::Linear
+            ));
+        }
 
-                // decrement anim timer and switch if neccessary
-                if t != 0.0 {
-                    self.animstate = AnimState::SlidingIn(t - 1.0);
-                } else {
-                    self.animstate = AnimState::Static(false);
-                }
-            },
-            AnimState::SlidingOut(t) => {
-                let n = t / ANIM_SLIDE_OUT_LEN_FRAMES;
-                self.timer_graphic.set_opacity(lerp(TIMER_OPACITY_MAX, 0.0, 1.0-n));
-                for net in self.current_nets.iter_mut() {
-                    net.set_opacity(
-                        lerp(1.0, 0.0, 1.0-n)
-                    )
-                }
+        self.tweens.play_then(Timeline::parallel(parts), |gs| {
+            gs.animstate = AnimState::Static;
+            gs.answer_buttons.set_all_disabled(false);
+        });
+    }
+    // the reverse of sliding in, then sets up the next round and slides that in
+    fn start_slide_out(&mut self) {
+        self.animstate = AnimState::SlidingOut;
+        self.answer_buttons.reset();
+        self.answer_buttons.set_all_disabled(true);
+
+        let mut parts = vec![
+            Timeline::tween(
+                target(|gs: &mut GameState| Some(&mut gs.current_column_grid as &mut dyn Tweenable)),
+                Property::Position,
+                Value::Vec3(column_grid_pos(self.sidelen) - Vector3::unit_x() * COLUMN_GRID_SLIDE_DISTANCE),
+                ANIM_SLIDE_OUT_LEN,
+                Easing::ExpoIn
+            ),
+            Timeline::tween(
+                target(|gs: &mut GameState| Some(&mut gs.timer_graphic as &mut dyn Tweenable)),
+                Property::Opacity,
+                Value::Float(0.0),
+                ANIM_SLIDE_OUT_LEN,
+                Easing::Linear
+            )
+        ];
+        for i in 0..self.current_nets.len() {
+            parts.push(Timeline::tween(
+                target(move |gs: &mut GameState| gs.current_nets.get_mut(i).map(|n| n as &mut dyn Tweenable)),
+                Property::Opacity,
+                Value::Float(0.0),
+                ANIM_SLIDE_OUT_LEN,
+                Easing::Linear
+            ));
+        }
 
-                if self.current_column_grid.pos.x > -30.0 {
-                    let offset = ( self.current_column_grid.pos.x ) * COLUMN_GRID_SLIDE_MULTIPLIER;
-                    self.current_column_grid.translate([-(0.2-offset),0.0,0.0].into());
-                }
+        // a wrong answer in sudden death is the last round, as is the one that wins a two player match
+        let last_round = match self.mode {
+            GameMode::SuddenDeath => !self.last_answer_was_correct && !self.round_skipped,
+            GameMode::HotSeat => self.hot_seat.as_ref().is_some_and(|hs| hs.winner().is_some()),
+            GameMode::Classic => false
+        };
+        if last_round {
+            self.tweens.play_then(Timeline::parallel(parts), |gs| {
+                gs.run_over = true;
+            });
+            return
+        }
 
-                // decrement anim timer and switch if neccessary
-                if t != 0.0 {
-                    self.animstate = AnimState::SlidingOut(t - 1.0);
-                } else {
-                    self.animstate = AnimState::Between(ANIM_BETWEEN_SLIDES_FRAMES);
-                }
-            },
-            AnimState::Between(t) => {
-                // only run on first frame of between
-                if t == ANIM_BETWEEN_SLIDES_FRAMES {
-                    if self.last_answer_was_correct {
-                        self.timer_max *= TIMER_REDUCTION_MULTIPLER;
-                    } else {
-                        self.timer_max = TIMER_DEFAULT_MAX;
-                    }
-                    self.timer = self.timer_max;
-                    let n = ( self.timer - self.timer_max ) / self.timer_max;
-                    self.timer_graphic.set_depth(lerp(1.0-0.25, 1.0, -n));
-
-                    self.current_column_grid = gen_cg(COLUMN_GRID_SIDELEN);
-                    (
-                        self.current_answer_buttons,
-                        self.current_nets,
-                        self.current_correct_index
-                    ) = gen_next_nets(&self.current_column_grid);
-                    for net in self.current_nets.iter_mut() {
-                        net.set_opacity(0.0)
-                    }
-                    self.current_column_grid.translate([30.0,0.0,0.0].into());
-                }
+        self.tweens.play(Timeline::sequence(vec![
+            Timeline::parallel(parts),
+            Timeline::call(|gs: &mut GameState| {
+                gs.start_next_round();
+                gs.animstate = AnimState::Between;
+            }),
+            Timeline::wait(ANIM_BETWEEN_SLIDES),
+            Timeline::call(|gs: &mut GameState| gs.start_slide_in())
+        ]));
+    }
+    // flashes the background a colour, then fades back to normal
+    fn flash_background(&mut self, col: [f32;4]) {
+        // a new flash takes over from any that is still fading
+        if let Some(h) = self.background_flash.take() {
+            self.tweens.cancel(h);
+        }
+        self.background_flash = Some(self.tweens.play(Timeline::tween_from(
+            target(|gs: &mut GameState| Some(&mut gs.background_manager as &mut dyn Tweenable)),
+            Property::Colour,
+            Value::Colour(col),
+            Value::Colour(DEFAULT_CLEARCOL),
+            ANIM_BACKGROUND_FLASH_LEN,
+            Easing::Linear
+        )));
+    }
+    // sets up the next puzzle, off screen and ready to slide in
+    fn start_next_round(&mut self) {
+        // a skipped round leaves the time alone
+        if !std::mem::take(&mut self.round_skipped) {
+            if self.last_answer_was_correct {
+                self.timer_max *= self.timer_reduction;
+            } else {
+                self.timer_max = self.timer_start;
+            }
+        }
+        self.timer = self.timer_max;
+        self.prev_timer = self.timer;
+        if let Some(hs) = &mut self.hot_seat {
+            hs.start_round();
+        }
 
-                // decrement anim timer and switch if neccessary
-                if t != 0.0 {
-                    self.animstate = AnimState::Between(t - 1.0);
-                } else {
-                    self.animstate = AnimState::SlidingIn(ANIM_SLIDE_IN_LEN_FRAMES);
-                }
+        if let Some(n) = self.next_sidelen.take() {
+            self.sidelen = n;
+        }
+        self.puzzle_seed = self.next_puzzle.take().unwrap_or_else(|| self.rng.gen());
+        (self.current_column_grid, self.current_nets, self.current_correct_index) = gen_puzzle(self.sidelen, self.puzzle_seed, &self.layout);
+        for net in self.current_nets.iter_mut() {
+            net.set_opacity(0.0)
+        }
+        self.current_column_grid.translate(Vector3::unit_x() * COLUMN_GRID_SLIDE_DISTANCE);
+        // don't interpolate the jump back to the start
+        self.prev_grid_pos = self.current_column_grid.pos;
+    }
+    // answers the round and moves on to the next
+    fn answer(&mut self, ans_index: usize, shared: &mut Shared) {
+        self.process_answer(ans_index, shared);
+        self.start_slide_out();
+    }
+    // one player's answer in two player, which only ends the round if it's right or nobody's left to answer
+    fn answer_for(&mut self, player: usize, ans_index: usize, shared: &mut Shared) {
+        let Some(hs) = &mut self.hot_seat else {
+            return
+        };
+        let correct = ans_index == self.current_correct_index;
+        let result = hs.answer(player, correct);
+        let streak = hs.streak(player);
+
+        match result {
+            RoundResult::Ignored => return,
+            RoundResult::Won => {
+                shared.events.emit(GameEvent::AnswerCorrect { streak });
+                self.flash_background(HOT_SEAT_COLOURS[player]);
+            }
+            RoundResult::LockedOut | RoundResult::AllLockedOut => {
+                shared.events.emit(GameEvent::AnswerIncorrect { timed_out: false });
+                self.flash_background([1.0,0.5,0.5,1.0]);
+            }
+        }
+        if result != RoundResult::LockedOut {
+            self.answers += 1;
+            if correct {
+                self.correct_answers += 1;
             }
+            self.last_answer_was_correct = correct;
+            self.start_slide_out();
         }
     }
-    pub fn process_answer(&mut self, ans_index: usize) {
+    fn process_answer(&mut self, ans_index: usize, shared: &mut Shared) {
+        self.answers += 1;
         if ans_index == self.current_correct_index {
-            self.answer_correct();
+            self.correct_answers += 1;
+            self.answer_correct(shared);
         } else {
-            self.answer_incorrect();
+            self.answer_incorrect(ans_index > NET_COUNT, shared);
         }
-        self.animstate = AnimState::Static(true);
+        self.accuracy_graphic.set_opacity(0.5);
+        self.accuracy_graphic.set(self.correct_answers as f32 / self.answers as f32);
     }
-    pub fn answer_correct(&mut self) {
-        self.try_play_sound(2);
-        self.background_manager.set_bg([0.5,1.0,0.5,1.0]);
-        self.background_manager.start_anim(DEFAULT_CLEARCOL, 30);
+    fn answer_correct(&mut self, shared: &mut Shared) {
+        self.flash_background([0.5,1.0,0.5,1.0]);
         self.counter += 1;
+        shared.events.emit(GameEvent::AnswerCorrect { streak: self.counter });
         self.last_answer_was_correct = true;
+        self.run_best = self.run_best.max(self.counter);
         if self.counter > self.best_counter {
             self.best_counter = self.counter;
-            self.best_counter_graphic.set(self.best_counter);
+            self.best_counter_graphic.roll_to(self.best_counter as f32, NUMBER_ROLL_LEN);
+            shared.best_score = self.best_counter;
         }
-        self.counter_graphic.set(self.counter);
+        self.counter_graphic.roll_to(self.counter as f32, NUMBER_ROLL_LEN);
     }
-    pub fn answer_incorrect(&mut self) {
-        self.try_play_sound(3);
-        self.background_manager.set_bg([1.0,0.5,0.5,1.0]);
-        self.background_manager.start_anim(DEFAULT_CLEARCOL, 30);
+    // timing out counts as a wrong answer
+    fn answer_incorrect(&mut self, timed_out: bool, shared: &mut Shared) {
+        shared.events.emit(GameEvent::AnswerIncorrect { timed_out });
+        self.flash_background([1.0,0.5,0.5,1.0]);
         self.last_answer_was_correct = false;
         self.counter = 0;
-        self.counter_graphic.set(self.counter);
+        self.counter_graphic.roll_to(0.0, NUMBER_ROLL_LEN);
     }
-    pub fn render(&mut self) -> Result<Duration, wgpu::SurfaceError> {
-        let start = Instant::now();
-        let mut cgs: Vec<ColumnGrid> = vec![];
-        cgs.push(self.current_column_grid.clone());
-
-        let nets: Vec<Net> = self.current_nets.clone();
-
-        let mut v: Vec<Box<dyn ToVertInd2D>> = vec![];
-
-        v.extend(nets.iter().map(|n| Box::new(n) as Box<dyn ToVertInd2D> ));
-        v.extend(self.current_answer_buttons.iter().map(|n| Box::new(n) as Box<dyn ToVertInd2D> ));
-        v.push(Box::new(self.timer_graphic.clone()));
-        v.push(Box::new(self.counter_graphic.clone()));
-        v.push(Box::new(self.best_counter_graphic.clone()));
-
-        let ds = depth_sort(v);
-        
-        let end = Instant::now();
-        let rt = self.gpustate.render(cgs, ds)?;
-        let t = (end - start) + rt;
-        Ok(t)
+    // moves on to the next puzzle without answering, if there's one showing to move on from
+    fn skip_round(&mut self) -> bool {
+        if !matches!(self.animstate, AnimState::Static) {
+            return false
+        }
+        self.round_skipped = true;
+        self.start_slide_out();
+        true
+    }
+    // for console commands that change the next puzzle, which is brought straight in if it can be
+    fn skip_to_next(&mut self, what: String) -> String {
+        if self.skip_round() {
+            format!("{what} now")
+        } else {
+            format!("{what} from the next puzzle")
+        }
+    }
+    fn set_grid_size(&mut self, shared: &mut Shared, args: &[&str]) -> CommandResult {
+        let n: u8 = parse_arg(args, 0, "a grid size")?;
+        let range = CONFIG_SIDELEN_RANGE;
+        if !range.contains(&n) {
+            return Err(format!("The grid size needs to be between {} and {}", range.start(), range.end()))
+        }
+        // later runs too
+        shared.config.sidelen = n;
+        self.next_sidelen = Some(n);
+        Ok(self.skip_to_next(format!("Grid is {n}x{n}")))
     }
-    pub fn print_net_debug(&self) {
+    /// Each net's squares and which others it matches, for the console
+    pub fn net_debug(&self) -> String {
+        let mut out = String::new();
         for (i,n) in self.current_nets.iter().enumerate() {
-            println!(
-                "Net {i}:\n\n{}",
-                n.square_debug_info()
-            );
+            out += &format!("Net {i}:\n{}", n.square_debug_info());
 
             // test for equality against every other net
             let mut matches: String = "Matches with nets: ".into();
             for (i, n2) in self.current_nets.iter().enumerate() {
                 if n.is_identical(n2) {matches += &format!("{} ", i)}
             }
-            println!("{}\n", matches)
+            out += &matches;
+            out += "\n";
+        }
+        out + &format!("Net {} is correct", self.current_correct_index)
+    }
+}
+
+impl Scene for GameState {
+    fn on_enter(&mut self, shared: &mut Shared) {
+        self.best_counter = shared.best_score;
+        self.best_at_start = shared.best_score;
+        self.best_counter_graphic.set(self.best_counter as f32);
+        // this run is the one to carry on with from now on
+        if self.saves() {
+            shared.saved_run = None;
+        }
+        self.start_slide_in();
+    }
+    fn on_resume(&mut self, shared: &mut Shared, message: Option<Message>) -> Transition {
+        match message {
+            Some(Message::EndRun) => self.end_run(shared),
+            None => Transition::None
+        }
+    }
+    /// Advances the game by `dt` seconds
+    fn update(&mut self, shared: &mut Shared, dt: f32) -> Transition {
+        // remember where things were, so rendering can interpolate between steps
+        self.prev_grid_pos = self.current_column_grid.pos;
+        self.prev_timer = self.timer;
+
+        // stuff that is independent of current animstate
+        // the tweener is taken out while it runs so it can be handed the rest of the state,
+        // anything its callbacks queue up in the meantime is merged back in afterwards
+        let mut tweens = std::mem::take(&mut self.tweens);
+        tweens.update(dt, self);
+        tweens.absorb(std::mem::take(&mut self.tweens));
+        self.tweens = tweens;
+
+        for n in [&mut self.counter_graphic, &mut self.best_counter_graphic] {
+            if n.is_rolling() {
+                n.update(dt);
+            }
+        }
+        if let Some(hs) = &mut self.hot_seat {
+            hs.update(dt);
+        }
+
+        if self.run_over {
+            return self.end_run(shared)
+        }
+
+        // stuff that is dependent on current animstate
+        // (everything but Static is driven by the tweens started in start_slide_in/out)
+        if let AnimState::Static = self.animstate {
+            if let Some(UiMessage::Clicked(i)) = self.answer_buttons.update(shared) {
+                match self.hot_seat {
+                    Some(_) => self.answer_for(HOT_SEAT_MOUSE_PLAYER, i, shared),
+                    None => self.answer(i, shared)
+                }
+            } else if !self.timer_frozen {
+                // count down the timer
+                self.timer -= dt;
+                if self.timer <= TIMER_MIN {
+                    if let Some(hs) = &mut self.hot_seat {
+                        hs.time_out();
+                    }
+                    // return answer outside of bounds so it's always wrong
+                    self.answer(NET_COUNT + 1, shared);
+                }
+            }
         }
+
+        Transition::None
     }
-    pub fn window(&self) -> &Window {
-        self.gpustate.window()
+    fn input(&mut self, shared: &mut Shared, key: &Key) -> Transition {
+        match (&self.hot_seat, key_answer(key)) {
+            (Some(_), Some((player, i))) => {
+                if let AnimState::Static = self.animstate {
+                    self.answer_for(player, i, shared);
+                }
+                return Transition::None
+            }
+            // moving focus and pressing enter can't tell whose answer it is
+            (Some(_), None) => {}
+            // the buttons are disabled unless Static, so they can't be answered with early
+            (None, _) => match self.answer_buttons.input(key) {
+                KeyResult::Message(UiMessage::Clicked(i)) => {
+                    self.answer(i, shared);
+                    return Transition::None
+                }
+                KeyResult::Ignored => {}
+                _ => return Transition::None
+            }
+        }
+        match key {
+            Key::Named(NamedKey::Escape) => {
+                self.save();
+                Transition::Push(Box::new(Pause::new(shared)))
+            }
+            _ => Transition::None
+        }
     }
-    pub fn resize_window(&mut self, s: PhysicalSize<u32>) {
-        self.gpustate.resize(s);
+    fn on_close(&mut self, _shared: &mut Shared) {
+        self.save();
     }
-    pub fn refresh_window(&mut self) {
-        self.gpustate.fake_resize();
+    // builds as the clock runs down, and as the streak grows
+    fn music(&self) -> Option<MusicCue> {
+        let pressure = (1.0 - self.timer / self.timer_max).clamp(0.0, 1.0);
+        let streak = self.hot_seat.as_ref().map_or(self.counter, |hs| hs.top_streak());
+        let streak = (streak as f32 / MUSIC_STREAK_FULL as f32).min(1.0);
+        Some(MusicCue {
+            track: Track::Game,
+            intensity: (pressure * pressure + streak * 0.5).min(1.0)
+        })
     }
-    pub fn try_play_sound(&mut self, index: u32) {
-        if self.sound_manager.is_some() {
-            self.sound_manager.as_mut().unwrap().play(index)
+    fn debug_info(&self, out: &mut Vec<String>) {
+        out.push(format!("{}: {:?}, round {}", self.mode.name(), self.animstate, self.answers + 1));
+        out.push(format!("Timer: {:.2}s of {:.2}s, starting at {:.2}s", self.timer, self.timer_max, self.timer_start));
+        match &self.hot_seat {
+            Some(hs) => hs.debug_info(out),
+            None => out.push(format!("Streak: {}, run best {}", self.counter, self.run_best))
+        }
+        out.push(format!("Seed: {}, puzzle {}", self.seed, self.puzzle_seed));
+        if self.timer_frozen {
+            out.push("Timer override on".into());
         }
     }
-    pub fn mouse_pos_update(&mut self, p: PhysicalPosition<f64>) {
-        self.mouse_pos = convert_mouse_coords(p, self.gpustate.size, self.gpustate.aspect_uniform.aspect);
+    fn commands(&self, out: &mut Vec<CommandSpec>) {
+        out.extend(COMMANDS);
     }
-    pub fn mouse_click_update(&mut self, state: ElementState, button: MouseButton) {
-        match button {
-            MouseButton::Left => {
-                self.mouse_clicked.0 = match state {
-                    ElementState::Pressed => {true}
-                    ElementState::Released => {false}
-                }
+    fn run_command(&mut self, shared: &mut Shared, name: &str, args: &[&str]) -> Option<CommandResult> {
+        let result = match name {
+            "skip" => match self.skip_round() {
+                true => Ok("Skipped".into()),
+                false => Err("There's no puzzle to skip until this one has slid in".into())
             }
-            MouseButton::Right => {
-                self.mouse_clicked.1 = match state {
-                    ElementState::Pressed => {true}
-                    ElementState::Released => {false}
+            "seed" => parse_arg(args, 0, "a whole number").map(|seed| {
+                self.seed = seed;
+                self.rng = Pcg32::seed_from_u64(seed);
+                format!("Puzzles after this one come from seed {seed}")
+            }),
+            "timer" => parse_arg::<f32>(args, 0, "a number of seconds").and_then(|t| match t.is_finite() && t > 0.0 {
+                true => {
+                    self.timer = t;
+                    self.prev_timer = t;
+                    Ok(format!("{t}s left this round"))
                 }
+                false => Err("The timer needs to be a number of seconds more than 0".into())
+            }),
+            "timer_override" => {
+                self.timer_frozen = !self.timer_frozen;
+                Ok(format!("Timer override {}", if self.timer_frozen {"on"} else {"off"}))
             }
-            _ => {}
+            "nets" => Ok(self.net_debug()),
+            "grid" => self.set_grid_size(shared, args),
+            "puzzle" => parse_arg(args, 0, "a puzzle seed").map(|seed| {
+                self.next_puzzle = Some(seed);
+                self.skip_to_next(format!("Puzzle {seed}"))
+            }),
+            _ => return None
+        };
+        Some(result)
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>, alpha: f32) {
+        let timer = lerp(self.prev_timer, self.timer, alpha);
+        // the console can give a round more time than it started with, which shows as a full spiral
+        let n = (( timer - self.timer_max ) / self.timer_max).min(0.0);
+        let depth = lerp(1.0-0.25, 1.0, -n);
+        if self.timer_graphic.depth() != depth {
+            self.timer_graphic.set_depth(depth);
+        }
+        // only as precise as the readout, so it isn't rebuilt every frame
+        let shown_time = (timer * 10.0).ceil() / 10.0;
+        if self.time_graphic.value() != shown_time {
+            self.time_graphic.set(shown_time);
         }
+
+        // draw the grid where it would be part way through the next step
+        self.render_grid.clone_from(&self.current_column_grid);
+        self.render_grid.pos = self.prev_grid_pos.lerp(self.current_column_grid.pos, alpha);
+        frame.grid = Some(&self.render_grid);
+        frame.clear_colour = Some(self.background_manager.current());
+
+        frame.shapes.extend(self.current_nets.iter_mut().map(|n| n as &mut dyn RetainedShape));
+        self.answer_buttons.render(frame);
+        frame.shapes.push(&mut self.timer_graphic);
+        // both players' scores take the place of the streak, best and time
+        if let Some(hs) = &mut self.hot_seat {
+            hs.render(frame);
+            return
+        }
+        frame.shapes.push(&mut self.counter_graphic);
+        frame.shapes.push(&mut self.best_counter_graphic);
+        frame.shapes.push(&mut self.time_graphic);
+        frame.shapes.push(&mut self.accuracy_graphic);
+    }
+    // move everything on screen to suit the current window shape
+    fn relayout(&mut self, layout: &Layout) {
+        self.layout = *layout;
+
+        self.timer_graphic.set_pos(self.layout.resolve(TIMER_ANCHOR));
+        self.counter_graphic.set_pos(self.layout.resolve(COUNTER_ANCHOR));
+        self.best_counter_graphic.set_pos(self.layout.resolve(BEST_COUNTER_ANCHOR));
+        self.time_graphic.set_pos(self.layout.resolve(TIME_ANCHOR));
+        self.accuracy_graphic.set_pos(self.layout.resolve(ACCURACY_ANCHOR));
+        if let Some(hs) = &mut self.hot_seat {
+            hs.relayout(layout);
+        }
+
+        let (positions, scale) = self.layout.net_positions();
+        for (i, pos) in positions.into_iter().enumerate() {
+            if let Some(n) = self.current_nets.get_mut(i) {
+                n.place(pos, net_scale(self.sidelen) * scale, net_edge_thickness(self.sidelen) * scale);
+            }
+            if let Some(b) = self.answer_buttons.get_mut(i) {
+                b.place(
+                    Anchored::new(Anchor::Centre, pos.into()),
+                    BUTTON_SIZE * scale,
+                    BUTTON_SIZE * scale,
+                    BUTTON_CORNER_RADIUS * scale
+                );
+            }
+        }
+        self.answer_buttons.relayout(layout);
     }
 }
 
-// f32s store how long the animation has left
-// bool stores whether an answer has just been given
+const COMMANDS: [CommandSpec; 7] = [
+    CommandSpec { name: "skip", usage: "", help: "Moves on to the next puzzle without answering" },
+    CommandSpec { name: "seed", usage: "<number>", help: "Reseeds the puzzles for the rest of the run" },
+    CommandSpec { name: "timer", usage: "<seconds>", help: "Sets the time left this round" },
+    CommandSpec { name: "timer_override", usage: "", help: "Stops or restarts the timer counting down" },
+    CommandSpec { name: "nets", usage: "", help: "Shows the nets' squares and which match" },
+    CommandSpec { name: "grid", usage: "<size>", help: "Changes the number of columns along each side" },
+    CommandSpec { name: "puzzle", usage: "<seed>", help: "Brings in the puzzle made from a seed, as shown on the debug overlay" },
+];
+
+// the timings of everything but Static live in the tweens that drive them
 #[derive(Debug, Clone)]
 pub enum AnimState {
-    Static(bool), // when waiting for input
-    SlidingOut(f32),
-    SlidingIn(f32),
-    Between(f32) // small buffer between questions
+    Static, // when waiting for input
+    SlidingOut,
+    SlidingIn,
+    Between // small buffer between questions
+}
+
+// everything about a puzzle comes from its own seed, so any puzzle can be made again from just that
+fn gen_puzzle(sidelen: u8, seed: u64, layout: &Layout) -> (ColumnGrid, Vec<Retained<Net>>, usize) {
+    let mut rng = Pcg32::seed_from_u64(seed);
+    let cg = gen_cg(sidelen, &mut rng);
+    let (nets, correct) = gen_next_nets(&cg, layout, &mut rng);
+    (cg, nets, correct)
 }
 
-pub fn gen_cg(sidelen: u8) -> ColumnGrid {
+pub fn gen_cg(sidelen: u8, rng: &mut impl Rng) -> ColumnGrid {
     loop {
         let next_cg = ColumnGrid::new_random(
-            COLUMN_GRID_POS,
+            column_grid_pos(sidelen),
             Some(Quaternion::from_angle_y(Deg(135.0))),
-            sidelen
+            sidelen,
+            rng
         );
         if next_cg.count_occupied_columns() > 5 && next_cg.highest_column() != 1 {
             break next_cg
@@ -374,47 +858,46 @@ pub fn gen_cg(sidelen: u8) -> ColumnGrid {
     }
 }
 
-// returns (answer buttons, nets, correct index)
-pub fn gen_next_nets(cg: &ColumnGrid) -> (Vec<Rectangle>, Vec<Net>, usize) {
+// returns (nets, correct index)
+pub fn gen_next_nets(cg: &ColumnGrid, layout: &Layout, rng: &mut impl Rng) -> (Vec<Retained<Net>>, usize) {
 
-    let mut rng = thread_rng();
+    // sized to the grid, so any size of grid makes nets that fill the same space
+    let net_scale = net_scale(cg.sidelen);
+    let edge_thickness = net_edge_thickness(cg.sidelen);
     let next_correct = rng.gen_range(0..NET_COUNT);
     let mut next_nets: Vec<Net> = vec![];
 
-    let mut answer_buttons = vec![];
-
     let correct_net = Net::from_columngrid(
-        &cg,
+        cg,
         NET_TEX_INDEX,
         NET_LAYER,
         [0.0;2].into(),
-        NET_SCALE,
-        NET_EDGE_THICKNESS,
+        net_scale,
+        edge_thickness,
         1.0
     );
 
 
-    for i in 0..NET_COUNT {
-        let mut x = -((NET_COUNT-1) as f32 * NET_GAP) * 0.5;
-        x += NET_GAP * i as f32;
-        let pos = Vector2::new(x, NET_Y_OFFSET);
+    let (positions, scale) = layout.net_positions();
+
+    for (i, pos) in positions.into_iter().enumerate() {
 
         let mut n: Net;
         if i == next_correct {
             n = Net::from_columngrid(
-                &cg,
+                cg,
                 NET_TEX_INDEX,
                 NET_LAYER,
                 pos,
-                NET_SCALE,
-                NET_EDGE_THICKNESS,
+                net_scale * scale,
+                edge_thickness * scale,
                 1.0
             );
         } else {
             loop {
                 let cg = loop {
                     let mut c = cg.clone();
-                    c.random_changes(NET_RANDOM_CHANGES);
+                    c.random_changes(NET_RANDOM_CHANGES, rng);
                     if c.highest_column() > 1 {
                         break c
                     }                    };
@@ -423,8 +906,8 @@ pub fn gen_next_nets(cg: &ColumnGrid) -> (Vec<Rectangle>, Vec<Net>, usize) {
                     NET_TEX_INDEX,
                     NET_LAYER,
                     pos,
-                    NET_SCALE,
-                    NET_EDGE_THICKNESS,
+                    net_scale * scale,
+                    edge_thickness * scale,
                     1.0
                 );
 
@@ -440,19 +923,56 @@ pub fn gen_next_nets(cg: &ColumnGrid) -> (Vec<Rectangle>, Vec<Net>, usize) {
             }
         }
         next_nets.push(n);
-
-        let bg = Rectangle::new(
-            0.75,
-            0.75,
-            pos,
-            0.0,
-            2,
-            BUTTON_TEX_INDEX,
-            false,
-            0.2
-        );
-        answer_buttons.push(bg);
     };
 
-    (answer_buttons, next_nets, next_correct)
+    (
+        next_nets.into_iter().map(Retained::new).collect(),
+        next_correct
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{configfile::GameConfig, d2::font::FontAtlas, events::EventBus, scenes::{MouseState, UserSettings}};
+
+    fn shared() -> Shared {
+        Shared {
+            events: EventBus::new(),
+            config: GameConfig::default(),
+            layout: Layout::new(16.0 / 9.0),
+            mouse: MouseState::new(),
+            best_score: 0,
+            font: Arc::new(FontAtlas::new(include_bytes!("res/font/Comfortaa.ttf")).unwrap()),
+            settings: UserSettings::default(),
+            saved_run: None
+        }
+    }
+
+    #[test]
+    fn closing_after_losing_leaves_no_save() {
+        // saved somewhere of the test's own rather than the player's data directory
+        let dir = std::env::temp_dir().join(format!("cube_game_test_{}", std::process::id()));
+        let path = dir.join(SAVE_FILE_NAME);
+
+        let mut shared = shared();
+        let mut gs = GameState::new(&shared, GameMode::SuddenDeath);
+        gs.save_path = Some(path.clone());
+        gs.on_enter(&mut shared);
+        gs.animstate = AnimState::Static;
+
+        // paused part way through, which saves
+        gs.save();
+        assert!(path.exists());
+
+        // then closed while the losing answer slides out
+        gs.answer((gs.current_correct_index + 1) % NET_COUNT, &mut shared);
+        assert!(matches!(gs.animstate, AnimState::SlidingOut));
+        gs.on_close(&mut shared);
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
@@ -0,0 +1,168 @@
+use std::{fmt::Display, ops::Range};
+
+use log::info;
+
+use crate::config::{GPU_BUFFER_GROWTH_HEADROOM, GPU_BUFFER_INITIAL_SIZE, GPU_BUFFER_SHRINK_AFTER_FRAMES, GPU_BUFFER_SHRINK_THRESHOLD};
+
+/// A GPU buffer that reallocates itself when a write doesn't fit,
+/// and shrinks back down after a sustained period of low usage
+pub struct GrowableBuffer {
+    label: &'static str,
+    usage: wgpu::BufferUsages,
+    buffer: wgpu::Buffer,
+    capacity: u64, // bytes
+    len: u64, // bytes written by the last write
+    low_usage_frames: u32,
+}
+
+impl GrowableBuffer {
+    pub fn new(device: &wgpu::Device, label: &'static str, usage: wgpu::BufferUsages) -> Self {
+        let capacity = align(GPU_BUFFER_INITIAL_SIZE);
+        let buffer = create_buffer(device, label, usage, capacity);
+
+        Self {
+            label,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            buffer,
+            capacity,
+            len: 0,
+            low_usage_frames: 0
+        }
+    }
+
+    /// Writes `data` to the start of the buffer, growing or shrinking it first if needed.
+    /// Should be called once per frame so that the shrink heuristic counts frames.
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8]) {
+        self.fit(device, data.len() as u64);
+        self.len = data.len() as u64;
+        write_padded(queue, &self.buffer, 0, data);
+    }
+
+    /// Like `write`, but only uploads the given element ranges of `data`,
+    /// unless the buffer had to be reallocated in which case everything is uploaded.
+    pub fn write_ranges<T: bytemuck::Pod>(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[T], ranges: &[Range<usize>]) {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        let reallocated = self.fit(device, bytes.len() as u64);
+        self.len = bytes.len() as u64;
+
+        if reallocated {
+            write_padded(queue, &self.buffer, 0, bytes);
+            return
+        }
+
+        let size = std::mem::size_of::<T>();
+        for r in ranges {
+            let start = r.start * size;
+            let end = (r.end * size).min(bytes.len());
+            if start < end {
+                write_padded(queue, &self.buffer, start as u64, &bytes[start..end]);
+            }
+        }
+    }
+
+    // makes sure there is room for `size` bytes, returns true if the buffer was reallocated
+    fn fit(&mut self, device: &wgpu::Device, size: u64) -> bool {
+        let needed = align(size);
+
+        if needed > self.capacity {
+            let new_capacity = align((needed as f32 * GPU_BUFFER_GROWTH_HEADROOM) as u64);
+            info!(
+                "Growing {} from {} to {} bytes",
+                self.label, self.capacity, new_capacity
+            );
+            self.reallocate(device, new_capacity);
+            return true
+        }
+
+        // only counted while there's something to shrink to, so a buffer already at its
+        // smallest doesn't count up forever
+        let shrunk = align(
+            ((needed as f32 * GPU_BUFFER_GROWTH_HEADROOM) as u64).max(GPU_BUFFER_INITIAL_SIZE)
+        );
+        if (needed as f32) < self.capacity as f32 * GPU_BUFFER_SHRINK_THRESHOLD && shrunk < self.capacity {
+            self.low_usage_frames += 1;
+            if self.low_usage_frames >= GPU_BUFFER_SHRINK_AFTER_FRAMES {
+                info!(
+                    "Shrinking {} from {} to {} bytes",
+                    self.label, self.capacity, shrunk
+                );
+                self.reallocate(device, shrunk);
+                return true
+            }
+        } else {
+            self.low_usage_frames = 0;
+        }
+
+        false
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn usage(&self) -> BufferUsage {
+        BufferUsage {
+            label: self.label,
+            used: self.len,
+            capacity: self.capacity
+        }
+    }
+
+    fn reallocate(&mut self, device: &wgpu::Device, capacity: u64) {
+        self.buffer = create_buffer(device, self.label, self.usage, capacity);
+        self.capacity = capacity;
+        self.low_usage_frames = 0;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BufferUsage {
+    pub label: &'static str,
+    pub used: u64,
+    pub capacity: u64
+}
+
+impl Display for BufferUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {:.1}/{:.1}KB ({:.0}%)",
+            self.label,
+            self.used as f32 / 1000.0,
+            self.capacity as f32 / 1000.0,
+            100.0 * self.used as f32 / self.capacity as f32
+        )
+    }
+}
+
+fn write_padded(queue: &wgpu::Queue, buffer: &wgpu::Buffer, offset: u64, data: &[u8]) {
+    if data.is_empty() {
+        return
+    }
+
+    if (data.len() as u64).is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT) {
+        queue.write_buffer(buffer, offset, data);
+    } else {
+        // writes must be a multiple of 4 bytes long, so pad with zeroes
+        let mut padded = data.to_vec();
+        padded.resize(align(data.len() as u64) as usize, 0);
+        queue.write_buffer(buffer, offset, &padded);
+    }
+}
+
+fn create_buffer(device: &wgpu::Device, label: &'static str, usage: wgpu::BufferUsages, size: u64) -> wgpu::Buffer {
+    device.create_buffer(
+        &wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            mapped_at_creation: false,
+            usage: usage | wgpu::BufferUsages::COPY_DST
+        }
+    )
+}
+
+// round up to the next multiple of the copy alignment
+fn align(size: u64) -> u64 {
+    let a = wgpu::COPY_BUFFER_ALIGNMENT;
+    size.div_ceil(a).max(1) * a
+}
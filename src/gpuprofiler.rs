@@ -0,0 +1,256 @@
+use std::{collections::VecDeque, fmt::Display, sync::{atomic::{AtomicU8, Ordering}, Arc}, time::{Duration, Instant}};
+
+use log::{debug, info};
+
+use crate::config::{PROFILER_LOG_INTERVAL, PROFILER_READBACKS, PROFILER_WINDOW};
+
+// Each span gets a timestamp written at the start and end of its render pass. They're copied
+// into a buffer that's read a frame or two later once it's mapped, so nothing waits on the GPU,
+// and frames are just left untimed if every buffer is still on its way back.
+// Without timestamp queries, the CPU time spent recording each pass is measured instead,
+// which says much less, but keeps the numbers coming.
+
+/// The parts of a frame that are timed, each its own render pass
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Span {
+    Pass3D,
+    Pass2D,
+    Resolve // from the multisampled framebuffer to the surface
+}
+
+const SPANS: usize = 3;
+const QUERIES: u32 = SPANS as u32 * 2; // a start and an end for each
+const RESULTS_SIZE: u64 = QUERIES as u64 * wgpu::QUERY_SIZE as u64;
+
+// a readback buffer's state, shared with its map callback
+const FREE: u8 = 0;
+const PENDING: u8 = 1;
+const MAPPED: u8 = 2;
+const FAILED: u8 = 3;
+
+/// Where timings came from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimingSource {
+    Gpu,
+    #[default]
+    Cpu // recording the commands, when the adapter can't do timestamps
+}
+
+/// How long each part of a frame took
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpanTimings {
+    pub pass_3d: Duration,
+    pub pass_2d: Duration,
+    pub resolve: Duration
+}
+
+impl SpanTimings {
+    fn from_array(a: [Duration; SPANS]) -> Self {
+        Self { pass_3d: a[0], pass_2d: a[1], resolve: a[2] }
+    }
+}
+
+impl Display for SpanTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ms = |d: Duration| d.as_secs_f32() * 1000.0;
+        write!(f, "3D {:.2}ms, 2D {:.2}ms, resolve {:.2}ms", ms(self.pass_3d), ms(self.pass_2d), ms(self.resolve))
+    }
+}
+
+struct Readback {
+    buffer: wgpu::Buffer,
+    state: Arc<AtomicU8>
+}
+
+struct Timestamps {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readbacks: Vec<Readback>,
+    period: f32, // nanoseconds per tick
+    current: Option<usize> // the readback this frame goes to, None if they're all busy
+}
+
+/// Times each render pass, averaged over the last PROFILER_WINDOW frames
+pub struct Profiler {
+    timestamps: Option<Timestamps>, // None without timestamp queries
+    cpu_started: [Option<Instant>; SPANS],
+    cpu_times: [Duration; SPANS],
+    window: VecDeque<[Duration; SPANS]>,
+    since_log: u32 // frames
+}
+
+impl Profiler {
+    /// Uses timestamps if `device` was created with them
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let timestamps = if device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            info!("Timing render passes with GPU timestamps");
+            Some(Timestamps {
+                query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("Profiler queries"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count: QUERIES
+                }),
+                resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Profiler resolve buffer"),
+                    size: RESULTS_SIZE,
+                    usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false
+                }),
+                readbacks: (0..PROFILER_READBACKS).map(|_| Readback {
+                    buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("Profiler readback buffer"),
+                        size: RESULTS_SIZE,
+                        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false
+                    }),
+                    state: Arc::new(AtomicU8::new(FREE))
+                }).collect(),
+                period: queue.get_timestamp_period(),
+                current: None
+            })
+        } else {
+            info!("GPU timestamps aren't supported here, timing render passes on the CPU instead");
+            None
+        };
+
+        Self {
+            timestamps,
+            cpu_started: [None; SPANS],
+            cpu_times: [Duration::ZERO; SPANS],
+            window: VecDeque::with_capacity(PROFILER_WINDOW),
+            since_log: 0
+        }
+    }
+
+    pub fn source(&self) -> TimingSource {
+        match self.timestamps {
+            Some(_) => TimingSource::Gpu,
+            None => TimingSource::Cpu
+        }
+    }
+
+    /// The average over the window, None until something has been timed
+    pub fn average(&self) -> Option<SpanTimings> {
+        if self.window.is_empty() {
+            return None
+        }
+        let mut total = [Duration::ZERO; SPANS];
+        for frame in self.window.iter() {
+            for (t, d) in total.iter_mut().zip(frame) {
+                *t += *d;
+            }
+        }
+        Some(SpanTimings::from_array(total.map(|t| t / self.window.len() as u32)))
+    }
+
+    /// Picks up timestamps from earlier frames that have made it back, then gets ready for a new one
+    pub fn begin_frame(&mut self, device: &wgpu::Device) {
+        self.cpu_started = [None; SPANS];
+        self.cpu_times = [Duration::ZERO; SPANS];
+
+        let Some(ts) = &mut self.timestamps else {
+            return
+        };
+        device.poll(wgpu::Maintain::Poll);
+
+        let mut finished = vec![];
+        for r in ts.readbacks.iter() {
+            match r.state.load(Ordering::Acquire) {
+                MAPPED => {
+                    finished.push(read_timings(&r.buffer, ts.period));
+                    r.buffer.unmap();
+                    r.state.store(FREE, Ordering::Release);
+                }
+                // nothing to unmap, the buffer can just be used again
+                FAILED => r.state.store(FREE, Ordering::Release),
+                _ => {}
+            }
+        }
+        ts.current = ts.readbacks.iter().position(|r| r.state.load(Ordering::Acquire) == FREE);
+
+        // a frame whose timestamps went backwards is left out
+        for t in finished.into_iter().flatten() {
+            self.push(t);
+        }
+    }
+
+    /// Where the render pass for `span` should write its timestamps, if it should
+    pub fn pass_writes(&self, span: Span) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let ts = self.timestamps.as_ref()?;
+        ts.current?;
+        let i = span as u32 * 2;
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set: &ts.query_set,
+            beginning_of_pass_write_index: Some(i),
+            end_of_pass_write_index: Some(i + 1)
+        })
+    }
+
+    /// Called just before recording a span's pass
+    pub fn start(&mut self, span: Span) {
+        self.cpu_started[span as usize] = Some(Instant::now());
+    }
+
+    /// Called once a span's pass has been recorded
+    pub fn end(&mut self, span: Span) {
+        if let Some(s) = self.cpu_started[span as usize] {
+            self.cpu_times[span as usize] = s.elapsed();
+        }
+    }
+
+    /// Copies this frame's timestamps somewhere they can be read, after the last pass
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(ts) = &self.timestamps else {
+            return
+        };
+        let Some(i) = ts.current else {
+            return
+        };
+        encoder.resolve_query_set(&ts.query_set, 0..QUERIES, &ts.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&ts.resolve_buffer, 0, &ts.readbacks[i].buffer, 0, RESULTS_SIZE);
+    }
+
+    /// Called once the frame has been submitted, to start its timestamps on their way back
+    pub fn submitted(&mut self) {
+        let Some(ts) = &mut self.timestamps else {
+            self.push(self.cpu_times);
+            return
+        };
+        let Some(i) = ts.current.take() else {
+            return
+        };
+        let r = &ts.readbacks[i];
+        r.state.store(PENDING, Ordering::Release);
+        let state = r.state.clone();
+        r.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            state.store(if result.is_ok() {MAPPED} else {FAILED}, Ordering::Release);
+        });
+    }
+
+    fn push(&mut self, times: [Duration; SPANS]) {
+        if self.window.len() == PROFILER_WINDOW {
+            self.window.pop_front();
+        }
+        self.window.push_back(times);
+
+        self.since_log += 1;
+        if self.since_log >= PROFILER_LOG_INTERVAL {
+            self.since_log = 0;
+            if let Some(avg) = self.average() {
+                debug!("Render passes ({:?} timed) over the last {} frames: {avg}", self.source(), self.window.len());
+            }
+        }
+    }
+}
+
+// the length of each span from a mapped readback buffer, None if any went backwards
+fn read_timings(buffer: &wgpu::Buffer, period: f32) -> Option<[Duration; SPANS]> {
+    let data = buffer.slice(..).get_mapped_range();
+    let ticks: Vec<u64> = data.chunks_exact(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())).collect();
+    let mut out = [Duration::ZERO; SPANS];
+    for (i, o) in out.iter_mut().enumerate() {
+        let t = ticks[i * 2 + 1].checked_sub(ticks[i * 2])?;
+        *o = Duration::from_nanos((t as f64 * period as f64) as u64);
+    }
+    Some(out)
+}
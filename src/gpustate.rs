@@ -1,9 +1,28 @@
 use std::{num::NonZeroU32, sync::Arc, time::{Duration, Instant}};
 
 use log::error;
+use wgpu::util::DeviceExt;
 use winit::window::Window;
 
-use crate::{config::{CAMERA_DISTANCE, CAMERA_FOV, MSAA_COUNT}, d2::{aspectuniform::AspectUniform, texture::tex_from_bytes, tovertind2d::ToVertInd2D, vertex::Vertex2D}, d3::{camera::{Camera, CameraUniform}, tovertind3d::ToVertInd3D, vertex::Vertex3D}};
+use crate::{assets::{load_shader, ShaderId}, gpubuffer::{BufferUsage, GrowableBuffer}, gpuprofiler::{Profiler, Span, SpanTimings, TimingSource}, config::{CAMERA_FOV, MSAA_COUNT}, d2::{aspectuniform::AspectUniform, drawlist::DrawList, texture::{tex_from_source, TextureSource}, vertex::Vertex2D}, d3::{camera::{Camera, CameraUniform}, column::unit_column_mesh, instance::{ColumnInstance, ToInstances3D}, tovertind3d::ToVertInd3D, vertex::Vertex3D}};
+
+/// How long a frame took and how much it drew
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameStats {
+    pub cpu: Duration, // building and submitting the frame
+    pub wait: Duration, // for a surface and to present, which is mostly waiting on the GPU
+    pub pass_3d: PassStats,
+    pub pass_2d: PassStats,
+    pub timings: Option<SpanTimings>, // averaged over the last few frames, None until there are any
+    pub timing_source: TimingSource
+}
+
+/// What one render pass drew, with instances counted as the geometry they expand to
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PassStats {
+    pub vertices: u32,
+    pub indices: u32
+}
 
 pub struct State<'a> {
     surface: wgpu::Surface<'a>,
@@ -14,9 +33,16 @@ pub struct State<'a> {
     pub size: winit::dpi::PhysicalSize<u32>,
     window: Arc<winit::window::Window>,
     clearcol: wgpu::Color,
+    render_pipeline_layout_3d: wgpu::PipelineLayout,
     render_pipeline_3d: wgpu::RenderPipeline,
-    vertex_buffer_3d: wgpu::Buffer,
-    index_buffer_3d: wgpu::Buffer,
+    render_pipeline_3d_instanced: wgpu::RenderPipeline,
+    column_mesh_vertex_buffer: wgpu::Buffer,
+    column_mesh_index_buffer: wgpu::Buffer,
+    column_mesh_vertex_count: u32,
+    column_mesh_index_count: u32,
+    column_instance_buffer: GrowableBuffer,
+    vertex_buffer_3d: GrowableBuffer,
+    index_buffer_3d: GrowableBuffer,
     camera: Camera,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
@@ -24,19 +50,24 @@ pub struct State<'a> {
     depth_texture_3d: wgpu::Texture,
     depth_texture_view_3d: wgpu::TextureView,
     msaa_framebuffer: wgpu::Texture,
+    render_pipeline_layout_2d: wgpu::PipelineLayout,
     render_pipeline_2d: wgpu::RenderPipeline,
-    vertex_buffer_2d: wgpu::Buffer,
-    index_buffer_2d: wgpu::Buffer,
+    vertex_buffer_2d: GrowableBuffer,
+    index_buffer_2d: GrowableBuffer,
     pub aspect_uniform: AspectUniform,
     aspect_bind_group: wgpu::BindGroup,
     aspect_buffer: wgpu::Buffer,
     depth_texture_2d: wgpu::Texture,
     depth_texture_view_2d: wgpu::TextureView,
-    texture_bind_group: wgpu::BindGroup
+    texture_views: Vec<wgpu::TextureView>,
+    texture_sampler: wgpu::Sampler,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    texture_bind_group: wgpu::BindGroup,
+    profiler: Profiler
 }
 
 impl State<'_> {
-    pub async fn new(window: Arc<Window>, tex_arr: Vec<&[u8]>, clearcolf32: [f32;4]) -> Self {
+    pub async fn new(window: Arc<Window>, tex_arr: Vec<TextureSource<'_>>, clearcolf32: [f32;4], camera_distance: f32) -> Self {
         let size = window.inner_size();
 
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
@@ -62,13 +93,14 @@ impl State<'_> {
 
         let (device, queue) = match adapter.request_device(
             &wgpu::DeviceDescriptor {
+                // timestamps are only for profiling, so they're used if they're there
                 required_features: 
                     wgpu::Features::TEXTURE_BINDING_ARRAY
-                    | wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
-                required_limits: {
-                    let mut l = wgpu::Limits::default();
-                    l.max_sampled_textures_per_shader_stage = 32;
-                    l
+                    | wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
+                    | (adapter.features() & wgpu::Features::TIMESTAMP_QUERY),
+                required_limits: wgpu::Limits {
+                    max_sampled_textures_per_shader_stage: 32,
+                    ..Default::default()
                 },
                 label: None
             },
@@ -85,8 +117,7 @@ impl State<'_> {
 
         let surface_format = surface_caps.formats.iter()
             .copied()
-            .filter(|f| f.is_srgb())
-            .next()
+            .find(|f| f.is_srgb())
             .unwrap_or(surface_caps.formats[0]);
 
         let config = wgpu::SurfaceConfiguration {
@@ -105,14 +136,12 @@ impl State<'_> {
         surface.configure(&device, &config);
 
         let mut tex_views: Vec<wgpu::TextureView> = vec![];
-        let mut texes: Vec<wgpu::Texture> = vec![];
 
         for (i,tb) in tex_arr.iter().enumerate() {
-            let len = (**tb).len();
-            if len == 0 {
+            if tb.is_empty() {
                 continue;
             }
-            let (t, v) = match tex_from_bytes(
+            let (_, v) = match tex_from_source(
                 tb,
                 &device,
                 &queue,
@@ -125,7 +154,6 @@ impl State<'_> {
                 }
             };
 
-            texes.push(t);
             tex_views.push(v);
         }
 
@@ -135,7 +163,8 @@ impl State<'_> {
                 address_mode_v: wgpu::AddressMode::ClampToEdge,
                 address_mode_w: wgpu::AddressMode::ClampToEdge,
                 mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Nearest,
+                // linear so that text stays smooth when drawn smaller than it was rasterised
+                min_filter: wgpu::FilterMode::Linear,
                 mipmap_filter: wgpu::FilterMode::Nearest,
                 ..Default::default()
             }
@@ -155,7 +184,7 @@ impl State<'_> {
                             }
                         },
                         count: Some(
-                            NonZeroU32::new(texes.len() as u32).expect("Texture array is of length zero")
+                            NonZeroU32::new(tex_views.len() as u32).expect("Texture array is of length zero")
                         )
                     },
                     wgpu::BindGroupLayoutEntry {
@@ -169,72 +198,30 @@ impl State<'_> {
             }
         );
 
-        let texture_bind_group = device.create_bind_group(
-            &wgpu::BindGroupDescriptor {
-                layout: &texture_bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureViewArray(
-                            &tex_views
-								.iter()
-								.map(|v| v)
-								.collect::<Vec<&wgpu::TextureView>>()
-                        )
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&texture_sampler)
-                    }
-                ],
-                label: Some("Gi Hun sphere bind group")
-            }
-        );
+        let texture_bind_group = create_texture_bind_group(&device, &texture_bind_group_layout, &tex_views, &texture_sampler);
 
-        let shader_3d = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("d3/shader.wgsl").into())
-        });
-
-        let shader_2d = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("d2/shader.wgsl").into())
-        });
-
-        let vertex_buffer_3d = device.create_buffer(
-            &wgpu::BufferDescriptor {
-                label: Some("3D Vertex Buffer"),
-                size: 2_000_000,
-                mapped_at_creation: false,
-                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST
-            }
+        let vertex_buffer_3d = GrowableBuffer::new(
+            &device,
+            "3D Vertex Buffer",
+            wgpu::BufferUsages::VERTEX
         );
 
-        let vertex_buffer_2d = device.create_buffer(
-            &wgpu::BufferDescriptor {
-                label: Some("2D Vertex Buffer"),
-                size: 2_000_000,
-                mapped_at_creation: false,
-                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST
-            }
+        let vertex_buffer_2d = GrowableBuffer::new(
+            &device,
+            "2D Vertex Buffer",
+            wgpu::BufferUsages::VERTEX
         );
 
-        let index_buffer_3d = device.create_buffer(
-            &wgpu::BufferDescriptor {
-                label: Some("3D Index Buffer"),
-                size: 2_000_000,
-                mapped_at_creation: false,
-                usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST
-            }
+        let index_buffer_3d = GrowableBuffer::new(
+            &device,
+            "3D Index Buffer",
+            wgpu::BufferUsages::INDEX
         );
 
-        let index_buffer_2d = device.create_buffer(
-            &wgpu::BufferDescriptor {
-                label: Some("2D Index Buffer"),
-                size: 2_000_000,
-                mapped_at_creation: false,
-                usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST
-            }
+        let index_buffer_2d = GrowableBuffer::new(
+            &device,
+            "2D Index Buffer",
+            wgpu::BufferUsages::INDEX
         );
 
         let depth_tex_desc_3d = wgpu::TextureDescriptor {
@@ -295,11 +282,11 @@ impl State<'_> {
         let msaa_framebuffer = device.create_texture(&msaa_framebuffer_desc);
 
         let camera = Camera {
-            eye: (0.0, CAMERA_DISTANCE, CAMERA_DISTANCE).into(),
+            eye: (0.0, camera_distance, camera_distance).into(),
             target: (0.0, 0.0, 0.0).into(),
             up: cgmath::Vector3::unit_y(),
             aspect: config.width as f32 / config.height as f32,
-            fovy: CAMERA_FOV,
+            fovy: fovy_for_aspect(config.width as f32 / config.height as f32),
             znear: 0.1,
             zfar: 100.0
         };
@@ -401,51 +388,35 @@ impl State<'_> {
             }
         );
 
-        let render_pipeline_3d = device.create_render_pipeline(
-            &wgpu::RenderPipelineDescriptor {
-                label: Some("3D Render Pipeline"),
-                layout: Some(&render_pipeline_layout_3d),
-                vertex: wgpu:: VertexState {
-                    module: &shader_3d,
-                    entry_point: "vs_main",
-                    buffers: &[
-                        Vertex3D::desc(),
-                    ]
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader_3d,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: config.format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })]
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Cw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false
-                },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Less,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default()
-                }),
-                multisample: wgpu::MultisampleState {
-                    count: MSAA_COUNT,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None
+        let (render_pipeline_3d, render_pipeline_3d_instanced) = build_with_fallback(
+            ShaderId::D3,
+            |source| create_render_pipelines_3d(&device, &render_pipeline_layout_3d, source, config.format)
+        );
+
+        let (column_mesh_verts, column_mesh_inds) = unit_column_mesh();
+
+        let column_mesh_vertex_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Column Mesh Vertex Buffer"),
+                contents: bytemuck::cast_slice(&column_mesh_verts),
+                usage: wgpu::BufferUsages::VERTEX
             }
         );
 
+        let column_mesh_index_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Column Mesh Index Buffer"),
+                contents: bytemuck::cast_slice(&column_mesh_inds),
+                usage: wgpu::BufferUsages::INDEX
+            }
+        );
+
+        let column_instance_buffer = GrowableBuffer::new(
+            &device,
+            "Column Instance Buffer",
+            wgpu::BufferUsages::VERTEX
+        );
+
         let render_pipeline_layout_2d = 
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("2D Render Pipeline Layout"),
@@ -457,50 +428,9 @@ impl State<'_> {
             }
         );
 
-        let render_pipeline_2d = device.create_render_pipeline(
-            &wgpu::RenderPipelineDescriptor {
-                label: Some("2D Render Pipeline"),
-                layout: Some(&render_pipeline_layout_2d),
-                vertex: wgpu:: VertexState {
-                    module: &shader_2d,
-                    entry_point: "vs_main",
-                    buffers: &[
-                        Vertex2D::desc(),
-                    ]
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader_2d,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: config.format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })]
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Cw,
-                    // cull_mode: Some(wgpu::Face::Back),
-                    cull_mode: None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false
-                },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Less,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default()
-                }),
-                multisample: wgpu::MultisampleState {
-                    count: MSAA_COUNT,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None
-            }
+        let render_pipeline_2d = build_with_fallback(
+            ShaderId::D2,
+            |source| create_render_pipeline_2d(&device, &render_pipeline_layout_2d, source, config.format)
         );
 
         let clearcol = wgpu::Color {
@@ -510,6 +440,8 @@ impl State<'_> {
             a: clearcolf32[3] as f64,
         };
 
+        let profiler = Profiler::new(&device, &queue);
+
         Self {
             window,
             surface,
@@ -519,7 +451,14 @@ impl State<'_> {
             config,
             size,
             clearcol,
+            render_pipeline_layout_3d,
             render_pipeline_3d,
+            render_pipeline_3d_instanced,
+            column_mesh_vertex_buffer,
+            column_mesh_index_buffer,
+            column_mesh_vertex_count: column_mesh_verts.len() as u32,
+            column_mesh_index_count: column_mesh_inds.len() as u32,
+            column_instance_buffer,
             vertex_buffer_3d,
             index_buffer_3d,
             camera,
@@ -531,13 +470,18 @@ impl State<'_> {
             msaa_framebuffer,
             index_buffer_2d,
             vertex_buffer_2d,
+            render_pipeline_layout_2d,
             render_pipeline_2d,
             aspect_uniform,
             aspect_bind_group,
             aspect_buffer,
             depth_texture_view_2d,
             depth_texture_2d,
-            texture_bind_group
+            texture_views: tex_views,
+            texture_sampler,
+            texture_bind_group_layout,
+            texture_bind_group,
+            profiler
         }
     }
 
@@ -555,6 +499,7 @@ impl State<'_> {
             let aspect = self.config.width as f32 / self.config.height as f32;
 
             self.camera.aspect = aspect;
+            self.camera.fovy = fovy_for_aspect(aspect);
 
             self.aspect_uniform.update_aspect(aspect);
 
@@ -622,11 +567,54 @@ impl State<'_> {
         self.resize(self.size)
     }
 
+    /// Moves the camera to frame a grid drawn `d` away
+    pub fn set_camera_distance(&mut self, d: f32) {
+        self.camera.eye = (0.0, d, d).into();
+    }
+
     pub fn update(&mut self) {
         self.camera_uniform.update_view_proj(&self.camera);
         self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]))
     }
 
+    pub fn buffer_usage(&self) -> [BufferUsage;5] {
+        [
+            self.vertex_buffer_3d.usage(),
+            self.index_buffer_3d.usage(),
+            self.column_instance_buffer.usage(),
+            self.vertex_buffer_2d.usage(),
+            self.index_buffer_2d.usage(),
+        ]
+    }
+
+    /// Swaps a texture for a new version of it, after its file changes
+    #[cfg_attr(not(debug_assertions), allow(dead_code))] // only hot reloading replaces textures
+    pub fn reload_texture(&mut self, index: u32, source: &TextureSource) -> Result<(), image::ImageError> {
+        let (_, view) = tex_from_source(source, &self.device, &self.queue)?;
+        match self.texture_views.get_mut(index as usize) {
+            Some(v) => *v = view,
+            None => error!("Tried to reload texture {index}, which doesn't exist"),
+        }
+        self.texture_bind_group = create_texture_bind_group(&self.device, &self.texture_bind_group_layout, &self.texture_views, &self.texture_sampler);
+        Ok(())
+    }
+
+    /// Rebuilds the pipelines that use a shader from new source,
+    /// keeping the old ones if it doesn't compile
+    #[cfg_attr(not(debug_assertions), allow(dead_code))] // only hot reloading replaces shaders
+    pub fn reload_shader(&mut self, id: ShaderId, source: &str) -> Result<(), wgpu::Error> {
+        match id {
+            ShaderId::D3 => {
+                (self.render_pipeline_3d, self.render_pipeline_3d_instanced) =
+                    create_render_pipelines_3d(&self.device, &self.render_pipeline_layout_3d, source, self.config.format)?;
+            }
+            ShaderId::D2 => {
+                self.render_pipeline_2d = create_render_pipeline_2d(&self.device, &self.render_pipeline_layout_2d, source, self.config.format)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn set_bg_col(&mut self, col: [f32;4]) {
         let clearcol = wgpu::Color {
             r: col[0] as f64,
@@ -637,12 +625,14 @@ impl State<'_> {
         self.clearcol = clearcol;
     }
 
-    pub fn render<T, U>(&mut self, d3_geom: T, d2_geom: U) -> Result<Duration, wgpu::SurfaceError>
+    pub fn render<T, I>(&mut self, d3_geom: T, d3_instances: I, d2_geom: &mut DrawList) -> Result<FrameStats, wgpu::SurfaceError>
     where
         T: ToVertInd3D,
-        U: ToVertInd2D
+        I: ToInstances3D
     {
+        let acquire_start = Instant::now();
         let surface_output = self.surface.get_current_texture()?;
+        let acquire_time = acquire_start.elapsed();
         let surface_view = surface_output.texture.create_view(&wgpu::TextureViewDescriptor::default());
 
         let start = Instant::now();
@@ -653,39 +643,61 @@ impl State<'_> {
         let (vert3d, ind3d) = d3_geom.to_vert_ind();
         let num_indices3d = ind3d.len() as u32;
 
-        let (vert2d, ind2d) = d2_geom.to_vert_ind();
-        let num_indices2d = ind2d.len() as u32;
+        let instances3d = d3_instances.to_instances();
+        let num_instances3d = instances3d.len() as u32;
 
-        self.queue.write_buffer(
-            &self.vertex_buffer_3d,
-            0,
+        let num_indices2d = d2_geom.indices().len() as u32;
+
+        let stats_3d = PassStats {
+            vertices: vert3d.len() as u32 + self.column_mesh_vertex_count * num_instances3d,
+            indices: num_indices3d + self.column_mesh_index_count * num_instances3d
+        };
+        let stats_2d = PassStats {
+            vertices: d2_geom.vertices().len() as u32,
+            indices: num_indices2d
+        };
+
+        self.vertex_buffer_3d.write(
+            &self.device,
+            &self.queue,
             bytemuck::cast_slice(&vert3d)
         );
 
-        self.queue.write_buffer(
-            &self.index_buffer_3d,
-            0,
+        self.index_buffer_3d.write(
+            &self.device,
+            &self.queue,
             bytemuck::cast_slice(&ind3d)
         );
 
+        self.column_instance_buffer.write(
+            &self.device,
+            &self.queue,
+            bytemuck::cast_slice(&instances3d)
+        );
+
         self.queue.write_buffer(
             &self.camera_buffer,
             0,
             bytemuck::cast_slice(&[self.camera_uniform])
         );
 
-        self.queue.write_buffer(
-            &self.vertex_buffer_2d,
-            0,
-            bytemuck::cast_slice(&vert2d)
+        // only the parts of the 2D geometry that changed get uploaded
+        self.vertex_buffer_2d.write_ranges(
+            &self.device,
+            &self.queue,
+            d2_geom.vertices(),
+            d2_geom.dirty_vertices()
         );
 
-        self.queue.write_buffer(
-            &self.index_buffer_2d,
-            0,
-            bytemuck::cast_slice(&ind2d)
+        self.index_buffer_2d.write_ranges(
+            &self.device,
+            &self.queue,
+            d2_geom.indices(),
+            d2_geom.dirty_indices()
         );
 
+        d2_geom.clear_dirty();
+
         self.queue.write_buffer(
             &self.aspect_buffer,
             0,
@@ -698,6 +710,10 @@ impl State<'_> {
             }
         );
 
+        self.profiler.begin_frame(&self.device);
+
+        // both passes draw into the multisampled framebuffer, which is resolved to the surface once at the end
+        self.profiler.start(Span::Pass3D);
         {
             let mut render_pass3d = encoder.begin_render_pass(
                 &wgpu::RenderPassDescriptor {
@@ -711,10 +727,10 @@ impl State<'_> {
                         stencil_ops: None
                     }),
                     occlusion_query_set: None,
-                    timestamp_writes: None,
+                    timestamp_writes: self.profiler.pass_writes(Span::Pass3D),
                     color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                         view: &view,
-                        resolve_target: Some(&surface_view),
+                        resolve_target: None,
                         ops: wgpu::Operations {
                             load: wgpu::LoadOp::Clear(self.clearcol),
                             store: wgpu::StoreOp::Store
@@ -725,11 +741,21 @@ impl State<'_> {
 
             render_pass3d.set_pipeline(&self.render_pipeline_3d);
             render_pass3d.set_bind_group(0, &self.camera_bind_group, &[]);
-            render_pass3d.set_vertex_buffer(0, self.vertex_buffer_3d.slice(..));
-            render_pass3d.set_index_buffer(self.index_buffer_3d.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass3d.set_vertex_buffer(0, self.vertex_buffer_3d.buffer().slice(..));
+            render_pass3d.set_index_buffer(self.index_buffer_3d.buffer().slice(..), wgpu::IndexFormat::Uint32);
             render_pass3d.draw_indexed(0..num_indices3d, 0, 0..1);
-            
+
+            if num_instances3d > 0 {
+                render_pass3d.set_pipeline(&self.render_pipeline_3d_instanced);
+                render_pass3d.set_vertex_buffer(0, self.column_mesh_vertex_buffer.slice(..));
+                render_pass3d.set_vertex_buffer(1, self.column_instance_buffer.buffer().slice(..));
+                render_pass3d.set_index_buffer(self.column_mesh_index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass3d.draw_indexed(0..self.column_mesh_index_count, 0, 0..num_instances3d);
+            }
         }
+        self.profiler.end(Span::Pass3D);
+
+        self.profiler.start(Span::Pass2D);
         {
             let mut render_pass2d = encoder.begin_render_pass(
                 &wgpu::RenderPassDescriptor {
@@ -743,10 +769,10 @@ impl State<'_> {
                         stencil_ops: None
                     }),
                     occlusion_query_set: None,
-                    timestamp_writes: None,
+                    timestamp_writes: self.profiler.pass_writes(Span::Pass2D),
                     color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                         view: &view,
-                        resolve_target: Some(&surface_view),
+                        resolve_target: None,
                         ops: wgpu::Operations {
                             load: wgpu::LoadOp::Load,
                             store: wgpu::StoreOp::Store
@@ -758,43 +784,260 @@ impl State<'_> {
             render_pass2d.set_pipeline(&self.render_pipeline_2d);
             render_pass2d.set_bind_group(0, &self.aspect_bind_group, &[]);
             render_pass2d.set_bind_group(1, &self.texture_bind_group, &[]);
-            render_pass2d.set_vertex_buffer(0, self.vertex_buffer_2d.slice(..));
-            render_pass2d.set_index_buffer(self.index_buffer_2d.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass2d.set_vertex_buffer(0, self.vertex_buffer_2d.buffer().slice(..));
+            render_pass2d.set_index_buffer(self.index_buffer_2d.buffer().slice(..), wgpu::IndexFormat::Uint32);
             render_pass2d.draw_indexed(0..num_indices2d, 0, 0..1);
-
         }
+        self.profiler.end(Span::Pass2D);
+
+        // draws nothing, the resolve happens as the pass ends
+        self.profiler.start(Span::Resolve);
+        encoder.begin_render_pass(
+            &wgpu::RenderPassDescriptor {
+                label: Some("Resolve pass"),
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: self.profiler.pass_writes(Span::Resolve),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: Some(&surface_view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        // the next frame clears it anyway
+                        store: wgpu::StoreOp::Discard
+                    }
+                })]
+            }
+        );
+        self.profiler.end(Span::Resolve);
+        self.profiler.resolve(&mut encoder);
 
         self.queue.submit(std::iter::once(encoder.finish()));
+        self.profiler.submitted();
+        let cpu = start.elapsed();
 
+        let present_start = Instant::now();
         surface_output.present();
 
-        let t = Instant::now().duration_since(start);
-
-        Ok(t)
-
+        Ok(FrameStats {
+            cpu,
+            wait: acquire_time + present_start.elapsed(),
+            pass_3d: stats_3d,
+            pass_2d: stats_2d,
+            timings: self.profiler.average(),
+            timing_source: self.profiler.source()
+        })
     }
 }
 
+// compiles a shader and builds whatever uses it, returning any errors
+// rather than leaving them to wgpu, which would panic
+fn with_shader<T>(device: &wgpu::Device, label: &str, source: &str, build: impl FnOnce(&wgpu::ShaderModule) -> T) -> Result<T, wgpu::Error> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::ShaderSource::Wgsl(source.into())
+    });
+    let built = build(&shader);
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(e) => Err(e),
+        None => Ok(built)
+    }
+}
 
+// builds with the shader's file, or the built in copy if that doesn't compile
+fn build_with_fallback<T>(id: ShaderId, build: impl Fn(&str) -> Result<T, wgpu::Error>) -> T {
+    let asset = id.asset();
+    let source = load_shader(asset);
+    match build(&source) {
+        Ok(t) => t,
+        Err(e) => {
+            error!("Failed to build {}, using the built in copy: {e}", asset.file);
+            match build(asset.source) {
+                Ok(t) => t,
+                Err(e) => {
+                    error!("The built in {} doesn't build either: {e}", asset.file);
+                    panic!()
+                }
+            }
+        }
+    }
+}
 
+// (plain, instanced)
+fn create_render_pipelines_3d(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    source: &str,
+    format: wgpu::TextureFormat
+) -> Result<(wgpu::RenderPipeline, wgpu::RenderPipeline), wgpu::Error> {
+    with_shader(device, "3D Shader", source, |shader| (
+        create_render_pipeline_3d(
+            device,
+            layout,
+            shader,
+            format,
+            "3D Render Pipeline",
+            "vs_main",
+            &[
+                Vertex3D::desc(),
+            ]
+        ),
+        create_render_pipeline_3d(
+            device,
+            layout,
+            shader,
+            format,
+            "3D Instanced Render Pipeline",
+            "vs_instanced",
+            &[
+                Vertex3D::desc(),
+                ColumnInstance::desc(),
+            ]
+        )
+    ))
+}
 
+fn create_render_pipeline_2d(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    source: &str,
+    format: wgpu::TextureFormat
+) -> Result<wgpu::RenderPipeline, wgpu::Error> {
+    with_shader(device, "2D Shader", source, |shader| {
+        device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: Some("2D Render Pipeline"),
+                layout: Some(layout),
+                vertex: wgpu:: VertexState {
+                    module: shader,
+                    entry_point: "vs_main",
+                    buffers: &[
+                        Vertex2D::desc(),
+                    ]
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })]
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Cw,
+                    // cull_mode: Some(wgpu::Face::Back),
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default()
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: MSAA_COUNT,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None
+            }
+        )
+    })
+}
 
+fn create_texture_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    views: &[wgpu::TextureView],
+    sampler: &wgpu::Sampler
+) -> wgpu::BindGroup {
+    device.create_bind_group(
+        &wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureViewArray(
+                        &views.iter().collect::<Vec<&wgpu::TextureView>>()
+                    )
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler)
+                }
+            ],
+            label: Some("Texture bind group")
+        }
+    )
+}
 
+fn create_render_pipeline_3d(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    label: &str,
+    vs_entry_point: &str,
+    buffers: &[wgpu::VertexBufferLayout]
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(
+        &wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
+            vertex: wgpu:: VertexState {
+                module: shader,
+                entry_point: vs_entry_point,
+                buffers
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })]
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default()
+            }),
+            multisample: wgpu::MultisampleState {
+                count: MSAA_COUNT,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None
+        }
+    )
+}
 
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
+// in portrait windows, widen the vertical fov so the horizontal fov never gets
+// narrower than it would be in a square window
+fn fovy_for_aspect(aspect: f32) -> f32 {
+    if aspect >= 1.0 {
+        return CAMERA_FOV
+    }
+    let half = (CAMERA_FOV.to_radians() * 0.5).tan() / aspect;
+    (2.0 * half.atan()).to_degrees()
+}
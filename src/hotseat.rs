@@ -0,0 +1,181 @@
+use winit::keyboard::Key;
+
+use crate::{config::*, d2::{layout::Layout, number::{Number, NumberFormat}, retained::Retained, shape::Shape, text::Align}, scenes::{Frame, Shared}, ui::{label::Label, Widget}};
+
+// Two players race on the same puzzle. The first right answer takes the round, a wrong
+// answer locks that player out until the next one, and the first to HOT_SEAT_WIN_SCORE
+// rounds takes the match. The round itself (sliding in and out, the timer) is GameState's.
+
+/// The player and answer a key gives, None if it isn't one of the answer keys
+pub fn key_answer(key: &Key) -> Option<(usize, usize)> {
+    let Key::Character(c) = key else {
+        return None
+    };
+    HOT_SEAT_KEYS.iter().enumerate().find_map(|(player, keys)| {
+        keys.iter().position(|k| *k == c.as_str()).map(|answer| (player, answer))
+    })
+}
+
+struct Player {
+    score: u32, // rounds won
+    streak: u32, // rounds won in a row
+    best_streak: u32,
+    locked_out: bool, // answered wrong this round
+    score_graphic: Retained<Number>,
+    streak_graphic: Retained<Number>,
+    name: Label
+}
+
+impl Player {
+    fn new(index: usize, shared: &Shared) -> Self {
+        let layout = &shared.layout;
+        // the first player's numbers hang off the left edge, the second's off the right
+        let align = if index == 0 {Align::Left} else {Align::Right};
+        let mut player = Self {
+            score: 0,
+            streak: 0,
+            best_streak: 0,
+            locked_out: false,
+            score_graphic: Retained::new(Number::new(0.0, NumberFormat::Integer, layout.resolve(HOT_SEAT_SCORE_ANCHORS[index]), 0.0, 0, 0.8, 0.15, 0.3, 0.0, align)),
+            streak_graphic: Retained::new(Number::new(0.0, NumberFormat::Integer, layout.resolve(HOT_SEAT_STREAK_ANCHORS[index]), 0.0, 0, 0.5, 0.075, 0.15, 0.0, align)),
+            name: Label::new("", HOT_SEAT_NAME_ANCHORS[index], MENU_LABEL_SIZE, 2, shared.font.clone(), layout)
+        };
+        player.show_name(index);
+        player
+    }
+
+    fn show_name(&mut self, index: usize) {
+        let keys = HOT_SEAT_KEYS[index];
+        let mouse = if index == HOT_SEAT_MOUSE_PLAYER {" or mouse"} else {""};
+        self.name.set_text(&match self.locked_out {
+            true => format!("Player {} locked out", index + 1),
+            false => format!("Player {}: {}-{}{mouse}", index + 1, keys[0], keys[NET_COUNT - 1])
+        });
+    }
+
+    fn set_streak(&mut self, streak: u32) {
+        self.streak = streak;
+        self.best_streak = self.best_streak.max(streak);
+        self.streak_graphic.roll_to(streak as f32, NUMBER_ROLL_LEN);
+    }
+}
+
+/// How an answer went for the round
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RoundResult {
+    Ignored, // from a player already locked out
+    Won,
+    LockedOut,
+    AllLockedOut // nobody is left to answer, so the round is over
+}
+
+/// Both players' scores and streaks, and the HUD that shows them
+pub struct HotSeat {
+    players: [Player; 2]
+}
+
+impl HotSeat {
+    pub fn new(shared: &Shared) -> Self {
+        Self {
+            players: [Player::new(0, shared), Player::new(1, shared)]
+        }
+    }
+
+    pub fn score(&self, player: usize) -> u32 {
+        self.players[player].score
+    }
+
+    pub fn streak(&self, player: usize) -> u32 {
+        self.players[player].streak
+    }
+
+    /// The longest streak going, for the music
+    pub fn top_streak(&self) -> u32 {
+        self.players.iter().map(|p| p.streak).max().unwrap_or(0)
+    }
+
+    /// The player who has taken the match, if either has
+    pub fn winner(&self) -> Option<usize> {
+        self.players.iter().position(|p| p.score >= HOT_SEAT_WIN_SCORE)
+    }
+
+    /// Counts `player`'s answer, `correct` being whether it was the right one
+    pub fn answer(&mut self, player: usize, correct: bool) -> RoundResult {
+        if self.players[player].locked_out {
+            return RoundResult::Ignored
+        }
+        if correct {
+            for (i, p) in self.players.iter_mut().enumerate() {
+                if i == player {
+                    p.score += 1;
+                    p.score_graphic.roll_to(p.score as f32, NUMBER_ROLL_LEN);
+                    p.set_streak(p.streak + 1);
+                } else if p.streak > 0 {
+                    p.set_streak(0);
+                }
+            }
+            return RoundResult::Won
+        }
+
+        let p = &mut self.players[player];
+        p.locked_out = true;
+        p.set_streak(0);
+        p.show_name(player);
+        match self.players.iter().all(|p| p.locked_out) {
+            true => RoundResult::AllLockedOut,
+            false => RoundResult::LockedOut
+        }
+    }
+
+    /// Nobody got it in time, which ends the streak of anyone still answering
+    pub fn time_out(&mut self) {
+        for p in self.players.iter_mut().filter(|p| !p.locked_out && p.streak > 0) {
+            p.set_streak(0);
+        }
+    }
+
+    /// Lets everyone answer again, for a new puzzle
+    pub fn start_round(&mut self) {
+        for (i, p) in self.players.iter_mut().enumerate() {
+            if p.locked_out {
+                p.locked_out = false;
+                p.show_name(i);
+            }
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for p in self.players.iter_mut() {
+            for n in [&mut p.score_graphic, &mut p.streak_graphic] {
+                if n.is_rolling() {
+                    n.update(dt);
+                }
+            }
+        }
+    }
+
+    pub fn debug_info(&self, out: &mut Vec<String>) {
+        for (i, p) in self.players.iter().enumerate() {
+            out.push(format!(
+                "Player {}: {} of {HOT_SEAT_WIN_SCORE}, streak {}, best {}{}",
+                i + 1, p.score, p.streak, p.best_streak, if p.locked_out {", locked out"} else {""}
+            ));
+        }
+    }
+
+    pub fn render<'f>(&'f mut self, frame: &mut Frame<'f>) {
+        for p in self.players.iter_mut() {
+            frame.shapes.push(&mut p.score_graphic);
+            frame.shapes.push(&mut p.streak_graphic);
+            p.name.render(frame);
+        }
+    }
+
+    pub fn relayout(&mut self, layout: &Layout) {
+        for (i, p) in self.players.iter_mut().enumerate() {
+            p.score_graphic.set_pos(layout.resolve(HOT_SEAT_SCORE_ANCHORS[i]));
+            p.streak_graphic.set_pos(layout.resolve(HOT_SEAT_STREAK_ANCHORS[i]));
+            p.name.relayout(layout);
+        }
+    }
+}
@@ -1,83 +1,136 @@
-use std::{process::exit, sync::Arc, time::Instant};
+use std::{process::exit, sync::Arc, time::Duration};
 
+use clock::{Clock, FixedTimestep, SteppedClock, SystemClock};
+
+use configfile::{ConfigError, GameConfig};
+use d2::{font::FontAtlas, texture::TextureSource};
 use rodio::{OutputStream, OutputStreamHandle};
-use winit::{dpi::PhysicalSize, event::{ElementState, Event, WindowEvent}, event_loop::EventLoop, keyboard::Key, window::WindowBuilder};
+use winit::{dpi::PhysicalSize, event::{ElementState, Event, WindowEvent}, event_loop::EventLoop, keyboard::Key, window::{Fullscreen, WindowBuilder}};
 
 mod gpustate;
+mod gpubuffer;
+mod gpuprofiler;
 mod d3;
 mod d2;
 mod game;
+mod app;
+mod scenes;
 mod config;
+mod configfile;
+mod savegame;
 mod mathsutils;
 mod soundmanager;
+mod assets;
+#[cfg(debug_assertions)]
+mod assetwatcher;
+mod audio;
+mod events;
+mod analytics;
+mod console;
+mod debugoverlay;
+mod hotseat;
+mod music;
+mod synth;
+mod clock;
+mod tween;
+mod ui;
 
 fn main() {
+    env_logger::init();
+
+    let config = match GameConfig::load(std::env::args().skip(1)) {
+        Ok(c) => c,
+        Err(ConfigError::Help) => {
+            println!("{}", ConfigError::Help);
+            return
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            exit(2);
+        }
+    };
     println!("Deleting System32...");
 
-    pollster::block_on(run());
+    pollster::block_on(run(config));
 }
 
-pub async fn run() {
+pub async fn run(config: GameConfig) {
 
     let ev_loop = EventLoop::new().unwrap();
     let window = Arc::new(
         WindowBuilder::new()
             .with_title("Cube Game")
-            .with_inner_size(PhysicalSize::new(config::WINDOW_WIDTH, config::WINDOW_HEIGHT))
-            .with_resizable(false)
+            .with_inner_size(PhysicalSize::new(config.window_width, config.window_height))
+            .with_fullscreen(config.fullscreen.then_some(Fullscreen::Borderless(None)))
+            .with_min_inner_size(PhysicalSize::new(config::WINDOW_MIN_WIDTH, config::WINDOW_MIN_HEIGHT))
+            .with_resizable(true)
             .build(&ev_loop)
             .unwrap()
     );
 
-    let textures: Vec<&[u8]> = vec![
-        include_bytes!("res/black.png"),
-        include_bytes!("res/roundedblackbox.png"),
-        include_bytes!("res/gen/0.png"),
-        include_bytes!("res/gen/1.png"),
-        include_bytes!("res/gen/2.png"),
-        include_bytes!("res/gen/3.png"),
-        include_bytes!("res/gen/4.png"),
-        include_bytes!("res/gen/5.png"),
-        include_bytes!("res/gen/6.png"),
-        include_bytes!("res/gen/7.png"),
-        include_bytes!("res/gen/8.png"),
-        include_bytes!("res/gen/9.png"),
-    ];
-
-    // (data, volume)
-    let sounds: Vec<( &[u8] , f32 )> = vec![
-        ( include_bytes!("res/click.wav"),  0.1 ),
-        ( include_bytes!("res/click2.wav"), 0.3 ),
-        ( include_bytes!("res/dingup.wav"), 0.1 ),
-        ( include_bytes!("res/dinglow.wav"), 0.3 ),
-    ];
+    let font = Arc::new(FontAtlas::new(include_bytes!("res/font/Comfortaa.ttf"))
+        .unwrap_or_else(|e| panic!("Failed to load the bundled font: {e}")));
+
+    // textures from files come first, their indices being where they are in the array
+    let mut textures: Vec<TextureSource> = assets::TEXTURES.iter().map(assets::load_texture).collect();
+    debug_assert!(assets::TEXTURES.iter().enumerate().all(|(i, t)| t.index == i as u32));
+    debug_assert_eq!(textures.len() as u32, config::NUMBER_TEX_INDEX_START);
+    for c in ('0'..='9').chain(config::NUMBER_SYMBOLS.chars()) {
+        let img = font.render_number_glyph(c)
+            .unwrap_or_else(|e| panic!("Failed to generate the texture for '{c}' from the bundled font: {e}"));
+        textures.push(TextureSource::Rgba(img));
+    }
+    debug_assert_eq!(textures.len() as u32, config::FONT_TEX_INDEX);
+    textures.push(TextureSource::Rgba(font.image().clone()));
 
     let (_stream, sh) : (Option<OutputStream>, Option<OutputStreamHandle>) = match rodio::OutputStream::try_default() {
         Ok(o) => {
             (Some(o.0), Some(o.1))
         }
         Err(_) => {
-            println!("Failed to get default audio device, there will be no audio");
+            log::warn!("Failed to get default audio device, there will be no audio");
             (None, None)
         }
     };
 
-    let mut since_last_ftime_readout = 0u128;
-    let ftime_readout_delta = 120u128;
-    let mut render_ftime = false;
-    let mut frametimes: [f64;240] = [0.0;240];
+    // setting this makes every frame advance the game by the same amount,
+    // however long it actually took, so runs can be reproduced exactly
+    let fixed_frame = std::env::var("CUBE_GAME_FIXED_FRAME_MS").ok().map(|v| {
+        // try_from turns away negative, infinite and NaN times
+        v.parse::<f64>().ok()
+            .and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok())
+            .filter(|dt| !dt.is_zero())
+            .ok_or(v)
+    });
+    let mut clock: Box<dyn Clock> = match fixed_frame {
+        Some(Ok(dt)) => {
+            log::info!("Using a fixed frame time of {dt:?}");
+            Box::new(SteppedClock::new(dt))
+        }
+        Some(Err(v)) => {
+            log::warn!("CUBE_GAME_FIXED_FRAME_MS should be a number of milliseconds above 0, not {v:?}, using the real frame time");
+            Box::new(SystemClock::new())
+        }
+        None => Box::new(SystemClock::new())
+    };
+    let mut timestep = FixedTimestep::new(
+        Duration::from_secs_f32(1.0 / config::UPDATE_RATE),
+        config::MAX_UPDATES_PER_FRAME
+    );
 
-    let mut state = game::GameState::new(
+    let mut state = app::App::new(
         window.clone(),
         textures,
-        sounds,
-        sh
+        sh,
+        font,
+        config
     ).await;
 
     ev_loop.run(move |event, _| match event {
         Event::WindowEvent { window_id, event } if window_id == state.window().id() => {
             match event {
                 WindowEvent::CloseRequested => {
+                    state.close();
                     exit(0);
                 }
                 WindowEvent::Resized(psize) => {
@@ -87,39 +140,13 @@ pub async fn run() {
                     event: kbevent,
                     ..
                 } => {
-                    if kbevent.logical_key == Key::Named(winit::keyboard::NamedKey::Escape) {
+                    if kbevent.state == ElementState::Pressed
+                        && !state.key_pressed(&kbevent.logical_key, kbevent.repeat) {
                         exit(0);
                     }
-                    if kbevent.logical_key == Key::Named(winit::keyboard::NamedKey::Enter)
-                        && kbevent.state == ElementState::Pressed {
-                    }
-                    match kbevent.logical_key.clone() {
-                        Key::Character(char) if kbevent.state == ElementState::Pressed => {
-                            match char.as_str() {
-                                #[cfg(debug_assertions)]
-                                "d" => {
-                                    println!("Printing net debug info:\n");
-                                    state.print_net_debug();
-                                }
-                                #[cfg(debug_assertions)]
-                                "f" => {
-                                    println!("Toggling frametime debug");
-                                    render_ftime = !render_ftime;
-                                }
-                                // #[cfg(debug_assertions)]
-                                // "g" => {
-                                //     println!("Toggling generation time debug");
-                                //     state.debug_print_gen_time = !state.debug_print_gen_time;
-                                // }
-                                // #[cfg(debug_assertions)]
-                                // "t" => {
-                                //     println!("Toggling timer override");
-                                //     state.toggle_timer_override();
-                                // }
-                                _ => {}
-                            }
-                        }
-                        _ => {}
+                    if kbevent.logical_key == Key::Named(winit::keyboard::NamedKey::F11)
+                        && kbevent.state == ElementState::Pressed && !kbevent.repeat {
+                        state.toggle_fullscreen();
                     }
                 }
                 WindowEvent::CursorMoved { position, .. } => {
@@ -129,46 +156,19 @@ pub async fn run() {
                     state.mouse_click_update(s, b);
                 }
                 WindowEvent::RedrawRequested => {
-                    let start = Instant::now();
-                    if since_last_ftime_readout > ftime_readout_delta {
-                        since_last_ftime_readout = 0;
-                        let mut max: f64 = 0.0;
-                        let mut acc: f64 = 0.0;
-                        for i in 0..frametimes.len() {
-                            acc += frametimes[i];
-                            if frametimes[i] > max {
-                                max = frametimes[i]
-                            }
+                    let steps = timestep.advance(clock.now());
+                    for _ in 0..steps {
+                        if !state.update(timestep.dt()) {
+                            exit(0);
                         }
-                        let avg = acc / frametimes.len() as f64;
-                        if render_ftime {
-                            println!(
-                                "Average frame time: {:.2}ms Peak: {:.2}ms",
-                                avg,
-                                max
-                            );
-                        }
-                    } else {
-                        since_last_ftime_readout += 1;
                     }
-                    state.update();
-
-                    let end = Instant::now();
-
-                    match state.render() {
-                        Ok(d) => {
-                            frametimes.rotate_left(1);
-                            frametimes[239] = {
-                                let t = end.duration_since(start) + d;
-                                t.as_micros() as f64 / 1000.0
-                            };
-                            window.request_redraw()
-                        },
+
+                    match state.render(timestep.alpha()) {
+                        Ok(()) => window.request_redraw(),
                         Err(wgpu::SurfaceError::Lost) => state.refresh_window(),
                         Err(wgpu::SurfaceError::OutOfMemory) => exit(0),
                         Err(e) => eprintln!("{:?}", e)
                     }
-
                 }
                 _ => {}
             }
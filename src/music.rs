@@ -0,0 +1,97 @@
+use crate::{config::*, soundmanager::{Channel, SoundHandle, SoundManager}, synth::{game_stems, menu_stems}};
+
+/// The pieces of music there are, each a loop with a base and an intensity layer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Track {
+    Menu,
+    Game
+}
+
+/// What a scene wants playing, asked for every update so the music follows the game
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MusicCue {
+    pub track: Track,
+    pub intensity: f32 // 0 to 1, how loud the layer over the base is
+}
+
+// the sound indices of a track's stems
+#[derive(Clone, Copy, Debug)]
+struct Stems {
+    base: u32,
+    layer: u32
+}
+
+// a track that's playing, its stems started together so they stay in step
+#[derive(Clone, Copy, Debug)]
+struct Playing {
+    track: Track,
+    base: SoundHandle,
+    layer: SoundHandle,
+    fade_in: f32 // 0 to 1
+}
+
+/// Plays whichever track the scenes ask for, crossfading when that changes,
+/// and turns its intensity layer up and down smoothly
+pub struct Music {
+    menu: Option<Stems>,
+    game: Option<Stems>,
+    current: Option<Playing>,
+    intensity: f32
+}
+
+impl Music {
+    /// Generates the tracks and hands their stems to the sound manager
+    pub fn new(sm: &mut SoundManager) -> Self {
+        let mut add = |(base, layer): (Vec<f32>, Vec<f32>)| Some(Stems {
+            base: sm.add_samples(base, MUSIC_SAMPLE_RATE, MUSIC_VOLUME, MUSIC_STEM_MAX_CONCURRENT)?,
+            layer: sm.add_samples(layer, MUSIC_SAMPLE_RATE, MUSIC_VOLUME, MUSIC_STEM_MAX_CONCURRENT)?
+        });
+        Self {
+            menu: add(menu_stems()),
+            game: add(game_stems()),
+            current: None,
+            intensity: 0.0
+        }
+    }
+
+    fn stems(&self, track: Track) -> Option<Stems> {
+        match track {
+            Track::Menu => self.menu,
+            Track::Game => self.game
+        }
+    }
+
+    /// Moves towards the cue by `dt` seconds, None fading the music out altogether
+    pub fn update(&mut self, dt: f32, cue: Option<MusicCue>, sm: &mut SoundManager) {
+        let wanted = cue.map(|c| c.track);
+        if self.current.map(|p| p.track) != wanted {
+            // the old track fades out by itself while the new one fades in
+            if let Some(old) = self.current.take() {
+                sm.fade(old.base, 0.0, MUSIC_CROSSFADE_LEN, true);
+                sm.fade(old.layer, 0.0, MUSIC_CROSSFADE_LEN, true);
+            }
+            self.current = wanted.and_then(|t| self.start(t, sm));
+        }
+
+        // eased rather than jumped to, so the layer swells in and out
+        let target = cue.map_or(0.0, |c| c.intensity.clamp(0.0, 1.0));
+        let step = MUSIC_INTENSITY_RATE * dt;
+        self.intensity += (target - self.intensity).clamp(-step, step);
+
+        if let Some(p) = self.current.as_mut() {
+            p.fade_in = (p.fade_in + dt / MUSIC_CROSSFADE_LEN).min(1.0);
+            sm.set_volume(p.base, p.fade_in);
+            sm.set_volume(p.layer, p.fade_in * self.intensity);
+        }
+    }
+
+    fn start(&self, track: Track, sm: &mut SoundManager) -> Option<Playing> {
+        let stems = self.stems(track)?;
+        let base = sm.play_looped(stems.base, Channel::Music, 0.0)?;
+        let Some(layer) = sm.play_looped(stems.layer, Channel::Music, 0.0) else {
+            sm.stop(base);
+            return None
+        };
+        Some(Playing { track, base, layer, fade_in: 0.0 })
+    }
+}
@@ -9,14 +9,16 @@ struct VertexInput {
     @location(1) depth: f32,
     @location(2) tex_coords: vec2<f32>,
     @location(3) tex_index: u32,
-    @location(4) alpha: f32
+    @location(4) alpha: f32,
+    @location(5) colour: vec3<f32>
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
     @location(1) tex_index: u32,
-    @location(2) alpha: f32
+    @location(2) alpha: f32,
+    @location(3) colour: vec3<f32>
 };
 
 @vertex
@@ -28,6 +30,7 @@ fn vs_main(
     out.tex_coords = in.tex_coords;
     out.tex_index = in.tex_index;
     out.alpha = in.alpha;
+    out.colour = in.colour;
 
     out.clip_position = vec4(in.position, in.depth, 1.0);
 
@@ -41,12 +44,19 @@ var t_arr: binding_array<texture_2d<f32>>;
 @group(1) @binding(1)
 var s: sampler;
 
+// matches SOLID_TEX_INDEX in config.rs, vertices with it are drawn in their plain colour
+const SOLID_TEX_INDEX: u32 = 0xffffffffu;
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let colour = in.alpha * textureSample(
-        t_arr[in.tex_index],
+    // sampling has to happen for every fragment, so solid shapes sample
+    // the first texture and ignore it
+    let solid = in.tex_index == SOLID_TEX_INDEX;
+    let tex = textureSample(
+        t_arr[select(in.tex_index, 0u, solid)],
         s,
         in.tex_coords
     );
-    return colour;
+    let base = select(tex, vec4(1.0), solid);
+    return in.alpha * vec4(base.rgb * in.colour, base.a);
 }
@@ -0,0 +1,82 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    pos: vec3<f32>
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) colour: vec4<f32>
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) colour: vec4<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) world_pos: vec3<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.colour = model.colour;
+    out.normal = model.normal;
+    out.world_pos = model.position;
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    return out;
+}
+
+struct InstanceInput {
+    @location(3) position: vec3<f32>,
+    @location(4) height: f32,
+    @location(5) width: f32,
+    @location(6) rotation: vec4<f32>,
+    @location(7) colour0: vec4<f32>,
+    @location(8) colour1: vec4<f32>,
+    @location(9) colour2: vec4<f32>,
+    @location(10) colour3: vec4<f32>,
+    @location(11) colour4: vec4<f32>,
+    @location(12) colour5: vec4<f32>,
+};
+
+fn quat_rotate(q: vec4<f32>, v: vec3<f32>) -> vec3<f32> {
+    let t = 2.0 * cross(q.xyz, v);
+    return v + q.w * t + cross(q.xyz, t);
+}
+
+// draws the unit column mesh once per instance
+@vertex
+fn vs_instanced(
+    model: VertexInput,
+    instance: InstanceInput,
+    @builtin(vertex_index) vertex_index: u32
+) -> VertexOutput {
+    var colours = array<vec4<f32>, 6>(
+        instance.colour0,
+        instance.colour1,
+        instance.colour2,
+        instance.colour3,
+        instance.colour4,
+        instance.colour5,
+    );
+
+    let scale = vec3<f32>(instance.width, instance.height, instance.width);
+    let world_pos = instance.position + quat_rotate(instance.rotation, model.position * scale);
+
+    var out: VertexOutput;
+    // the unit mesh has 4 vertices per face, in the same order as the colours
+    out.colour = colours[vertex_index / 4u];
+    out.normal = quat_rotate(instance.rotation, model.normal);
+    out.world_pos = world_pos;
+    out.clip_position = camera.view_proj * vec4<f32>(world_pos, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.colour);
+}
@@ -0,0 +1,140 @@
+use std::{fmt, io::ErrorKind, path::{Path, PathBuf}};
+
+use log::warn;
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::*, game::GameMode};
+
+// A run in progress is written out when the game is paused or closed, and read back at startup
+// so it can be carried on from the title screen. The version goes up whenever what's saved
+// changes, so an older save is turned away rather than misread.
+
+pub const SAVE_VERSION: u32 = 1;
+
+/// Everything needed to carry on a run exactly where it was left
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunSave {
+    pub version: u32,
+    pub mode: GameMode,
+    pub sidelen: u8,
+    pub grid: Vec<Vec<u8>>, // column heights
+    pub nets: Vec<Vec<Vec<u8>>>, // the column heights each net was drawn from, in order
+    pub correct_index: usize,
+    pub answered: bool, // the puzzle had just been answered, so the next one is due
+    pub last_answer_was_correct: bool,
+    pub timer: f32,
+    pub timer_max: f32,
+    pub timer_start: f32,
+    pub timer_reduction: f32,
+    pub counter: u32,
+    pub run_best: u32,
+    pub answers: u32,
+    pub correct_answers: u32,
+    pub seed: u64,
+    pub puzzle_seed: u64,
+    pub rng: Pcg32
+}
+
+// read on its own first, so a save from another version is caught before the rest is parsed
+#[derive(Deserialize)]
+struct Header {
+    version: u32
+}
+
+/// Why a save couldn't be read or written
+#[derive(Debug)]
+pub enum SaveError {
+    NoDir,
+    Read(PathBuf, std::io::Error),
+    Write(PathBuf, std::io::Error),
+    Parse(PathBuf, serde_json::Error),
+    Version(u32),
+    Invalid(&'static str)
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::NoDir => write!(f, "There's nowhere to keep saves on this system"),
+            SaveError::Read(p, e) => write!(f, "Couldn't read {}: {e}", p.display()),
+            SaveError::Write(p, e) => write!(f, "Couldn't write {}: {e}", p.display()),
+            SaveError::Parse(p, e) => write!(f, "{} isn't a save this game understands: {e}", p.display()),
+            SaveError::Version(v) => write!(f, "The saved run is from version {v} of the save format, this game uses version {SAVE_VERSION}"),
+            SaveError::Invalid(why) => write!(f, "The saved run is broken, {why}")
+        }
+    }
+}
+
+/// Where the run in progress is saved
+pub fn save_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join(CONFIG_DIR_NAME).join(SAVE_FILE_NAME))
+}
+
+/// The saved run, None if there isn't one
+pub fn load() -> Result<Option<RunSave>, SaveError> {
+    load_from(&save_path().ok_or(SaveError::NoDir)?)
+}
+
+/// The run saved at `path`, None if there isn't one
+pub fn load_from(path: &Path) -> Result<Option<RunSave>, SaveError> {
+    let path = path.to_path_buf();
+    let text = match std::fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(SaveError::Read(path, e))
+    };
+
+    let header: Header = serde_json::from_str(&text).map_err(|e| SaveError::Parse(path.clone(), e))?;
+    if header.version != SAVE_VERSION {
+        return Err(SaveError::Version(header.version))
+    }
+    let save: RunSave = serde_json::from_str(&text).map_err(|e| SaveError::Parse(path, e))?;
+    save.validate()?;
+    Ok(Some(save))
+}
+
+pub fn save_to(path: &Path, run: &RunSave) -> Result<(), SaveError> {
+    let write_err = |e| SaveError::Write(path.to_path_buf(), e);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(write_err)?;
+    }
+    let text = serde_json::to_string(run).expect("A run can always be written as JSON");
+    // written alongside then moved over the old save, so closing part way through can't leave half a file
+    let temp = path.with_extension("tmp");
+    std::fs::write(&temp, text).map_err(write_err)?;
+    std::fs::rename(&temp, path).map_err(write_err)
+}
+
+/// Removes the run saved at `path`, once it's over or been carried on
+pub fn delete_at(path: &Path) {
+    if let Err(e) = std::fs::remove_file(path) {
+        if e.kind() != ErrorKind::NotFound {
+            warn!("Failed to remove {}: {e}", path.display());
+        }
+    }
+}
+
+impl RunSave {
+    // a save can be edited by hand, so anything that would trip the game up is checked for
+    fn validate(&self) -> Result<(), SaveError> {
+        let n = self.sidelen as usize;
+        // well over the tallest column the generator makes
+        let grid_ok = |g: &Vec<Vec<u8>>| g.len() == n && g.iter().all(|c| c.len() == n && c.iter().all(|&h| h <= self.sidelen * 2));
+
+        if !CONFIG_SIDELEN_RANGE.contains(&self.sidelen) {
+            return Err(SaveError::Invalid("the grid is a size the game can't make"))
+        }
+        if !grid_ok(&self.grid) || !self.nets.iter().all(grid_ok) {
+            return Err(SaveError::Invalid("a grid doesn't match its size"))
+        }
+        if self.nets.len() != NET_COUNT || self.correct_index >= NET_COUNT {
+            return Err(SaveError::Invalid("it doesn't have the right answers"))
+        }
+        let times = [self.timer, self.timer_max, self.timer_start];
+        if !times.iter().all(|t| t.is_finite() && *t > 0.0) || !CONFIG_TIMER_REDUCTION_RANGE.contains(&self.timer_reduction) {
+            return Err(SaveError::Invalid("the timer is out of range"))
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{config::*, d2::{layout::{Anchored, Layout}, number::{Number, NumberFormat}, text::Align}, tween::{target, Easing, Property, Timeline, Tweenable, Tweener, Value}, ui::{button::Button, label::Label, Ui}};
+
+use super::Shared;
+
+/// A square button with a label on it, like the answer buttons in game
+pub fn menu_button(label: &str, anchor: Anchored, shared: &Shared) -> Box<Button> {
+    Box::new(Button::new(
+        label,
+        anchor,
+        MENU_BUTTON_SIZE,
+        MENU_BUTTON_SIZE,
+        BUTTON_CORNER_RADIUS * MENU_BUTTON_SIZE / BUTTON_SIZE,
+        MENU_LAYER + 1,
+        MENU_LABEL_SIZE,
+        shared.font.clone(),
+        &shared.layout
+    ))
+}
+
+/// Centred text for the top of a menu or hints along the bottom
+pub fn menu_label(text: &str, anchor: Anchored, size: f32, shared: &Shared) -> Label {
+    Label::new(text, anchor, size, MENU_LAYER + 2, shared.font.clone(), &shared.layout)
+}
+
+/// Anchors for `count` buttons in a row, centred on `MENU_ROW_ANCHOR`
+pub fn row_anchors(count: usize) -> Vec<Anchored> {
+    let width = (count.max(1) - 1) as f32 * MENU_BUTTON_SPACING;
+    (0..count).map(|i| {
+        let mut a = MENU_ROW_ANCHOR;
+        a.offset.x += i as f32 * MENU_BUTTON_SPACING - width * 0.5;
+        a
+    }).collect()
+}
+
+/// A number centred on an anchor, for showing scores
+pub fn centred_number(value: f32, format: NumberFormat, anchor: Anchored, digit_h: f32, opacity: f32, layout: &Layout) -> Number {
+    Number::new(value, format, layout.resolve(anchor), 0.0, MENU_LAYER + 2, opacity, digit_h * 0.5, digit_h, 0.0, Align::Centre)
+}
+
+/// Fades a whole menu in from nothing
+pub fn fade_in(tweens: &mut Tweener<Ui>) {
+    tweens.play(Timeline::tween_from(
+        target(|ui: &mut Ui| Some(ui as &mut dyn Tweenable)),
+        Property::Opacity,
+        Value::Float(0.0),
+        Value::Float(1.0),
+        ANIM_MENU_FADE_LEN,
+        Easing::QuadOut
+    ));
+}
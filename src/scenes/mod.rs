@@ -0,0 +1,266 @@
+pub mod title;
+pub mod modeselect;
+pub mod pause;
+pub mod results;
+pub mod settings;
+pub mod menu;
+
+use std::sync::Arc;
+
+use cgmath::Vector2;
+use winit::keyboard::Key;
+
+use crate::{configfile::GameConfig, console::{CommandResult, CommandSpec}, d2::{font::FontAtlas, layout::Layout, retained::RetainedShape}, d3::columngrid::ColumnGrid, events::{EventBus, GameEvent}, music::MusicCue, savegame::RunSave};
+
+/// What a scene wants to happen to the stack after handling an update or input
+pub enum Transition {
+    None,
+    Push(Box<dyn Scene>),
+    Pop,
+    /// Pops this scene and hands the message to the one uncovered
+    PopWith(Message),
+    Replace(Box<dyn Scene>),
+    /// Pops everything above the root scene, then pushes the given scene if any
+    Unwind(Option<Box<dyn Scene>>),
+    Quit
+}
+
+/// Passed down to the scene beneath when one pops with `PopWith`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Message {
+    EndRun
+}
+
+/// State shared between every scene, which outlives any one of them
+pub struct Shared {
+    pub events: EventBus,
+    pub config: GameConfig,
+    pub layout: Layout,
+    pub mouse: MouseState,
+    pub best_score: u32,
+    pub font: Arc<FontAtlas>,
+    pub settings: UserSettings,
+    pub saved_run: Option<RunSave> // a run left part way through, to offer carrying on with
+}
+
+impl Shared {
+    /// Passes the settings on to whatever they affect, after they change
+    pub fn apply_settings(&mut self) {
+        self.events.emit(GameEvent::SettingsChanged(self.settings));
+    }
+}
+
+/// What the player has chosen on the settings screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UserSettings {
+    pub sound_on: bool,
+    pub volume: f32, // 0 to 1, multiplies every sound's own volume
+    pub sfx_volume: f32, // on top of volume, for everything but music
+    pub music_volume: f32
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self { sound_on: true, volume: 1.0, sfx_volume: 1.0, music_volume: 1.0 }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MouseState {
+    pub pos: Vector2<f32>, // GUI space
+    pub left: bool,
+    pub last_left: bool
+}
+
+impl MouseState {
+    pub fn new() -> Self {
+        Self {
+            pos: [100.0;2].into(),
+            left: false,
+            last_left: false
+        }
+    }
+    /// Whether the left button went down since the last update
+    pub fn left_clicked(&self) -> bool {
+        self.left && !self.last_left
+    }
+    // called once per update, after the scenes have seen this update's state
+    pub fn end_update(&mut self) {
+        self.last_left = self.left;
+    }
+}
+
+/// Everything the visible scenes want drawn this frame
+#[derive(Default)]
+pub struct Frame<'f> {
+    pub shapes: Vec<&'f mut dyn RetainedShape>,
+    pub grid: Option<&'f ColumnGrid>,
+    pub clear_colour: Option<[f32;4]>
+}
+
+pub trait Scene {
+    fn on_enter(&mut self, _shared: &mut Shared) {}
+    fn on_exit(&mut self, _shared: &mut Shared) {}
+    /// Called on every scene when the window is closing, top first
+    fn on_close(&mut self, _shared: &mut Shared) {}
+    /// Called when the scene above this one pops, with its message if it left one
+    fn on_resume(&mut self, _shared: &mut Shared, _message: Option<Message>) -> Transition {
+        Transition::None
+    }
+    /// Advances the scene by `dt` seconds, only called on the top scene
+    fn update(&mut self, shared: &mut Shared, dt: f32) -> Transition;
+    /// Handles a key press, only called on the top scene
+    fn input(&mut self, shared: &mut Shared, key: &Key) -> Transition;
+    /// Adds this scene's shapes to the frame, `alpha` being how far between the last
+    /// two updates to interpolate
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>, alpha: f32);
+    /// Moves things around to suit a new window shape
+    fn relayout(&mut self, layout: &Layout);
+    /// The music the scene wants playing, None to leave it to the scene beneath
+    fn music(&self) -> Option<MusicCue> {
+        None
+    }
+    /// Overlays are drawn over the scene beneath them, which stays frozen underneath
+    fn is_overlay(&self) -> bool {
+        false
+    }
+    /// Adds lines about the scene's state to the debug overlay
+    fn debug_info(&self, _out: &mut Vec<String>) {}
+    /// Adds the console commands the scene understands
+    fn commands(&self, _out: &mut Vec<CommandSpec>) {}
+    /// Runs a console command if it's one of the scene's, None if it isn't
+    fn run_command(&mut self, _shared: &mut Shared, _name: &str, _args: &[&str]) -> Option<CommandResult> {
+        None
+    }
+}
+
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>
+}
+
+impl SceneStack {
+    pub fn new(mut root: Box<dyn Scene>, shared: &mut Shared) -> Self {
+        root.on_enter(shared);
+        Self { scenes: vec![root] }
+    }
+
+    pub fn push(&mut self, scene: Box<dyn Scene>, shared: &mut Shared) {
+        self.apply(Transition::Push(scene), shared);
+    }
+
+    /// Returns false once a scene asks to quit
+    pub fn update(&mut self, shared: &mut Shared, dt: f32) -> bool {
+        let t = match self.scenes.last_mut() {
+            Some(s) => s.update(shared, dt),
+            None => Transition::Quit
+        };
+        self.apply(t, shared)
+    }
+
+    /// Returns false once a scene asks to quit
+    pub fn input(&mut self, shared: &mut Shared, key: &Key) -> bool {
+        let t = match self.scenes.last_mut() {
+            Some(s) => s.input(shared, key),
+            None => Transition::Quit
+        };
+        self.apply(t, shared)
+    }
+
+    /// Draws the top scene, along with any beneath it that show through overlays
+    pub fn render<'f>(&'f mut self, frame: &mut Frame<'f>, alpha: f32) {
+        let mut first = self.scenes.len().saturating_sub(1);
+        while first > 0 && self.scenes[first].is_overlay() {
+            first -= 1;
+        }
+        for s in self.scenes[first..].iter_mut() {
+            s.render(frame, alpha);
+        }
+    }
+
+    /// The music asked for by the topmost scene that has an opinion, None for silence
+    pub fn music(&self) -> Option<MusicCue> {
+        self.scenes.iter().rev().find_map(|s| s.music())
+    }
+
+    pub fn relayout(&mut self, layout: &Layout) {
+        for s in self.scenes.iter_mut() {
+            s.relayout(layout);
+        }
+    }
+
+    /// What every scene on the stack has to say for the debug overlay, bottom first
+    pub fn debug_info(&self, out: &mut Vec<String>) {
+        for s in self.scenes.iter() {
+            s.debug_info(out);
+        }
+    }
+
+    /// The console commands of every scene on the stack, top first
+    pub fn commands(&self, out: &mut Vec<CommandSpec>) {
+        for s in self.scenes.iter().rev() {
+            s.commands(out);
+        }
+    }
+
+    /// Runs a console command on the topmost scene that understands it
+    pub fn run_command(&mut self, shared: &mut Shared, name: &str, args: &[&str]) -> Option<CommandResult> {
+        self.scenes.iter_mut().rev().find_map(|s| s.run_command(shared, name, args))
+    }
+
+    /// Lets every scene know the window is closing
+    pub fn close(&mut self, shared: &mut Shared) {
+        for s in self.scenes.iter_mut().rev() {
+            s.on_close(shared);
+        }
+    }
+
+    fn apply(&mut self, t: Transition, shared: &mut Shared) -> bool {
+        match t {
+            Transition::None => {}
+            Transition::Push(mut s) => {
+                s.on_enter(shared);
+                self.scenes.push(s);
+            }
+            Transition::Pop => return self.pop(shared, None),
+            Transition::PopWith(m) => return self.pop(shared, Some(m)),
+            Transition::Replace(mut s) => {
+                if let Some(mut old) = self.scenes.pop() {
+                    old.on_exit(shared);
+                }
+                s.on_enter(shared);
+                self.scenes.push(s);
+            }
+            Transition::Unwind(s) => {
+                while self.scenes.len() > 1 {
+                    if let Some(mut old) = self.scenes.pop() {
+                        old.on_exit(shared);
+                    }
+                }
+                match s {
+                    Some(s) => return self.apply(Transition::Push(s), shared),
+                    None => return self.resume_top(shared, None)
+                }
+            }
+            Transition::Quit => return false
+        }
+        true
+    }
+
+    fn pop(&mut self, shared: &mut Shared, message: Option<Message>) -> bool {
+        if let Some(mut old) = self.scenes.pop() {
+            old.on_exit(shared);
+        }
+        if self.scenes.is_empty() {
+            return false
+        }
+        self.resume_top(shared, message)
+    }
+
+    fn resume_top(&mut self, shared: &mut Shared, message: Option<Message>) -> bool {
+        let t = match self.scenes.last_mut() {
+            Some(s) => s.on_resume(shared, message),
+            None => Transition::Quit
+        };
+        self.apply(t, shared)
+    }
+}
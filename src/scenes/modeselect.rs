@@ -0,0 +1,82 @@
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{config::*, d2::layout::Layout, game::{GameMode, GameState}, tween::Tweener, ui::{label::Label, KeyResult, Ui, UiMessage, Widget}};
+
+use super::{menu::{fade_in, menu_button, menu_label, row_anchors}, Frame, Scene, Shared, Transition};
+
+/// Picks which kind of run to start, one button per mode
+pub struct ModeSelect {
+    ui: Ui,
+    heading: Label,
+    hint: Label,
+    tweens: Tweener<Ui>
+}
+
+impl ModeSelect {
+    pub fn new(shared: &Shared) -> Self {
+        let mut ui: Ui = Ui::new();
+        for (a, mode) in row_anchors(GameMode::ALL.len()).into_iter().zip(GameMode::ALL) {
+            ui.add(menu_button(mode.name(), a, shared));
+        }
+
+        Self {
+            ui,
+            heading: menu_label("Choose a mode", MENU_HEADING_ANCHOR, MENU_HEADING_SIZE, shared),
+            hint: menu_label(
+                "Classic goes on forever, a wrong answer just resets your streak.\nIn sudden death, one wrong answer ends the run.\nIn two player, first to answer wins the round: keys 1-4 against 7-0 or the mouse.",
+                MENU_HINT_ANCHOR,
+                MENU_LABEL_SIZE,
+                shared
+            ),
+            tweens: Tweener::new()
+        }
+    }
+
+    fn choose(&mut self, i: usize, shared: &Shared) -> Transition {
+        match GameMode::ALL.get(i) {
+            // replacing keeps the title underneath to come back to
+            Some(&mode) => Transition::Replace(Box::new(GameState::new(shared, mode))),
+            None => Transition::None
+        }
+    }
+}
+
+impl Scene for ModeSelect {
+    fn on_enter(&mut self, _shared: &mut Shared) {
+        fade_in(&mut self.tweens);
+    }
+    fn update(&mut self, shared: &mut Shared, dt: f32) -> Transition {
+        self.tweens.update(dt, &mut self.ui);
+
+        match self.ui.update(shared) {
+            Some(UiMessage::Clicked(i)) => self.choose(i, shared),
+            _ => Transition::None
+        }
+    }
+    fn input(&mut self, shared: &mut Shared, key: &Key) -> Transition {
+        match self.ui.input(key) {
+            KeyResult::Message(UiMessage::Clicked(i)) => return self.choose(i, shared),
+            KeyResult::Ignored => {}
+            _ => return Transition::None
+        }
+        match key {
+            Key::Named(NamedKey::Enter) | Key::Named(NamedKey::Space) => self.choose(0, shared),
+            Key::Named(NamedKey::Escape) => Transition::Pop,
+            Key::Character(c) => match c.parse::<usize>() {
+                Ok(n) if n > 0 => self.choose(n - 1, shared),
+                _ => Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>, _alpha: f32) {
+        self.ui.render(frame);
+        self.heading.render(frame);
+        self.hint.render(frame);
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        self.ui.relayout(layout);
+        self.heading.relayout(layout);
+        self.hint.relayout(layout);
+    }
+}
@@ -0,0 +1,81 @@
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{config::*, d2::layout::Layout, tween::Tweener, ui::{label::Label, panel::Panel, KeyResult, Ui, UiMessage, Widget}};
+
+use super::{menu::{fade_in, menu_button, menu_label, row_anchors}, Frame, Message, Scene, Shared, Transition};
+
+const RESUME: usize = 0;
+const END_RUN: usize = 1;
+
+/// Dims the game underneath, which stays frozen until this is popped
+pub struct Pause {
+    ui: Ui,
+    heading: Label,
+    tweens: Tweener<Ui>
+}
+
+impl Pause {
+    pub fn new(shared: &Shared) -> Self {
+        let mut ui: Ui = Ui::new();
+        for (a, label) in row_anchors(2).into_iter().zip(["Resume", "End run"]) {
+            ui.add(menu_button(label, a, shared));
+        }
+        // added after the buttons so they keep the first ids, it's drawn beneath them by layer
+        ui.add(Box::new(Panel::covering(MENU_LAYER, [0.0;3], PAUSE_DIM_OPACITY, &shared.layout)));
+
+        Self {
+            ui,
+            heading: menu_label("Paused", MENU_HEADING_ANCHOR, MENU_HEADING_SIZE, shared),
+            tweens: Tweener::new()
+        }
+    }
+
+    fn choose(&mut self, i: usize) -> Transition {
+        match i {
+            RESUME => Transition::Pop,
+            END_RUN => Transition::PopWith(Message::EndRun),
+            _ => Transition::None
+        }
+    }
+}
+
+impl Scene for Pause {
+    fn on_enter(&mut self, _shared: &mut Shared) {
+        fade_in(&mut self.tweens);
+    }
+    fn update(&mut self, shared: &mut Shared, dt: f32) -> Transition {
+        self.tweens.update(dt, &mut self.ui);
+
+        match self.ui.update(shared) {
+            Some(UiMessage::Clicked(i)) => self.choose(i),
+            _ => Transition::None
+        }
+    }
+    fn input(&mut self, _shared: &mut Shared, key: &Key) -> Transition {
+        match self.ui.input(key) {
+            KeyResult::Message(UiMessage::Clicked(i)) => return self.choose(i),
+            KeyResult::Ignored => {}
+            _ => return Transition::None
+        }
+        match key {
+            Key::Named(NamedKey::Escape) => Transition::Pop,
+            Key::Character(c) => match c.as_str() {
+                "1" => self.choose(RESUME),
+                "2" => self.choose(END_RUN),
+                _ => Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>, _alpha: f32) {
+        self.ui.render(frame);
+        self.heading.render(frame);
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        self.ui.relayout(layout);
+        self.heading.relayout(layout);
+    }
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,124 @@
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{config::*, d2::{layout::Layout, number::{Number, NumberFormat}, retained::Retained, shape::Shape}, game::{GameMode, GameState}, tween::Tweener, ui::{label::Label, KeyResult, Ui, UiMessage, Widget}};
+
+use super::{menu::{centred_number, fade_in, menu_button, menu_label, row_anchors}, Frame, Scene, Shared, Transition};
+
+const PLAY_AGAIN: usize = 0;
+const TO_TITLE: usize = 1;
+
+const SCORE_DIGIT_H: f32 = 0.4;
+const IMPROVEMENT_DIGIT_H: f32 = 0.15;
+
+/// How a run went, handed from the game to the results screen
+#[derive(Clone, Copy, Debug)]
+pub struct RunSummary {
+    pub mode: GameMode,
+    pub score: u32, // best streak within the run
+    pub previous_best: u32, // all time best before the run
+    pub scores: Option<[u32; 2]> // each player's rounds won, in two player
+}
+
+/// Shown when a run ends, with the score, the best ever score,
+/// and buttons to play again or go back to the title.
+/// After a two player match, it's the winner and both scores instead.
+pub struct Results {
+    summary: RunSummary,
+    score: Retained<Number>,
+    improvement: Retained<Number>, // how much a new best beat the old one by
+    heading: Label,
+    best: Label,
+    ui: Ui,
+    tweens: Tweener<Ui>
+}
+
+impl Results {
+    pub fn new(summary: RunSummary, shared: &Shared) -> Self {
+        let mut ui: Ui = Ui::new();
+        for (a, label) in row_anchors(2).into_iter().zip(["Again", "Menu"]) {
+            ui.add(menu_button(label, a, shared));
+        }
+
+        Self {
+            summary,
+            score: Retained::new(centred_number(0.0, NumberFormat::Integer, MENU_SCORE_ANCHOR, SCORE_DIGIT_H, 0.8, &shared.layout)),
+            improvement: Retained::new(centred_number(
+                summary.score as f32 - summary.previous_best as f32,
+                NumberFormat::Signed,
+                MENU_IMPROVEMENT_ANCHOR,
+                IMPROVEMENT_DIGIT_H,
+                if summary.score > summary.previous_best && summary.scores.is_none() {0.6} else {0.0},
+                &shared.layout
+            )),
+            heading: menu_label(&match summary.scores {
+                Some([a, b]) if a == b => "Draw".into(),
+                Some([a, b]) => format!("Player {} wins", if a > b {1} else {2}),
+                None => summary.mode.name().into()
+            }, MENU_HEADING_ANCHOR, MENU_HEADING_SIZE, shared),
+            best: menu_label("", MENU_BEST_ANCHOR, MENU_LABEL_SIZE, shared),
+            ui,
+            tweens: Tweener::new()
+        }
+    }
+
+    fn choose(&mut self, i: usize, shared: &Shared) -> Transition {
+        match i {
+            PLAY_AGAIN => Transition::Replace(Box::new(GameState::new(shared, self.summary.mode))),
+            TO_TITLE => Transition::Pop,
+            _ => Transition::None
+        }
+    }
+}
+
+impl Scene for Results {
+    fn on_enter(&mut self, shared: &mut Shared) {
+        self.best.set_text(&match self.summary.scores {
+            Some([a, b]) => format!("Player 1: {a}  Player 2: {b}"),
+            None => format!("Best {}", shared.best_score)
+        });
+        self.score.roll_to(self.summary.score as f32, NUMBER_ROLL_LEN * 2.0);
+        fade_in(&mut self.tweens);
+    }
+    fn update(&mut self, shared: &mut Shared, dt: f32) -> Transition {
+        self.tweens.update(dt, &mut self.ui);
+        if self.score.is_rolling() {
+            self.score.update(dt);
+        }
+
+        match self.ui.update(shared) {
+            Some(UiMessage::Clicked(i)) => self.choose(i, shared),
+            _ => Transition::None
+        }
+    }
+    fn input(&mut self, shared: &mut Shared, key: &Key) -> Transition {
+        match self.ui.input(key) {
+            KeyResult::Message(UiMessage::Clicked(i)) => return self.choose(i, shared),
+            KeyResult::Ignored => {}
+            _ => return Transition::None
+        }
+        match key {
+            Key::Named(NamedKey::Enter) | Key::Named(NamedKey::Space) => self.choose(PLAY_AGAIN, shared),
+            Key::Named(NamedKey::Escape) => self.choose(TO_TITLE, shared),
+            Key::Character(c) => match c.as_str() {
+                "1" => self.choose(PLAY_AGAIN, shared),
+                "2" => self.choose(TO_TITLE, shared),
+                _ => Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>, _alpha: f32) {
+        frame.shapes.push(&mut self.score);
+        frame.shapes.push(&mut self.improvement);
+        self.heading.render(frame);
+        self.best.render(frame);
+        self.ui.render(frame);
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        self.score.set_pos(layout.resolve(MENU_SCORE_ANCHOR));
+        self.improvement.set_pos(layout.resolve(MENU_IMPROVEMENT_ANCHOR));
+        self.heading.relayout(layout);
+        self.best.relayout(layout);
+        self.ui.relayout(layout);
+    }
+}
@@ -0,0 +1,107 @@
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{config::*, d2::layout::Layout, events::GameEvent, tween::Tweener, ui::{label::Label, slider::Slider, toggle::Toggle, KeyResult, Ui, UiMessage, Widget}};
+
+use super::{menu::{fade_in, menu_button, menu_label, row_anchors}, Frame, Scene, Shared, Transition};
+
+const SOUND: usize = 0;
+const VOLUME: usize = 1;
+const SFX: usize = 2;
+const MUSIC: usize = 3;
+const BACK: usize = 4;
+
+/// Sound on or off and how loud, changes take effect straight away
+pub struct Settings {
+    ui: Ui,
+    heading: Label,
+    blipped_at: f32, // the volume the last blip was played at
+    tweens: Tweener<Ui>
+}
+
+impl Settings {
+    pub fn new(shared: &Shared) -> Self {
+        let (font, layout) = (shared.font.clone(), &shared.layout);
+        let mut ui: Ui = Ui::new();
+        ui.add(Box::new(Toggle::new("Sound", shared.settings.sound_on, MENU_SOUND_ROW_ANCHOR, MENU_LAYER + 1, font.clone(), layout)));
+        ui.add(Box::new(Slider::new("Volume", shared.settings.volume, MENU_VOLUME_ROW_ANCHOR, MENU_LAYER + 1, font.clone(), layout)));
+        ui.add(Box::new(Slider::new("Effects", shared.settings.sfx_volume, MENU_SFX_ROW_ANCHOR, MENU_LAYER + 1, font.clone(), layout)));
+        ui.add(Box::new(Slider::new("Music", shared.settings.music_volume, MENU_MUSIC_ROW_ANCHOR, MENU_LAYER + 1, font, layout)));
+        ui.add(menu_button("Back", row_anchors(1)[0], shared));
+        for id in [VOLUME, SFX, MUSIC] {
+            ui.set_disabled(id, !shared.settings.sound_on);
+        }
+
+        Self {
+            ui,
+            heading: menu_label("Settings", MENU_HEADING_ANCHOR, MENU_HEADING_SIZE, shared),
+            blipped_at: shared.settings.volume,
+            tweens: Tweener::new()
+        }
+    }
+
+    fn handle(&mut self, m: UiMessage, shared: &mut Shared) -> Transition {
+        match m {
+            UiMessage::Toggled(SOUND, on) => {
+                shared.settings.sound_on = on;
+                shared.apply_settings();
+                for id in [VOLUME, SFX, MUSIC] {
+                    self.ui.set_disabled(id, !on);
+                }
+            }
+            UiMessage::Changed(MUSIC, v) => {
+                // the music is already playing to judge it by
+                shared.settings.music_volume = v;
+                shared.apply_settings();
+            }
+            UiMessage::Changed(id @ (VOLUME | SFX), v) => {
+                if id == VOLUME {
+                    shared.settings.volume = v;
+                } else {
+                    shared.settings.sfx_volume = v;
+                }
+                shared.apply_settings();
+                // a blip at the new volume so it can be judged by ear,
+                // but not on every step of a drag
+                if (v - self.blipped_at).abs() >= UI_SLIDER_STEP * 0.99 {
+                    self.blipped_at = v;
+                    shared.events.emit(GameEvent::VolumeTested);
+                }
+            }
+            UiMessage::Clicked(BACK) => return Transition::Pop,
+            _ => {}
+        }
+        Transition::None
+    }
+}
+
+impl Scene for Settings {
+    fn on_enter(&mut self, _shared: &mut Shared) {
+        fade_in(&mut self.tweens);
+    }
+    fn update(&mut self, shared: &mut Shared, dt: f32) -> Transition {
+        self.tweens.update(dt, &mut self.ui);
+
+        match self.ui.update(shared) {
+            Some(m) => self.handle(m, shared),
+            None => Transition::None
+        }
+    }
+    fn input(&mut self, shared: &mut Shared, key: &Key) -> Transition {
+        match self.ui.input(key) {
+            KeyResult::Message(m) => self.handle(m, shared),
+            KeyResult::Handled => Transition::None,
+            KeyResult::Ignored => match key {
+                Key::Named(NamedKey::Escape) => Transition::Pop,
+                _ => Transition::None
+            }
+        }
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>, _alpha: f32) {
+        self.ui.render(frame);
+        self.heading.render(frame);
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        self.ui.relayout(layout);
+        self.heading.relayout(layout);
+    }
+}
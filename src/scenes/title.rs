@@ -0,0 +1,144 @@
+use rand::thread_rng;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{config::*, d2::{layout::Layout, number::{Number, NumberFormat}, text::Align, retained::Retained, shape::Shape}, d3::columngrid::ColumnGrid, game::{gen_cg, GameState}, music::{MusicCue, Track}, tween::Tweener, ui::{label::Label, KeyResult, Ui, UiMessage, Widget}};
+
+use super::{menu::{fade_in, menu_button, menu_label, row_anchors}, modeselect::ModeSelect, settings::Settings, Frame, Message, Scene, Shared, Transition};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Choice {
+    Continue,
+    Play,
+    Settings,
+    Quit
+}
+
+impl Choice {
+    fn label(&self) -> &'static str {
+        match self {
+            Choice::Continue => "Continue",
+            Choice::Play => "Play",
+            Choice::Settings => "Settings",
+            Choice::Quit => "Quit"
+        }
+    }
+}
+
+/// The first thing shown, a puzzle to look at along with play, settings and quit buttons,
+/// continue too if there's a run to carry on, and the best score so far in the corner
+pub struct Title {
+    ui: Ui,
+    choices: Vec<Choice>, // one per button, in order
+    heading: Label,
+    hint: Label,
+    best: Retained<Number>,
+    best_value: u32,
+    grid: ColumnGrid,
+    tweens: Tweener<Ui>
+}
+
+impl Title {
+    pub fn new(shared: &Shared) -> Self {
+        let layout = &shared.layout;
+        let (ui, choices) = Self::buttons(shared);
+
+        Self {
+            ui,
+            choices,
+            heading: menu_label("Cube Game", MENU_HEADING_ANCHOR, MENU_HEADING_SIZE, shared),
+            hint: menu_label("Look at the shape, pick the correct top-down view", MENU_HINT_ANCHOR, MENU_LABEL_SIZE, shared),
+            best: Retained::new(Number::new(0.0, NumberFormat::Integer, layout.resolve(BEST_COUNTER_ANCHOR), 0.0, MENU_LAYER, 0.5, 0.075, 0.15, 0.0, Align::Right)),
+            best_value: 0,
+            grid: gen_cg(shared.config.sidelen, &mut thread_rng()),
+            tweens: Tweener::new()
+        }
+    }
+
+    fn buttons(shared: &Shared) -> (Ui, Vec<Choice>) {
+        let mut choices = vec![Choice::Play, Choice::Settings, Choice::Quit];
+        if shared.saved_run.is_some() {
+            choices.insert(0, Choice::Continue);
+        }
+        let mut ui: Ui = Ui::new();
+        for (a, c) in row_anchors(choices.len()).into_iter().zip(&choices) {
+            ui.add(menu_button(c.label(), a, shared));
+        }
+        (ui, choices)
+    }
+
+    fn choose(&mut self, i: usize, shared: &mut Shared) -> Transition {
+        match self.choices.get(i) {
+            Some(Choice::Continue) => match shared.saved_run.take() {
+                Some(save) => Transition::Push(Box::new(GameState::resume(shared, save))),
+                None => Transition::None
+            },
+            Some(Choice::Play) => Transition::Push(Box::new(ModeSelect::new(shared))),
+            Some(Choice::Settings) => Transition::Push(Box::new(Settings::new(shared))),
+            Some(Choice::Quit) => Transition::Quit,
+            None => Transition::None
+        }
+    }
+
+    fn show_best(&mut self, shared: &Shared) {
+        if self.best_value != shared.best_score {
+            self.best_value = shared.best_score;
+            self.best.set(self.best_value as f32);
+        }
+    }
+}
+
+impl Scene for Title {
+    fn on_enter(&mut self, shared: &mut Shared) {
+        self.show_best(shared);
+        fade_in(&mut self.tweens);
+    }
+    fn on_resume(&mut self, shared: &mut Shared, _message: Option<Message>) -> Transition {
+        // coming back from a run, which may have set a new best
+        self.show_best(shared);
+        self.grid = gen_cg(shared.config.sidelen, &mut thread_rng());
+        // the run left to carry on may have been started or finished since
+        (self.ui, self.choices) = Self::buttons(shared);
+        fade_in(&mut self.tweens);
+        Transition::None
+    }
+    fn update(&mut self, shared: &mut Shared, dt: f32) -> Transition {
+        self.tweens.update(dt, &mut self.ui);
+
+        match self.ui.update(shared) {
+            Some(UiMessage::Clicked(i)) => self.choose(i, shared),
+            _ => Transition::None
+        }
+    }
+    fn input(&mut self, shared: &mut Shared, key: &Key) -> Transition {
+        match self.ui.input(key) {
+            KeyResult::Message(UiMessage::Clicked(i)) => return self.choose(i, shared),
+            KeyResult::Ignored => {}
+            _ => return Transition::None
+        }
+        match key {
+            Key::Named(NamedKey::Enter) | Key::Named(NamedKey::Space) => self.choose(0, shared),
+            Key::Named(NamedKey::Escape) => Transition::Quit,
+            Key::Character(c) => match c.parse::<usize>() {
+                Ok(n) if n > 0 => self.choose(n - 1, shared),
+                _ => Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+    fn music(&self) -> Option<MusicCue> {
+        Some(MusicCue { track: Track::Menu, intensity: 0.0 })
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>, _alpha: f32) {
+        self.ui.render(frame);
+        self.heading.render(frame);
+        self.hint.render(frame);
+        frame.shapes.push(&mut self.best);
+        frame.grid = Some(&self.grid);
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        self.ui.relayout(layout);
+        self.heading.relayout(layout);
+        self.hint.relayout(layout);
+        self.best.set_pos(layout.resolve(BEST_COUNTER_ANCHOR));
+    }
+}
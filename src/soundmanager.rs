@@ -1,29 +1,336 @@
-
-use std::io::Cursor;
+use std::{io::Cursor, sync::Arc, time::Duration};
 
 use debug_ignore::DebugIgnore;
-use rodio::{Decoder, OutputStreamHandle, Source};
+use log::{error, warn};
+use rodio::{decoder::DecoderError, Decoder, OutputStreamHandle, Sink, Source};
+
+use crate::assets::{load, SoundAsset, SoundId};
+
+// every sample of a sound, decoded when it's loaded so nothing is left to decode on the audio thread
+#[derive(Clone)]
+struct Decoded {
+    channels: u16,
+    sample_rate: u32,
+    samples: Arc<[f32]>
+}
+
+impl Decoded {
+    // a playback of the samples, sharing them rather than copying
+    fn source(&self, looped: bool) -> Playback {
+        Playback { sound: self.clone(), pos: 0, looped }
+    }
+}
+
+// reads through a decoded sound, going back to the start at the end if looped
+struct Playback {
+    sound: Decoded,
+    pos: usize,
+    looped: bool
+}
+
+impl Iterator for Playback {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.looped && self.pos == self.sound.samples.len() {
+            self.pos = 0;
+        }
+        let sample = self.sound.samples.get(self.pos).copied();
+        self.pos += 1;
+        sample
+    }
+}
+
+impl Source for Playback {
+    // the channels and rate never change part way through
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+    fn channels(&self) -> u16 {
+        self.sound.channels
+    }
+    fn sample_rate(&self) -> u32 {
+        self.sound.sample_rate
+    }
+    fn total_duration(&self) -> Option<Duration> {
+        let frames = self.sound.samples.len() as f64 / self.sound.channels.max(1) as f64;
+        (!self.looped).then(|| Duration::from_secs_f64(frames / self.sound.sample_rate as f64))
+    }
+}
+
+/// Which volume a sound is mixed at, on top of the master volume
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Channel {
+    Sfx,
+    Music
+}
+
+/// Identifies one playback of a sound, to stop or fade it later.
+/// Handles to sounds that have finished are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SoundHandle(u64);
+
+// a sound decoded up front, so playing it only has to read its samples
+struct Sound {
+    source: Decoded,
+    volume: f32,
+    max_concurrent: usize
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Fade {
+    from: f32,
+    to: f32,
+    elapsed: f32,
+    len: f32,
+    stop_after: bool
+}
+
+// one sound that's currently playing
+struct Playing {
+    handle: SoundHandle,
+    sound: usize,
+    channel: Channel,
+    sink: Sink,
+    volume: f32, // the sound's own volume, times any fade
+    fade: Option<Fade>
+}
 
 #[derive(Debug)]
 pub struct SoundManager {
     stream_handle: DebugIgnore<OutputStreamHandle>,
-    sounds: Vec<(&'static[u8],f32)>,
+    sounds: DebugIgnore<Vec<Option<Sound>>>, // None if it failed to decode
+    ids: Vec<SoundId>, // of the sounds from files, which come first
+    playing: DebugIgnore<Vec<Playing>>,
+    next_handle: u64,
+    master: f32,
+    sfx: f32,
+    music: f32,
+    muted: bool
 }
 
 impl SoundManager {
-    pub fn new(sounds: Vec<(&'static[u8],f32)>, osh: OutputStreamHandle) -> Self {
-        let stream_handle = osh;
+    /// Loads and decodes every sound in the manifest, falling back on the built in copy of
+    /// any file that won't decode. Anything that still fails is logged and stays silent,
+    /// rather than taking the game down with it.
+    pub fn new(assets: &[SoundAsset], osh: OutputStreamHandle) -> Self {
+        let sounds = assets.iter().map(|a| {
+            let data = load(a.file, a.data);
+            let decoded = decode(data.to_vec()).or_else(|e| {
+                if data == a.data {
+                    return Err(e)
+                }
+                warn!("Failed to decode {}, using the built in copy: {e}", a.file);
+                decode(a.data.to_vec())
+            });
+            match decoded {
+                Ok(source) => Some(Sound { source, volume: a.volume, max_concurrent: a.max_concurrent }),
+                Err(e) => {
+                    error!("Failed to decode sound {}: {e}", a.file);
+                    None
+                }
+            }
+        }).collect::<Vec<_>>();
 
         Self {
-            stream_handle: stream_handle.into(),
-            sounds,
+            stream_handle: osh.into(),
+            sounds: sounds.into(),
+            ids: assets.iter().map(|a| a.id).collect(),
+            playing: vec![].into(),
+            next_handle: 0,
+            master: 1.0,
+            sfx: 1.0,
+            music: 1.0,
+            muted: false
+        }
+    }
+
+    /// Adds a sound made at runtime from mono samples, returning its index.
+    /// None if the sample rate makes no sense.
+    pub fn add_samples(&mut self, samples: Vec<f32>, sample_rate: u32, volume: f32, max_concurrent: usize) -> Option<u32> {
+        if sample_rate == 0 {
+            error!("Can't add a sound with a sample rate of 0");
+            return None
+        }
+        let source = Decoded { channels: 1, sample_rate, samples: samples.into() };
+        self.sounds.push(Some(Sound { source, volume, max_concurrent }));
+        Some(self.sounds.len() as u32 - 1)
+    }
+
+    /// Swaps a sound for a new version of its file, keeping the old one if it won't decode.
+    /// Anything already playing finishes with the old version.
+    #[cfg(debug_assertions)]
+    pub fn replace(&mut self, id: SoundId, data: Vec<u8>) {
+        let Some(index) = self.ids.iter().position(|i| *i == id) else {
+            return
+        };
+        match (decode(data), self.sounds[index].as_mut()) {
+            (Ok(source), Some(sound)) => sound.source = source,
+            (Ok(_), None) => warn!("{id:?} failed to load at startup, so can't be reloaded"),
+            (Err(e), _) => error!("Failed to decode the new {id:?}, keeping the old one: {e}")
+        }
+    }
+
+    /// Plays one of the manifest's sounds once
+    pub fn play(&mut self, id: SoundId, channel: Channel) -> Option<SoundHandle> {
+        let Some(index) = self.ids.iter().position(|i| *i == id) else {
+            warn!("Tried to play {id:?}, which isn't in the manifest");
+            return None
+        };
+        self.start(index, channel, false, 1.0)
+    }
+
+    /// Plays a sound added with `add_samples` over and over until it's stopped,
+    /// starting at `volume` times its own volume
+    pub fn play_looped(&mut self, soundindex: u32, channel: Channel, volume: f32) -> Option<SoundHandle> {
+        self.start(soundindex as usize, channel, true, volume)
+    }
+
+    fn start(&mut self, index: usize, channel: Channel, looped: bool, volume: f32) -> Option<SoundHandle> {
+        let Some(Some(sound)) = self.sounds.get(index) else {
+            warn!("Tried to play sound {index}, which doesn't exist or failed to load");
+            return None
+        };
+
+        // at the limit, the oldest playback of the sound makes way for the new one,
+        // so quick repeats like hovering along a row of buttons all get heard
+        let count = self.playing.iter().filter(|p| p.sound == index).count();
+        if count >= sound.max_concurrent {
+            if let Some(oldest) = self.playing.iter().position(|p| p.sound == index) {
+                self.playing.remove(oldest).sink.stop();
+            }
         }
+
+        let sink = match Sink::try_new(&self.stream_handle) {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Failed to start sound {index}: {e}");
+                return None
+            }
+        };
+        sink.append(sound.source.source(looped));
+
+        let handle = SoundHandle(self.next_handle);
+        self.next_handle += 1;
+        let p = Playing {
+            handle,
+            sound: index,
+            channel,
+            sink,
+            volume: sound.volume * volume,
+            fade: None
+        };
+        p.sink.set_volume(p.volume * self.gain(channel));
+        self.playing.push(p);
+        Some(handle)
     }
-    pub fn play(&mut self, soundindex: u32) {
-        self.stream_handle.play_raw(
-            Decoder::new_wav(
-                Cursor::new(self.sounds[soundindex as usize].0)
-            ).unwrap().convert_samples().amplify(self.sounds[soundindex as usize].1)
-        ).unwrap();
+
+    /// Stops a sound straight away
+    pub fn stop(&mut self, handle: SoundHandle) {
+        if let Some(i) = self.playing.iter().position(|p| p.handle == handle) {
+            self.playing.remove(i).sink.stop();
+        }
     }
+
+    /// Fades a sound to `volume` times its own volume over `len` seconds,
+    /// stopping it at the end if `stop_after` is set
+    pub fn fade(&mut self, handle: SoundHandle, volume: f32, len: f32, stop_after: bool) {
+        let Some(p) = self.playing.iter_mut().find(|p| p.handle == handle) else {
+            return
+        };
+        let own = self.sounds[p.sound].as_ref().map_or(1.0, |s| s.volume);
+        p.fade = Some(Fade { from: p.volume, to: own * volume, elapsed: 0.0, len, stop_after });
+    }
+
+    /// Sets a sound to `volume` times its own volume straight away, cancelling any fade
+    pub fn set_volume(&mut self, handle: SoundHandle, volume: f32) {
+        let gains = self.gains();
+        let Some(p) = self.playing.iter_mut().find(|p| p.handle == handle) else {
+            return
+        };
+        let own = self.sounds[p.sound].as_ref().map_or(1.0, |s| s.volume);
+        p.volume = own * volume;
+        p.fade = None;
+        p.sink.set_volume(p.volume * gains[p.channel as usize]);
+    }
+
+    /// Whether a sound is still playing
+    #[allow(dead_code)] // nothing waits on a sound yet
+    pub fn is_playing(&self, handle: SoundHandle) -> bool {
+        self.playing.iter().any(|p| p.handle == handle)
+    }
+
+    pub fn set_master_volume(&mut self, v: f32) {
+        self.master = v.clamp(0.0, 1.0);
+        self.apply_volumes();
+    }
+
+    pub fn set_channel_volume(&mut self, channel: Channel, v: f32) {
+        let v = v.clamp(0.0, 1.0);
+        match channel {
+            Channel::Sfx => self.sfx = v,
+            Channel::Music => self.music = v
+        }
+        self.apply_volumes();
+    }
+
+    /// Silences everything without forgetting the volumes, sounds carry on playing underneath
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.apply_volumes();
+    }
+
+    /// Advances fades by `dt` seconds and forgets sounds that have finished
+    pub fn update(&mut self, dt: f32) {
+        let gains = self.gains();
+
+        self.playing.retain_mut(|p| {
+            if let Some(f) = p.fade.as_mut() {
+                f.elapsed += dt;
+                let t = if f.len > 0.0 {(f.elapsed / f.len).min(1.0)} else {1.0};
+                p.volume = f.from + (f.to - f.from) * t;
+                p.sink.set_volume(p.volume * gains[p.channel as usize]);
+                if t >= 1.0 {
+                    let stop = f.stop_after;
+                    p.fade = None;
+                    if stop {
+                        p.sink.stop();
+                        return false
+                    }
+                }
+            }
+            !p.sink.empty()
+        });
+    }
+
+    // what a channel's sounds are multiplied by
+    fn gain(&self, channel: Channel) -> f32 {
+        if self.muted {
+            return 0.0
+        }
+        self.master * match channel {
+            Channel::Sfx => self.sfx,
+            Channel::Music => self.music
+        }
+    }
+
+    // every channel's gain, indexed by channel
+    fn gains(&self) -> [f32;2] {
+        [self.gain(Channel::Sfx), self.gain(Channel::Music)]
+    }
+
+    fn apply_volumes(&mut self) {
+        for p in self.playing.iter() {
+            p.sink.set_volume(p.volume * self.gain(p.channel));
+        }
+    }
+}
+
+fn decode(data: Vec<u8>) -> Result<Decoded, DecoderError> {
+    let decoder = Decoder::new(Cursor::new(data))?;
+    Ok(Decoded {
+        channels: decoder.channels(),
+        sample_rate: decoder.sample_rate(),
+        samples: decoder.convert_samples().collect()
+    })
 }
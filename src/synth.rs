@@ -0,0 +1,136 @@
+use std::f32::consts::PI;
+
+use crate::config::MUSIC_SAMPLE_RATE;
+
+// Music is generated when the game starts rather than shipped as audio files.
+// Each track is a loop made of two stems the same length, a calm base that always plays
+// and a busier layer on top that fades in with intensity, so they can be mixed freely.
+
+/// The waveform a note is played with
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Voice {
+    Sine,
+    Triangle,
+    Noise // for percussion, pitch is ignored
+}
+
+/// A loop being written, one channel of samples at MUSIC_SAMPLE_RATE
+struct Stem {
+    samples: Vec<f32>,
+    beat_len: f32, // seconds
+    noise: u32 // state for the noise voice, so every run sounds the same
+}
+
+impl Stem {
+    fn new(bpm: f32, beats: usize) -> Self {
+        let beat_len = 60.0 / bpm;
+        let len = (beat_len * beats as f32 * MUSIC_SAMPLE_RATE as f32).round() as usize;
+        Self { samples: vec![0.0; len], beat_len, noise: 0x9e37_79b9 }
+    }
+
+    // a cheap xorshift, in -1 to 1
+    fn next_noise(&mut self) -> f32 {
+        self.noise ^= self.noise << 13;
+        self.noise ^= self.noise >> 17;
+        self.noise ^= self.noise << 5;
+        self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    /// Adds a note starting `beat` beats in, lasting `beats` beats plus its release.
+    /// Anything running past the end of the loop wraps round to the start, so the loop is seamless.
+    #[allow(clippy::too_many_arguments)]
+    fn note(&mut self, voice: Voice, midi: f32, beat: f32, beats: f32, gain: f32, attack: f32, release: f32) {
+        let rate = MUSIC_SAMPLE_RATE as f32;
+        let freq = 440.0 * 2f32.powf((midi - 69.0) / 12.0);
+        let start = (beat * self.beat_len * rate) as usize;
+        let held = beats * self.beat_len;
+        let total = ((held + release) * rate) as usize;
+        let len = self.samples.len();
+
+        for i in 0..total {
+            let t = i as f32 / rate;
+            // linear attack, then an exponential tail once the note is let go
+            let env = if t < attack {
+                t / attack
+            } else if t < held {
+                1.0
+            } else {
+                (-(t - held) * 5.0 / release).exp()
+            };
+            let phase = (t * freq).fract();
+            let wave = match voice {
+                Voice::Sine => (phase * 2.0 * PI).sin(),
+                Voice::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+                Voice::Noise => self.next_noise()
+            };
+            self.samples[(start + i) % len] += wave * env * gain;
+        }
+    }
+
+    /// Scales the loop so its loudest sample is at `peak`
+    fn finish(mut self, peak: f32) -> Vec<f32> {
+        let max = self.samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        if max > 0.0 {
+            for s in self.samples.iter_mut() {
+                *s *= peak / max;
+            }
+        }
+        self.samples
+    }
+}
+
+// A minor, F, C, G, one chord a bar, as midi notes from the root up
+const PROGRESSION: [[f32;3];4] = [
+    [57.0, 60.0, 64.0],
+    [53.0, 57.0, 60.0],
+    [48.0, 52.0, 55.0],
+    [55.0, 59.0, 62.0]
+];
+
+/// (base, layer) for the menus, slow pads with a gentle arpeggio over them
+pub fn menu_stems() -> (Vec<f32>, Vec<f32>) {
+    let mut base = Stem::new(76.0, 16);
+    let mut layer = Stem::new(76.0, 16);
+
+    for (bar, chord) in PROGRESSION.iter().enumerate() {
+        let b = bar as f32 * 4.0;
+        for n in chord {
+            base.note(Voice::Sine, *n, b, 3.5, 0.3, 0.6, 1.5);
+        }
+        base.note(Voice::Sine, chord[0] - 12.0, b, 3.5, 0.4, 0.3, 1.0);
+
+        for step in 0..8 {
+            let n = chord[step % 3] + if step >= 4 {12.0} else {0.0};
+            layer.note(Voice::Triangle, n + 12.0, b + step as f32 * 0.5, 0.25, 0.25, 0.01, 0.6);
+        }
+    }
+
+    (base.finish(0.5), layer.finish(0.4))
+}
+
+/// (base, layer) for a run, a driving bass line, with hats and a quicker arpeggio layered on top
+pub fn game_stems() -> (Vec<f32>, Vec<f32>) {
+    let mut base = Stem::new(120.0, 16);
+    let mut layer = Stem::new(120.0, 16);
+
+    for (bar, chord) in PROGRESSION.iter().enumerate() {
+        let b = bar as f32 * 4.0;
+        for beat in 0..8 {
+            let n = chord[0] - 12.0 + if beat % 2 == 1 {12.0} else {0.0};
+            base.note(Voice::Triangle, n, b + beat as f32 * 0.5, 0.3, 0.5, 0.005, 0.15);
+        }
+        for n in chord {
+            base.note(Voice::Sine, *n, b, 3.8, 0.12, 0.2, 0.4);
+        }
+
+        for step in 0..16 {
+            let s = b + step as f32 * 0.25;
+            // off beat hats, louder on the eighths
+            layer.note(Voice::Noise, 0.0, s, 0.02, if step % 2 == 1 {0.15} else {0.07}, 0.001, 0.05);
+            let n = chord[(step * 2) % 3] + 24.0;
+            layer.note(Voice::Sine, n, s, 0.1, 0.2, 0.005, 0.2);
+        }
+    }
+
+    (base.finish(0.5), layer.finish(0.35))
+}
@@ -0,0 +1,266 @@
+use std::{collections::VecDeque, sync::atomic::{AtomicU64, Ordering}};
+
+use cgmath::{Vector2, Vector3, VectorSpace};
+
+use crate::mathsutils::{lerp, lerp4d};
+
+/// Easing curves, mapping linear progress from 0 to 1 onto eased progress
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    QuadOut,
+    CubicOut,
+    ExpoIn,
+    ExpoOut
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::ExpoIn => if t == 0.0 {0.0} else {(2.0f32).powf(10.0 * t - 10.0)},
+            Easing::ExpoOut => if t == 1.0 {1.0} else {1.0 - (2.0f32).powf(-10.0 * t)}
+        }
+    }
+}
+
+/// Something about a shape that can be animated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Property {
+    Position,
+    #[allow(dead_code)] // every shape can be turned, but no transition turns one yet
+    Rotation,
+    Opacity,
+    Colour,
+    #[allow(dead_code)] // every shape can be resized, but no transition resizes one yet
+    Scale
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    Float(f32),
+    Vec2(Vector2<f32>),
+    Vec3(Vector3<f32>),
+    Colour([f32;4])
+}
+
+impl Value {
+    /// Interpolates between two values of the same kind.
+    /// Mismatched kinds snap to `other` at the end rather than panicking.
+    pub fn lerp(self, other: Value, t: f32) -> Value {
+        match (self, other) {
+            (Value::Float(a), Value::Float(b)) => Value::Float(lerp(a, b, t)),
+            (Value::Vec2(a), Value::Vec2(b)) => Value::Vec2(a.lerp(b, t)),
+            (Value::Vec3(a), Value::Vec3(b)) => Value::Vec3(a.lerp(b, t)),
+            (Value::Colour(a), Value::Colour(b)) => Value::Colour(lerp4d(a, b, t)),
+            _ => if t >= 1.0 {other} else {self}
+        }
+    }
+}
+
+/// Implemented by anything whose properties can be tweened.
+/// Properties a type doesn't have should return `None` and ignore sets.
+pub trait Tweenable {
+    fn get_property(&self, p: Property) -> Option<Value>;
+    fn set_property(&mut self, p: Property, v: Value);
+}
+
+/// Finds the thing a tween animates, given the context the tweens run in.
+/// Returning `None` (e.g. because the target has since been removed) just skips the tween.
+pub type Target<C> = Box<dyn for<'a> Fn(&'a mut C) -> Option<&'a mut dyn Tweenable>>;
+
+/// Helper to box a target closure, which also lets the compiler infer its lifetimes
+pub fn target<C, F>(f: F) -> Target<C>
+where
+    F: for<'a> Fn(&'a mut C) -> Option<&'a mut dyn Tweenable> + 'static
+{
+    Box::new(f)
+}
+
+/// Run once when a timeline reaches it
+pub type Callback<C> = Box<dyn FnOnce(&mut C)>;
+
+pub struct Tween<C> {
+    target: Target<C>,
+    property: Property,
+    from: Option<Value>, // filled in from the target when the tween starts
+    to: Value,
+    duration: f32, // seconds
+    elapsed: f32,
+    easing: Easing
+}
+
+/// A tree of tweens, waits and callbacks
+pub enum Timeline<C> {
+    Tween(Tween<C>),
+    Wait(f32),
+    Call(Option<Callback<C>>),
+    Sequence(VecDeque<Timeline<C>>),
+    Parallel(Vec<Timeline<C>>)
+}
+
+impl<C> Timeline<C> {
+    pub fn tween(target: Target<C>, property: Property, to: Value, duration: f32, easing: Easing) -> Self {
+        Timeline::Tween(Tween {
+            target,
+            property,
+            from: None,
+            to,
+            duration,
+            elapsed: 0.0,
+            easing
+        })
+    }
+    /// Like `tween`, but with an explicit start value rather than the target's current one
+    pub fn tween_from(target: Target<C>, property: Property, from: Value, to: Value, duration: f32, easing: Easing) -> Self {
+        Timeline::Tween(Tween {
+            target,
+            property,
+            from: Some(from),
+            to,
+            duration,
+            elapsed: 0.0,
+            easing
+        })
+    }
+    pub fn wait(duration: f32) -> Self {
+        Timeline::Wait(duration)
+    }
+    pub fn call(f: impl FnOnce(&mut C) + 'static) -> Self {
+        Timeline::Call(Some(Box::new(f)))
+    }
+    /// Runs each timeline after the previous one finishes
+    pub fn sequence(items: Vec<Timeline<C>>) -> Self {
+        Timeline::Sequence(items.into())
+    }
+    /// Runs all the timelines at once, finishing when the longest does
+    pub fn parallel(items: Vec<Timeline<C>>) -> Self {
+        Timeline::Parallel(items)
+    }
+
+    /// Advances by `dt` seconds.
+    /// Returns `Some(leftover time)` once finished, or `None` while still running.
+    fn step(&mut self, dt: f32, ctx: &mut C) -> Option<f32> {
+        match self {
+            Timeline::Tween(t) => {
+                let Some(target) = (t.target)(ctx) else {
+                    return Some(dt)
+                };
+
+                let from = *t.from.get_or_insert_with(|| {
+                    target.get_property(t.property).unwrap_or(t.to)
+                });
+
+                t.elapsed += dt;
+                let n = if t.duration <= 0.0 {1.0} else {(t.elapsed / t.duration).min(1.0)};
+                target.set_property(t.property, from.lerp(t.to, t.easing.apply(n)));
+
+                if t.elapsed >= t.duration {
+                    Some(t.elapsed - t.duration)
+                } else {
+                    None
+                }
+            }
+            Timeline::Wait(remaining) => {
+                *remaining -= dt;
+                if *remaining <= 0.0 {
+                    Some(-*remaining)
+                } else {
+                    None
+                }
+            }
+            Timeline::Call(f) => {
+                if let Some(f) = f.take() {
+                    f(ctx);
+                }
+                Some(dt)
+            }
+            Timeline::Sequence(items) => {
+                // time left over from one item carries on into the next,
+                // so sequences don't drift from their nominal length
+                let mut dt = dt;
+                while let Some(front) = items.front_mut() {
+                    match front.step(dt, ctx) {
+                        Some(leftover) => {
+                            items.pop_front();
+                            dt = leftover;
+                        }
+                        None => return None
+                    }
+                }
+                Some(dt)
+            }
+            Timeline::Parallel(items) => {
+                let mut leftover = dt;
+                items.retain_mut(|item| {
+                    match item.step(dt, ctx) {
+                        Some(l) => {
+                            leftover = leftover.min(l);
+                            false
+                        }
+                        None => true
+                    }
+                });
+                if items.is_empty() {
+                    Some(leftover)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(0);
+
+/// Refers to a playing timeline, handles are unique across every tweener
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TweenHandle(u64);
+
+/// Runs timelines against a context, usually the struct that owns the things being animated
+pub struct Tweener<C> {
+    running: Vec<(TweenHandle, Timeline<C>)>
+}
+
+impl<C> Default for Tweener<C> {
+    fn default() -> Self {
+        Self {
+            running: vec![]
+        }
+    }
+}
+
+impl<C> Tweener<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn play(&mut self, timeline: Timeline<C>) -> TweenHandle {
+        let handle = TweenHandle(NEXT_HANDLE.fetch_add(1, Ordering::Relaxed));
+        self.running.push((handle, timeline));
+        handle
+    }
+
+    /// Plays `timeline`, then calls `on_complete` when it finishes
+    pub fn play_then(&mut self, timeline: Timeline<C>, on_complete: impl FnOnce(&mut C) + 'static) -> TweenHandle {
+        self.play(Timeline::sequence(vec![timeline, Timeline::call(on_complete)]))
+    }
+
+    /// Stops a timeline where it is, without running any of its remaining callbacks
+    pub fn cancel(&mut self, handle: TweenHandle) {
+        self.running.retain(|(h, _)| *h != handle);
+    }
+
+    pub fn update(&mut self, dt: f32, ctx: &mut C) {
+        self.running.retain_mut(|(_, t)| t.step(dt, ctx).is_none());
+    }
+
+    /// Takes on timelines from another tweener, e.g. ones queued by callbacks while
+    /// this one was busy updating
+    pub fn absorb(&mut self, mut other: Tweener<C>) {
+        self.running.append(&mut other.running);
+    }
+}
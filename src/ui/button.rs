@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use cgmath::Vector2;
+
+use crate::{config::*, d2::{font::FontAtlas, hittest::HitTest, layout::{Anchored, Layout}, retained::Retained, roundedrect::RoundedRect, shape::Shape, tessellate::Style, text::{Align, Text}}, scenes::Frame};
+
+use super::{fill_opacity, Interaction, UiMessage, Widget, WidgetId};
+
+/// A rounded box with a label on it, clicked to do something
+pub struct Button {
+    bg: Retained<RoundedRect>,
+    ring: Retained<RoundedRect>, // shown while focused
+    label: Retained<Text>,
+    anchor: Anchored,
+    interaction: Interaction,
+    fade: f32
+}
+
+impl Button {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        label: &str,
+        anchor: Anchored,
+        width: f32,
+        height: f32,
+        radius: f32,
+        layer: u8,
+        text_size: f32,
+        font: Arc<FontAtlas>,
+        layout: &Layout
+    ) -> Self {
+        let pos = layout.resolve(anchor);
+        let mut b = Self {
+            bg: Retained::new(RoundedRect::new(width, height, radius, pos, Style::Fill, layer, BUTTON_COLOUR, 0.0)),
+            ring: Retained::new(RoundedRect::new(
+                0.0,
+                0.0,
+                0.0,
+                pos,
+                Style::Stroke(UI_FOCUS_RING_WIDTH),
+                layer,
+                UI_ACCENT_COLOUR,
+                0.0
+            )),
+            label: Retained::new(Text::new(
+                label,
+                font,
+                pos,
+                0.0,
+                text_size,
+                layer + 1,
+                TEXT_COLOUR,
+                0.0,
+                Align::Centre,
+                Some(width * 0.85)
+            )),
+            anchor,
+            interaction: Interaction::default(),
+            fade: 1.0
+        };
+        b.fit_ring();
+        b
+    }
+
+    /// Moves and resizes the button, taking effect at the next relayout
+    pub fn place(&mut self, anchor: Anchored, width: f32, height: f32, radius: f32) {
+        self.anchor = anchor;
+        self.bg.width = width;
+        self.bg.height = height;
+        self.bg.radius = radius;
+        self.label.set_wrap_width(Some(width * 0.85));
+        self.fit_ring();
+    }
+
+    fn fit_ring(&mut self) {
+        self.ring.width = self.bg.width + UI_FOCUS_RING_GAP * 2.0;
+        self.ring.height = self.bg.height + UI_FOCUS_RING_GAP * 2.0;
+        self.ring.radius = self.bg.radius + UI_FOCUS_RING_GAP;
+    }
+
+    fn restyle(&mut self) {
+        let i = self.interaction;
+        self.bg.set_opacity(fill_opacity(i) * self.fade);
+        self.ring.set_opacity(if i.focused {UI_TEXT_OPACITY * self.fade} else {0.0});
+        let text = if i.disabled {UI_TEXT_OPACITY * 0.5} else {UI_TEXT_OPACITY};
+        self.label.set_opacity(text * self.fade);
+    }
+}
+
+impl HitTest for Button {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        self.bg.hit(p)
+    }
+}
+
+impl Widget for Button {
+    fn layer(&self) -> u8 {
+        self.bg.layer
+    }
+    fn show(&mut self, interaction: Interaction) {
+        self.interaction = interaction;
+        self.restyle();
+    }
+    fn set_fade(&mut self, fade: f32) {
+        self.fade = fade;
+        self.restyle();
+    }
+    fn activate(&mut self, id: WidgetId) -> Option<UiMessage> {
+        Some(UiMessage::Clicked(id))
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        let pos = layout.resolve(self.anchor);
+        self.bg.set_pos(pos);
+        self.ring.set_pos(pos);
+        self.label.set_pos(pos);
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>) {
+        frame.shapes.push(&mut self.bg);
+        frame.shapes.push(&mut self.ring);
+        frame.shapes.push(&mut self.label);
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use cgmath::Vector2;
+
+use crate::{config::*, d2::{font::FontAtlas, hittest::HitTest, layout::{Anchored, Layout}, retained::Retained, shape::Shape, text::{Align, Text}, tovertind2d::ToVertInd2D}, scenes::Frame};
+
+use super::{Interaction, Widget};
+
+/// Centred text pinned to an anchor, wrapped to fit the window
+pub struct Label {
+    text: Retained<Text>,
+    anchor: Anchored
+}
+
+impl Label {
+    pub fn new(text: &str, anchor: Anchored, size: f32, layer: u8, font: Arc<FontAtlas>, layout: &Layout) -> Self {
+        Self {
+            text: Retained::new(Text::new(
+                text,
+                font,
+                layout.resolve(anchor),
+                0.0,
+                size,
+                layer,
+                TEXT_COLOUR,
+                UI_TEXT_OPACITY,
+                Align::Centre,
+                Some(wrap_width(layout))
+            )),
+            anchor
+        }
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text.set_text(text);
+    }
+}
+
+fn wrap_width(layout: &Layout) -> f32 {
+    2.0 * (layout.aspect - NET_LAYOUT_MARGIN)
+}
+
+// just something to read, the mouse goes straight through it
+impl HitTest for Label {
+    fn hit(&self, _p: Vector2<f32>) -> bool {
+        false
+    }
+}
+
+impl Widget for Label {
+    fn layer(&self) -> u8 {
+        self.text.layer()
+    }
+    fn focusable(&self) -> bool {
+        false
+    }
+    fn show(&mut self, _interaction: Interaction) {}
+    fn set_fade(&mut self, fade: f32) {
+        self.text.set_opacity(UI_TEXT_OPACITY * fade);
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        self.text.set_pos(layout.resolve(self.anchor));
+        self.text.set_wrap_width(Some(wrap_width(layout)));
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>) {
+        frame.shapes.push(&mut self.text);
+    }
+}
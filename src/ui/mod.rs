@@ -0,0 +1,310 @@
+pub mod button;
+pub mod label;
+pub mod toggle;
+pub mod slider;
+pub mod panel;
+
+use cgmath::Vector2;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{config::*, d2::{hittest::{topmost_hit, HitTest}, layout::Layout}, events::GameEvent, scenes::{Frame, Shared}, tween::{Property, Tweenable, Value}};
+
+/// Index of a widget within its `Ui`, in the order they were added
+pub type WidgetId = usize;
+
+/// Something the player did to a widget, for the scene that owns it to act on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UiMessage {
+    Clicked(WidgetId),
+    Toggled(WidgetId, bool),
+    Changed(WidgetId, f32)
+}
+
+/// What a key press did to a `Ui`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyResult {
+    Ignored, // the scene can do what it likes with the key
+    Handled, // moved focus or similar, nothing for the scene to do
+    Message(UiMessage)
+}
+
+/// How the player is interacting with a widget, which decides how it looks
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Interaction {
+    pub hovered: bool,
+    pub pressed: bool,
+    pub focused: bool,
+    pub disabled: bool
+}
+
+/// Opacity of a widget's background for how it's being interacted with, before fading
+pub fn fill_opacity(i: Interaction) -> f32 {
+    if i.disabled {
+        UI_DISABLED_OPACITY
+    } else if i.pressed {
+        UI_PRESSED_OPACITY
+    } else if i.hovered {
+        UI_HOVER_OPACITY
+    } else {
+        UI_IDLE_OPACITY
+    }
+}
+
+/// Whether a point is within a toggle or slider row centred on `centre`
+pub fn in_row(p: Vector2<f32>, centre: Vector2<f32>) -> bool {
+    (p.x - centre.x).abs() < UI_ROW_WIDTH * 0.5 && (p.y - centre.y).abs() < UI_ROW_HEIGHT * 0.5
+}
+
+/// Where a row's right aligned label goes, and the left edge of its control
+pub fn row_parts(centre: Vector2<f32>) -> (Vector2<f32>, f32) {
+    (Vector2::new(centre.x - UI_ROW_GAP, centre.y), centre.x + UI_ROW_GAP)
+}
+
+/// Hit testing decides whether the mouse is over the widget
+pub trait Widget: HitTest {
+    /// The layer of the widget's backmost part, which decides which widget is on top
+    fn layer(&self) -> u8;
+    /// Whether keyboard focus can land on the widget
+    fn focusable(&self) -> bool {
+        true
+    }
+    /// Updates the widget's looks, only called when something changes
+    fn show(&mut self, interaction: Interaction);
+    /// Multiplies the opacity of everything in the widget, for fading in and out
+    fn set_fade(&mut self, fade: f32);
+    /// A click, or enter while focused
+    fn activate(&mut self, _id: WidgetId) -> Option<UiMessage> {
+        None
+    }
+    /// The mouse moving while held down on the widget
+    fn drag(&mut self, _id: WidgetId, _p: Vector2<f32>) -> Option<UiMessage> {
+        None
+    }
+    /// Left or right while focused, returns None if the widget has no use for it
+    fn nudge(&mut self, _id: WidgetId, _dir: f32) -> Option<Option<UiMessage>> {
+        None
+    }
+    fn relayout(&mut self, layout: &Layout);
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>);
+}
+
+/// A set of widgets handled together, tracking which is hovered, held down and focused.
+/// Generic so a scene with only one kind of widget can still get at them directly.
+pub struct Ui<W: Widget + ?Sized = dyn Widget> {
+    widgets: Vec<Box<W>>,
+    looks: Vec<Interaction>,
+    hovered: Option<WidgetId>,
+    pressed: Option<WidgetId>,
+    focused: Option<WidgetId>,
+    fade: f32
+}
+
+impl<W: Widget + ?Sized> Ui<W> {
+    pub fn new() -> Self {
+        Self {
+            widgets: vec![],
+            looks: vec![],
+            hovered: None,
+            pressed: None,
+            focused: None,
+            fade: 1.0
+        }
+    }
+
+    pub fn add(&mut self, widget: Box<W>) -> WidgetId {
+        let mut widget = widget;
+        widget.show(Interaction::default());
+        widget.set_fade(self.fade);
+        self.widgets.push(widget);
+        self.looks.push(Interaction::default());
+        self.widgets.len() - 1
+    }
+
+    pub fn get_mut(&mut self, id: WidgetId) -> Option<&mut W> {
+        self.widgets.get_mut(id).map(|w| &mut **w)
+    }
+
+    pub fn set_disabled(&mut self, id: WidgetId, disabled: bool) {
+        if let Some(look) = self.looks.get(id) {
+            let look = Interaction { disabled, ..*look };
+            self.set_look(id, look);
+        }
+        if disabled {
+            self.forget(id);
+        }
+    }
+
+    pub fn set_all_disabled(&mut self, disabled: bool) {
+        for id in 0..self.widgets.len() {
+            self.set_disabled(id, disabled);
+        }
+    }
+
+    /// Drops any hover, press or focus, like after the widgets have been used
+    pub fn reset(&mut self) {
+        self.hovered = None;
+        self.pressed = None;
+        self.focused = None;
+        self.refresh();
+    }
+
+    /// Follows the mouse, returning a message if a widget was clicked or dragged
+    pub fn update(&mut self, shared: &mut Shared) -> Option<UiMessage> {
+        let mouse = shared.mouse;
+
+        let hovered = topmost_hit(
+            mouse.pos,
+            self.widgets.iter().enumerate()
+                .filter(|(i, _)| self.usable(*i))
+                .map(|(i, w)| (i, w.layer(), &**w))
+        );
+        if hovered != self.hovered && hovered.is_some() {
+            shared.events.emit(GameEvent::HoverChanged);
+        }
+        self.hovered = hovered;
+
+        let mut message = None;
+        if mouse.left_clicked() {
+            self.pressed = hovered;
+            if hovered.is_some() {
+                self.focused = None;
+            }
+        }
+        if let Some(p) = self.pressed {
+            if mouse.left {
+                message = self.widgets[p].drag(p, mouse.pos);
+            } else {
+                // only counts as a click if it's let go over the same widget
+                if hovered == Some(p) {
+                    message = self.widgets[p].activate(p);
+                }
+                self.pressed = None;
+            }
+        }
+
+        self.refresh();
+        message
+    }
+
+    /// Moves focus with tab and the arrow keys, and activates the focused widget with enter or space
+    pub fn input(&mut self, key: &Key) -> KeyResult {
+        let Key::Named(k) = key else {
+            return KeyResult::Ignored
+        };
+
+        let result = match k {
+            NamedKey::Tab | NamedKey::ArrowDown => self.move_focus(1),
+            NamedKey::ArrowUp => self.move_focus(-1),
+            NamedKey::ArrowLeft | NamedKey::ArrowRight => {
+                let dir = if *k == NamedKey::ArrowRight {1.0} else {-1.0};
+                let nudged = self.focused.and_then(|f| self.widgets[f].nudge(f, dir));
+                match nudged {
+                    Some(Some(m)) => KeyResult::Message(m),
+                    Some(None) => KeyResult::Handled,
+                    None => self.move_focus(dir as isize)
+                }
+            }
+            NamedKey::Enter | NamedKey::Space => match self.focused {
+                Some(f) => match self.widgets[f].activate(f) {
+                    Some(m) => KeyResult::Message(m),
+                    None => KeyResult::Handled
+                },
+                None => KeyResult::Ignored
+            }
+            _ => KeyResult::Ignored
+        };
+
+        self.refresh();
+        result
+    }
+
+    pub fn relayout(&mut self, layout: &Layout) {
+        for w in self.widgets.iter_mut() {
+            w.relayout(layout);
+        }
+    }
+
+    pub fn render<'f>(&'f mut self, frame: &mut Frame<'f>) {
+        for w in self.widgets.iter_mut() {
+            w.render(frame);
+        }
+    }
+
+    fn usable(&self, id: WidgetId) -> bool {
+        self.widgets[id].focusable() && !self.looks[id].disabled
+    }
+
+    // steps to the next usable widget, wrapping round
+    fn move_focus(&mut self, step: isize) -> KeyResult {
+        let n = self.widgets.len() as isize;
+        if n == 0 {
+            return KeyResult::Ignored
+        }
+        let mut i = match self.focused {
+            Some(f) => f as isize,
+            // so the first step lands on the first or last widget
+            None => if step > 0 {-1} else {n}
+        };
+        for _ in 0..n {
+            i = (i + step).rem_euclid(n);
+            if self.usable(i as usize) {
+                self.focused = Some(i as usize);
+                return KeyResult::Handled
+            }
+        }
+        KeyResult::Ignored
+    }
+
+    fn forget(&mut self, id: WidgetId) {
+        for slot in [&mut self.hovered, &mut self.pressed, &mut self.focused] {
+            if *slot == Some(id) {
+                *slot = None;
+            }
+        }
+    }
+
+    fn set_look(&mut self, id: WidgetId, look: Interaction) {
+        if self.looks[id] != look {
+            self.looks[id] = look;
+            self.widgets[id].show(look);
+        }
+    }
+
+    // works out how every widget should look, and tells the ones that changed
+    fn refresh(&mut self) {
+        for id in 0..self.widgets.len() {
+            let disabled = self.looks[id].disabled;
+            let look = Interaction {
+                hovered: !disabled && self.hovered == Some(id),
+                pressed: !disabled && self.pressed == Some(id) && self.hovered == Some(id),
+                focused: !disabled && self.focused == Some(id),
+                disabled
+            };
+            self.set_look(id, look);
+        }
+    }
+}
+
+impl<W: Widget + ?Sized> Default for Ui<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// fading the whole ui at once, everything in it fades together
+impl<W: Widget + ?Sized> Tweenable for Ui<W> {
+    fn get_property(&self, p: Property) -> Option<Value> {
+        match p {
+            Property::Opacity => Some(Value::Float(self.fade)),
+            _ => None
+        }
+    }
+    fn set_property(&mut self, p: Property, v: Value) {
+        if let (Property::Opacity, Value::Float(f)) = (p, v) {
+            self.fade = f;
+            for w in self.widgets.iter_mut() {
+                w.set_fade(f);
+            }
+        }
+    }
+}
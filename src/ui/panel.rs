@@ -0,0 +1,72 @@
+use cgmath::Vector2;
+
+use crate::{d2::{hittest::HitTest, layout::{Anchor, Anchored, Layout}, retained::Retained, roundedrect::RoundedRect, shape::Shape, tessellate::Style}, scenes::Frame};
+
+use super::{Interaction, Widget};
+
+/// A plain backing behind other widgets, or over the whole window to dim what's underneath
+pub struct Panel {
+    bg: Retained<RoundedRect>,
+    anchor: Anchored,
+    opacity: f32, // before fading
+    covers_window: bool
+}
+
+impl Panel {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        anchor: Anchored,
+        width: f32,
+        height: f32,
+        radius: f32,
+        layer: u8,
+        colour: [f32;3],
+        opacity: f32,
+        layout: &Layout
+    ) -> Self {
+        Self {
+            bg: Retained::new(RoundedRect::new(width, height, radius, layout.resolve(anchor), Style::Fill, layer, colour, opacity)),
+            anchor,
+            opacity,
+            covers_window: false
+        }
+    }
+
+    /// A panel filling the window, kept that way as it resizes
+    pub fn covering(layer: u8, colour: [f32;3], opacity: f32, layout: &Layout) -> Self {
+        let mut p = Self::new(Anchored::new(Anchor::Centre, [0.0, 0.0]), 0.0, 0.0, 0.0, layer, colour, opacity, layout);
+        p.covers_window = true;
+        p.relayout(layout);
+        p
+    }
+}
+
+// the widgets on it take the mouse, not the panel itself
+impl HitTest for Panel {
+    fn hit(&self, _p: Vector2<f32>) -> bool {
+        false
+    }
+}
+
+impl Widget for Panel {
+    fn layer(&self) -> u8 {
+        self.bg.layer
+    }
+    fn focusable(&self) -> bool {
+        false
+    }
+    fn show(&mut self, _interaction: Interaction) {}
+    fn set_fade(&mut self, fade: f32) {
+        self.bg.set_opacity(self.opacity * fade);
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        if self.covers_window {
+            self.bg.width = layout.aspect * 2.0;
+            self.bg.height = 2.0;
+        }
+        self.bg.set_pos(layout.resolve(self.anchor));
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>) {
+        frame.shapes.push(&mut self.bg);
+    }
+}
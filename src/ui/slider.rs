@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use cgmath::Vector2;
+
+use crate::{config::*, d2::{circle::Circle, hittest::HitTest, font::FontAtlas, layout::{Anchored, Layout}, retained::Retained, roundedrect::RoundedRect, shape::Shape, tessellate::Style, text::{Align, Text}}, scenes::Frame};
+
+use super::{fill_opacity, in_row, row_parts, Interaction, UiMessage, Widget, WidgetId};
+
+/// A labelled value from 0 to 1, set by dragging the knob along a track or with the arrow keys
+pub struct Slider {
+    label: Retained<Text>,
+    track: Retained<RoundedRect>,
+    fill: Retained<RoundedRect>, // the part of the track left of the knob
+    knob: Retained<Circle>,
+    ring: Retained<RoundedRect>, // shown while focused
+    anchor: Anchored, // centre of the whole row
+    centre: Vector2<f32>,
+    value: f32,
+    interaction: Interaction,
+    fade: f32
+}
+
+impl Slider {
+    pub fn new(label: &str, value: f32, anchor: Anchored, layer: u8, font: Arc<FontAtlas>, layout: &Layout) -> Self {
+        let (w, h) = (UI_SLIDER_WIDTH, UI_SLIDER_HEIGHT);
+        let centre = layout.resolve(anchor);
+        let mut s = Self {
+            label: Retained::new(Text::new(label, font, centre, 0.0, MENU_LABEL_SIZE, layer + 1, TEXT_COLOUR, 0.0, Align::Right, None)),
+            track: Retained::new(RoundedRect::new(w, h, h * 0.5, centre, Style::Fill, layer, BUTTON_COLOUR, 0.0)),
+            fill: Retained::new(RoundedRect::new(0.0, h, h * 0.5, centre, Style::Fill, layer + 1, UI_ACCENT_COLOUR, 0.0)),
+            knob: Retained::new(Circle::new(centre, UI_SLIDER_KNOB_RADIUS, Style::Fill, layer + 2, UI_ACCENT_COLOUR, 0.0)),
+            ring: Retained::new(RoundedRect::new(
+                w + UI_SLIDER_KNOB_RADIUS * 2.0 + UI_FOCUS_RING_GAP * 2.0,
+                UI_SLIDER_KNOB_RADIUS * 2.0 + UI_FOCUS_RING_GAP * 2.0,
+                UI_SLIDER_KNOB_RADIUS + UI_FOCUS_RING_GAP,
+                centre,
+                Style::Stroke(UI_FOCUS_RING_WIDTH),
+                layer,
+                UI_ACCENT_COLOUR,
+                0.0
+            )),
+            anchor,
+            centre,
+            value: value.clamp(0.0, 1.0),
+            interaction: Interaction::default(),
+            fade: 1.0
+        };
+        s.place();
+        s
+    }
+
+    #[allow(dead_code)] // settings are only changed by the player so far
+    pub fn set(&mut self, v: f32) {
+        let v = v.clamp(0.0, 1.0);
+        if self.value != v {
+            self.value = v;
+            self.place();
+        }
+    }
+
+    // sets the value, returning a message if it changed
+    fn change(&mut self, id: WidgetId, v: f32) -> Option<UiMessage> {
+        let v = v.clamp(0.0, 1.0);
+        if self.value == v {
+            return None
+        }
+        self.value = v;
+        self.place();
+        Some(UiMessage::Changed(id, v))
+    }
+
+    fn place(&mut self) {
+        let (label_pos, left) = row_parts(self.centre);
+        let w = UI_SLIDER_WIDTH;
+        // the track is inset by the knob's radius so the knob stays inside the row at either end
+        let left = left + UI_SLIDER_KNOB_RADIUS;
+        let filled = w * self.value;
+        self.label.set_pos(label_pos);
+        self.track.set_pos(Vector2::new(left + w * 0.5, self.centre.y));
+        self.ring.set_pos(Vector2::new(left + w * 0.5, self.centre.y));
+        self.fill.width = filled;
+        self.fill.set_pos(Vector2::new(left + filled * 0.5, self.centre.y));
+        self.knob.set_pos(Vector2::new(left + filled, self.centre.y));
+    }
+
+    // the value at a point along the track
+    fn value_at(&self, x: f32) -> f32 {
+        let (_, left) = row_parts(self.centre);
+        (x - left - UI_SLIDER_KNOB_RADIUS) / UI_SLIDER_WIDTH
+    }
+
+    fn restyle(&mut self) {
+        let i = self.interaction;
+        self.track.set_opacity(fill_opacity(i) * self.fade);
+        let accent = if i.disabled {0.3} else {0.9};
+        self.fill.set_opacity(accent * self.fade);
+        self.knob.opacity = accent * self.fade;
+        self.ring.set_opacity(if i.focused {UI_TEXT_OPACITY * self.fade} else {0.0});
+        let text = if i.disabled {UI_TEXT_OPACITY * 0.5} else {UI_TEXT_OPACITY};
+        self.label.set_opacity(text * self.fade);
+    }
+}
+
+impl HitTest for Slider {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        in_row(p, self.centre)
+    }
+}
+
+impl Widget for Slider {
+    fn layer(&self) -> u8 {
+        self.track.layer
+    }
+    fn show(&mut self, interaction: Interaction) {
+        self.interaction = interaction;
+        self.restyle();
+    }
+    fn set_fade(&mut self, fade: f32) {
+        self.fade = fade;
+        self.restyle();
+    }
+    fn drag(&mut self, id: WidgetId, p: Vector2<f32>) -> Option<UiMessage> {
+        let v = self.value_at(p.x);
+        self.change(id, v)
+    }
+    fn nudge(&mut self, id: WidgetId, dir: f32) -> Option<Option<UiMessage>> {
+        // snapped to whole steps so repeated presses land on round numbers
+        let steps = (self.value / UI_SLIDER_STEP).round() + dir.signum();
+        Some(self.change(id, steps * UI_SLIDER_STEP))
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        self.centre = layout.resolve(self.anchor);
+        self.place();
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>) {
+        frame.shapes.push(&mut self.ring);
+        frame.shapes.push(&mut self.track);
+        frame.shapes.push(&mut self.fill);
+        frame.shapes.push(&mut self.knob);
+        frame.shapes.push(&mut self.label);
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use cgmath::Vector2;
+
+use crate::{config::*, d2::{circle::Circle, hittest::HitTest, font::FontAtlas, layout::{Anchored, Layout}, retained::Retained, roundedrect::RoundedRect, shape::Shape, tessellate::Style, text::{Align, Text}}, scenes::Frame};
+
+use super::{fill_opacity, in_row, row_parts, Interaction, UiMessage, Widget, WidgetId};
+
+/// A labelled on/off switch, a pill with a knob that sits at the right end when on
+pub struct Toggle {
+    label: Retained<Text>,
+    track: Retained<RoundedRect>,
+    knob: Retained<Circle>,
+    ring: Retained<RoundedRect>, // shown while focused
+    anchor: Anchored, // centre of the whole row
+    centre: Vector2<f32>,
+    on: bool,
+    interaction: Interaction,
+    fade: f32
+}
+
+impl Toggle {
+    pub fn new(label: &str, on: bool, anchor: Anchored, layer: u8, font: Arc<FontAtlas>, layout: &Layout) -> Self {
+        let (w, h) = (UI_TOGGLE_WIDTH, UI_TOGGLE_HEIGHT);
+        let centre = layout.resolve(anchor);
+        let mut t = Self {
+            label: Retained::new(Text::new(label, font, centre, 0.0, MENU_LABEL_SIZE, layer + 1, TEXT_COLOUR, 0.0, Align::Right, None)),
+            track: Retained::new(RoundedRect::new(w, h, h * 0.5, centre, Style::Fill, layer, BUTTON_COLOUR, 0.0)),
+            knob: Retained::new(Circle::new(centre, h * 0.35, Style::Fill, layer + 1, [1.0;3], 0.0)),
+            ring: Retained::new(RoundedRect::new(
+                w + UI_FOCUS_RING_GAP * 2.0,
+                h + UI_FOCUS_RING_GAP * 2.0,
+                h * 0.5 + UI_FOCUS_RING_GAP,
+                centre,
+                Style::Stroke(UI_FOCUS_RING_WIDTH),
+                layer,
+                UI_ACCENT_COLOUR,
+                0.0
+            )),
+            anchor,
+            centre,
+            on,
+            interaction: Interaction::default(),
+            fade: 1.0
+        };
+        t.place();
+        t
+    }
+
+    #[allow(dead_code)] // settings are only changed by the player so far
+    pub fn set(&mut self, on: bool) {
+        if self.on != on {
+            self.on = on;
+            self.place();
+            self.restyle();
+        }
+    }
+
+    // lines the parts up along the row, with the knob at whichever end matches the value
+    fn place(&mut self) {
+        let (label_pos, left) = row_parts(self.centre);
+        let (w, h) = (UI_TOGGLE_WIDTH, UI_TOGGLE_HEIGHT);
+        let track_pos = Vector2::new(left + w * 0.5, self.centre.y);
+        let knob_x = if self.on {left + w - h * 0.5} else {left + h * 0.5};
+        self.label.set_pos(label_pos);
+        self.track.set_pos(track_pos);
+        self.ring.set_pos(track_pos);
+        self.knob.set_pos(Vector2::new(knob_x, self.centre.y));
+    }
+
+    fn restyle(&mut self) {
+        let i = self.interaction;
+        if self.on {
+            self.track.colour = UI_ACCENT_COLOUR;
+            self.track.set_opacity((fill_opacity(i) + 0.5) * self.fade);
+        } else {
+            self.track.colour = BUTTON_COLOUR;
+            self.track.set_opacity(fill_opacity(i) * self.fade);
+        }
+        self.knob.opacity = 0.9 * self.fade;
+        self.ring.set_opacity(if i.focused {UI_TEXT_OPACITY * self.fade} else {0.0});
+        let text = if i.disabled {UI_TEXT_OPACITY * 0.5} else {UI_TEXT_OPACITY};
+        self.label.set_opacity(text * self.fade);
+    }
+}
+
+impl HitTest for Toggle {
+    fn hit(&self, p: Vector2<f32>) -> bool {
+        in_row(p, self.centre)
+    }
+}
+
+impl Widget for Toggle {
+    fn layer(&self) -> u8 {
+        self.track.layer
+    }
+    fn show(&mut self, interaction: Interaction) {
+        self.interaction = interaction;
+        self.restyle();
+    }
+    fn set_fade(&mut self, fade: f32) {
+        self.fade = fade;
+        self.restyle();
+    }
+    fn activate(&mut self, id: WidgetId) -> Option<UiMessage> {
+        self.on = !self.on;
+        self.place();
+        self.restyle();
+        Some(UiMessage::Toggled(id, self.on))
+    }
+    fn nudge(&mut self, id: WidgetId, dir: f32) -> Option<Option<UiMessage>> {
+        // right turns it on and left off, like sliding the knob across
+        let on = dir > 0.0;
+        if on == self.on {
+            return Some(None)
+        }
+        Some(self.activate(id))
+    }
+    fn relayout(&mut self, layout: &Layout) {
+        self.centre = layout.resolve(self.anchor);
+        self.place();
+    }
+    fn render<'f>(&'f mut self, frame: &mut Frame<'f>) {
+        frame.shapes.push(&mut self.ring);
+        frame.shapes.push(&mut self.track);
+        frame.shapes.push(&mut self.knob);
+        frame.shapes.push(&mut self.label);
+    }
+}